@@ -0,0 +1,229 @@
+use ethers::types::{I256, U256, U512};
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Decimal places used for prices unless configured otherwise.
+pub const DEFAULT_PRICE_SCALE: u32 = 6;
+
+/// Largest `--price-scale` accepted: well past any token's decimals, far from overflowing U256.
+pub const MAX_PRICE_SCALE: u32 = 18;
+
+/// `10^exp` as a U256, None if it does not fit (exp > 77).
+fn pow10(exp: u32) -> Option<U256> {
+    (exp <= 77).then(|| U256::exp10(exp as usize))
+}
+
+/// `10^exp` as a U512, None if it does not fit (exp > 154).
+fn pow10_wide(exp: u32) -> Option<U512> {
+    (exp <= 154).then(|| U512::exp10(exp as usize))
+}
+
+/// Exact fixed-point decimal: `raw / 10^decimals`.
+///
+/// Token amounts keep their on-chain base units as `raw`, so sums and differences reconcile
+/// to the last unit. Division is exact up to the requested scale and truncates beyond it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Amount {
+    raw: U256,
+    decimals: u32,
+}
+
+impl Amount {
+    pub fn new(raw: U256, decimals: u32) -> Self {
+        Self { raw, decimals }
+    }
+
+    pub fn zero(decimals: u32) -> Self {
+        Self::new(U256::zero(), decimals)
+    }
+
+    pub fn raw(&self) -> U256 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    /// `numerator / denominator` truncated to `scale` decimals. None if the denominator is zero
+    /// or the result (or an intermediate power of ten) does not fit.
+    pub fn ratio(numerator: Amount, denominator: Amount, scale: u32) -> Option<Amount> {
+        if denominator.is_zero() {
+            return None;
+        }
+        // (n / 10^nd) / (d / 10^dd) * 10^scale = n * 10^(dd + scale) / (d * 10^nd)
+        let num = U512::from(numerator.raw).checked_mul(pow10_wide(denominator.decimals.checked_add(scale)?)?)?;
+        let den = U512::from(denominator.raw).checked_mul(pow10_wide(numerator.decimals)?)?;
+        let raw = U256::try_from(num / den).ok()?;
+        Some(Amount::new(raw, scale))
+    }
+
+    /// Same value with `decimals` places; truncates when reducing precision. None if the
+    /// result does not fit.
+    pub fn rescale(&self, decimals: u32) -> Option<Amount> {
+        let raw = match decimals.cmp(&self.decimals) {
+            Ordering::Equal => self.raw,
+            Ordering::Greater => self.raw.checked_mul(pow10(decimals - self.decimals)?)?,
+            // Dividing by more than U256 can hold leaves nothing
+            Ordering::Less => pow10(self.decimals - decimals).map_or(U256::zero(), |divisor| self.raw / divisor),
+        };
+        Some(Amount::new(raw, decimals))
+    }
+
+    pub fn checked_add(&self, other: Amount) -> Option<Amount> {
+        let decimals = self.decimals.max(other.decimals);
+        let raw = self.rescale(decimals)?.raw.checked_add(other.rescale(decimals)?.raw)?;
+        Some(Amount::new(raw, decimals))
+    }
+
+    /// None if the result would be negative.
+    #[cfg(test)]
+    pub fn checked_sub(&self, other: Amount) -> Option<Amount> {
+        let decimals = self.decimals.max(other.decimals);
+        let raw = self.rescale(decimals)?.raw.checked_sub(other.rescale(decimals)?.raw)?;
+        Some(Amount::new(raw, decimals))
+    }
+
+    /// Product truncated to `scale` decimals.
+    pub fn checked_mul(&self, other: Amount, scale: u32) -> Option<Amount> {
+        let product = Amount::new(self.raw.checked_mul(other.raw)?, self.decimals.checked_add(other.decimals)?);
+        product.rescale(scale)
+    }
+
+    /// Human-friendly rendering: exact value with trailing zeros trimmed.
+    pub fn to_trimmed_string(self) -> String {
+        let full = self.to_string();
+        if !full.contains('.') {
+            return full;
+        }
+        let trimmed = full.trim_end_matches('0');
+        if trimmed.ends_with('.') {
+            format!("{}0", trimmed)
+        } else {
+            trimmed.to_string()
+        }
+    }
+
+    fn aligned(&self, other: &Amount) -> (U512, U512) {
+        let decimals = self.decimals.max(other.decimals);
+        // Past 10^78 the scaled side exceeds any U256 raw, so saturating keeps the order exact
+        let scale = |a: &Amount| U512::from(a.raw).saturating_mul(U512::exp10((decimals - a.decimals).min(78) as usize));
+        (scale(self), scale(other))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.raw.to_string();
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return write!(f, "{}", digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+        write!(f, "{}.{}", int_part, frac_part)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = anyhow::Error;
+
+    /// Parses a plain decimal string ("12", "0.045"); the number of fractional digits becomes `decimals`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (int_part, frac_part) = s.split_once('.').unwrap_or((s, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(anyhow::anyhow!("Empty decimal string"));
+        }
+        let digits = format!("{}{}", int_part, frac_part);
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(anyhow::anyhow!("Invalid decimal string: {}", s));
+        }
+        let raw = U256::from_dec_str(&digits).map_err(|e| anyhow::anyhow!("Invalid decimal string {}: {:?}", s, e))?;
+        Ok(Amount::new(raw, frac_part.len() as u32))
+    }
+}
+
+impl PartialEq for Amount {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = self.aligned(other);
+        a == b
+    }
+}
+
+impl Eq for Amount {}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = self.aligned(other);
+        a.cmp(&b)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Signed counterpart of `Amount` for profits and losses: `raw / 10^decimals`, `raw` may be negative.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignedAmount {
+    raw: I256,
+    decimals: u32,
+}
+
+impl SignedAmount {
+    pub fn new(raw: I256, decimals: u32) -> Self {
+        Self { raw, decimals }
+    }
+
+    pub fn raw(&self) -> I256 {
+        self.raw
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.raw.is_negative()
+    }
+
+    /// Like `Amount::to_trimmed_string`, with a leading `-` for losses.
+    pub fn to_trimmed_string(self) -> String {
+        let magnitude = Amount::new(self.raw.unsigned_abs(), self.decimals).to_trimmed_string();
+        if self.is_negative() { format!("-{}", magnitude) } else { magnitude }
+    }
+}
+
+impl From<Amount> for SignedAmount {
+    fn from(amount: Amount) -> Self {
+        Self::new(I256::from_raw(amount.raw), amount.decimals)
+    }
+}
+
+impl fmt::Display for SignedAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = Amount::new(self.raw.unsigned_abs(), self.decimals);
+        if self.is_negative() { write!(f, "-{}", magnitude) } else { write!(f, "{}", magnitude) }
+    }
+}
+
+impl Serialize for SignedAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+#[path = "amount_test.rs"]
+mod amount_test;
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::amount::{Amount, SignedAmount};
+    use crate::utils::calculate_price;
+    use ethers::types::{I256, U256};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_display_keeps_every_base_unit() {
+        assert_eq!(Amount::new(U256::from(1_250_000u64), 6).to_string(), "1.250000");
+        assert_eq!(Amount::new(U256::from(5u64), 6).to_string(), "0.000005");
+        assert_eq!(Amount::new(U256::from(42u64), 0).to_string(), "42");
+
+        // Far beyond f64's 53-bit mantissa
+        let huge = U256::from_dec_str("123456789012345678901234567").unwrap();
+        assert_eq!(Amount::new(huge, 6).to_string(), "123456789012345678901.234567");
+    }
+
+    #[test]
+    fn test_ratio_truncates_to_scale() {
+        // 1 USDC for 3 tokens
+        let price = calculate_price(U256::from(1_000_000u64), 6, U256::from(3_000_000u64), 6, 6);
+        assert_eq!(price.to_string(), "0.333333");
+
+        let precise = calculate_price(U256::from(1_000_000u64), 6, U256::from(3_000_000u64), 6, 18);
+        assert_eq!(precise.to_string(), "0.333333333333333333");
+
+        // Sub-cent fill: 1 base unit of USDC for 1000 tokens
+        let tiny = calculate_price(U256::from(1u64), 6, U256::from(1_000_000_000u64), 6, 12);
+        assert_eq!(tiny.to_string(), "0.000000001000");
+
+        // Mixed decimals and zero denominator
+        let mixed = calculate_price(U256::from(500_000u64), 6, U256::exp10(18), 18, 6);
+        assert_eq!(mixed.to_string(), "0.500000");
+        assert!(calculate_price(U256::from(1u64), 6, U256::zero(), 6, 6).is_zero());
+    }
+
+    #[test]
+    fn test_out_of_range_scales_return_none() {
+        let one = Amount::from_str("1").unwrap();
+        assert!(Amount::ratio(one, one, 200).is_none());
+        assert!(Amount::ratio(one, one, u32::MAX).is_none());
+        assert!(calculate_price(U256::from(1u64), 6, U256::from(3u64), 6, 200).is_zero());
+
+        assert!(one.rescale(200).is_none());
+        assert!(Amount::new(U256::MAX, 200).rescale(0).unwrap().is_zero());
+        assert!(Amount::new(U256::one(), u32::MAX).checked_mul(Amount::from_str("1.0").unwrap(), 6).is_none());
+
+        // Comparisons across wildly different decimals stay exact
+        assert!(Amount::new(U256::one(), 0) > Amount::new(U256::MAX, 200));
+    }
+
+    #[test]
+    fn test_sums_reconcile_exactly() {
+        let fills = ["0.1", "0.2", "0.000001"];
+        let total = fills.iter()
+            .map(|f| Amount::from_str(f).unwrap())
+            .try_fold(Amount::zero(6), |acc, a| acc.checked_add(a))
+            .unwrap();
+        assert_eq!(total.to_string(), "0.300001");
+        assert_eq!(total, Amount::from_str("0.300001000").unwrap());
+
+        assert_eq!(total.checked_sub(Amount::from_str("0.3").unwrap()).unwrap().raw(), U256::from(1u64));
+        assert!(Amount::from_str("1").unwrap().checked_sub(total.checked_mul(Amount::from_str("4").unwrap(), 6).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_parse_and_trim() {
+        let amount = Amount::from_str("12.500").unwrap();
+        assert_eq!(amount.decimals(), 3);
+        assert_eq!(amount.to_trimmed_string(), "12.5");
+        assert_eq!(Amount::from_str("3.000").unwrap().to_trimmed_string(), "3.0");
+        assert!(Amount::from_str("1.2.3").is_err());
+        assert!(Amount::from_str("-1").is_err());
+    }
+
+    #[test]
+    fn test_signed_amount_renders_losses() {
+        let loss = SignedAmount::new(I256::from(-1_500_000i64), 6);
+        assert_eq!(loss.to_string(), "-1.500000");
+        assert_eq!(loss.to_trimmed_string(), "-1.5");
+        assert_eq!(SignedAmount::from(Amount::from_str("0.25").unwrap()).to_trimmed_string(), "0.25");
+        assert_eq!(serde_json::to_string(&SignedAmount::new(I256::from(-5i64), 6)).unwrap(), "\"-0.000005\"");
+    }
+}
@@ -0,0 +1,153 @@
+use crate::db::{Database, TradeFilter};
+use crate::models::TradeSide;
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Trades returned per page when `limit` is not given.
+pub const DEFAULT_PAGE_LIMIT: u64 = 100;
+pub const MAX_PAGE_LIMIT: u64 = 1000;
+
+// Read-only JSON API over the indexer database:
+//   GET /markets/{slug}
+//   GET /markets/{slug}/trades?limit&offset&maker&taker&side&outcome&from&to
+//   GET /markets/by-condition/{condition_id}
+//   GET /trades/{tx_hash}
+// Markets and trades use the same camelCase shape as the CLI's JSON output.
+
+type ApiResult = std::result::Result<Value, (StatusCode, String)>;
+
+/// Binds `addr` and returns the bound address (useful with port 0) and the server future.
+pub fn bind(db: Database, addr: SocketAddr) -> Result<(SocketAddr, impl Future<Output = Result<()>>)> {
+    // rusqlite connections are not Sync; requests take turns on the one connection
+    let db = Arc::new(Mutex::new(db));
+    let make_service = make_service_fn(move |_conn| {
+        let db = db.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let db = db.clone();
+                async move { Ok::<_, Infallible>(handle(db, req).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    let local_addr = server.local_addr();
+    Ok((local_addr, async move { server.await.map_err(anyhow::Error::from) }))
+}
+
+async fn handle(db: Arc<Mutex<Database>>, req: Request<Body>) -> Response<Body> {
+    let result = if req.method() != Method::GET {
+        Err((StatusCode::METHOD_NOT_ALLOWED, "Only GET is supported".to_string()))
+    } else {
+        let path = req.uri().path().to_string();
+        let query = req.uri().query().unwrap_or("").to_string();
+        // SQLite calls block: run them (and the wait for the connection) off the async workers
+        tokio::task::spawn_blocking(move || {
+            let db = db.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            route(&db, &path, &query)
+        }).await.unwrap_or_else(|e| Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())))
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (StatusCode::OK, body),
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_default()
+}
+
+/// Dispatches one GET request. `query` is the raw query string without `?`.
+pub fn route(db: &Database, path: &str, query: &str) -> ApiResult {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+    let params = query_params(query);
+
+    match segments.as_slice() {
+        ["markets", "by-condition", condition_id] => {
+            let market = db.market_by_condition_id(condition_id).map_err(internal)?;
+            to_json(market.ok_or_else(|| not_found("market", condition_id))?)
+        }
+        ["markets", slug] => {
+            let market = db.market_by_slug(slug).map_err(internal)?;
+            to_json(market.ok_or_else(|| not_found("market", slug))?)
+        }
+        ["markets", slug, "trades"] => {
+            let market = db.market_by_slug(slug).map_err(internal)?.ok_or_else(|| not_found("market", slug))?;
+            let filter = TradeFilter { condition_id: Some(market.condition_id), ..trade_filter(&params)? };
+            to_json(db.trades(&filter).map_err(internal)?)
+        }
+        ["trades", tx_hash] => {
+            let filter = TradeFilter { tx_hash: Some(tx_hash.to_string()), ..trade_filter(&params)? };
+            let trades = db.trades(&filter).map_err(internal)?;
+            if trades.is_empty() {
+                return Err(not_found("trades for transaction", tx_hash));
+            }
+            to_json(trades)
+        }
+        _ => Err((StatusCode::NOT_FOUND, format!("No route for {}", path))),
+    }
+}
+
+/// Pagination and the maker/taker/side/outcome/time filters shared by the trade endpoints.
+fn trade_filter(params: &HashMap<String, String>) -> std::result::Result<TradeFilter, (StatusCode, String)> {
+    let number = |key: &str| -> std::result::Result<Option<u64>, (StatusCode, String)> {
+        params.get(key)
+            .map(|v| v.parse::<u64>().map_err(|_| bad_request(&format!("{} must be a non-negative integer", key))))
+            .transpose()
+    };
+    let side = match params.get("side").map(|s| s.to_uppercase()).as_deref() {
+        None => None,
+        Some("BUY") => Some(TradeSide::BUY),
+        Some("SELL") => Some(TradeSide::SELL),
+        Some(_) => return Err(bad_request("side must be BUY or SELL")),
+    };
+
+    Ok(TradeFilter {
+        maker: params.get("maker").cloned(),
+        taker: params.get("taker").cloned(),
+        side,
+        outcome: params.get("outcome").cloned(),
+        from_time: number("from")?,
+        to_time: number("to")?,
+        limit: Some(number("limit")?.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT)),
+        offset: number("offset")?.unwrap_or(0),
+        ..TradeFilter::default()
+    })
+}
+
+/// `a=1&b=2` into a map, percent-decoded (`+` is a space); empty values are dropped.
+fn query_params(query: &str) -> HashMap<String, String> {
+    form_urlencoded::parse(query.as_bytes())
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect()
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> ApiResult {
+    serde_json::to_value(value).map_err(|e| internal(e.into()))
+}
+
+fn not_found(what: &str, id: &str) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("No {} {}", what, id))
+}
+
+fn bad_request(message: &str) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, message.to_string())
+}
+
+fn internal(error: anyhow::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+}
+
+#[cfg(test)]
+#[path = "api_test.rs"]
+mod api_test;
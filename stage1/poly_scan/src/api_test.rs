@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use crate::amount::Amount;
+    use crate::api::{bind, route};
+    use crate::db::{Batch, Database};
+    use crate::indexer::INDEXER_SYNC_KEY;
+    use crate::models::{GammaMarket, GammaStatus, MarketInfo, TradeOutput, TradeSide};
+    use hyper::StatusCode;
+    use serde_json::Value;
+    use std::str::FromStr;
+
+    const CONDITION_ID: &str = "0x00000000000000000000000000000000000000000000000000000000000000c0";
+
+    fn market() -> MarketInfo {
+        MarketInfo {
+            condition_id: CONDITION_ID.to_string(),
+            title: Some("Will it rain?".to_string()),
+            ..MarketInfo::fixture("0xabcd", "0xabce")
+        }
+    }
+
+    fn gamma(market: &MarketInfo) -> GammaMarket {
+        GammaMarket {
+            id: "1".to_string(),
+            slug: "will-it-rain".to_string(),
+            question: market.title.clone(),
+            description: None,
+            condition_id: market.condition_id.clone(),
+            question_id: Some(market.question_id.clone()),
+            clob_token_ids: Vec::new(),
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+            neg_risk: false,
+            status: GammaStatus::Active,
+            resolved_by: None,
+            event_slug: None,
+        }
+    }
+
+    fn trade(log_index: u64, block_number: u64, maker: &str, token_id: &str, side: TradeSide) -> TradeOutput {
+        TradeOutput {
+            log_index,
+            match_log_index: Some(log_index + 10),
+            timestamp: Some(1_700_000_000 + (block_number - 100) * 4),
+            maker: maker.to_string(),
+            fee: "1000".to_string(),
+            fee_usdc: Amount::from_str("0.001000").unwrap(),
+            fee_bps: Amount::from_str("20.00").unwrap(),
+            ..TradeOutput::fixture(token_id).at_block(block_number).with_side(side)
+        }
+    }
+
+    fn database() -> Database {
+        let mut db = Database::open_in_memory().unwrap();
+        let batch = Batch {
+            markets: vec![market()],
+            trades: vec![
+                trade(1, 100, "0x11", "0xabcd", TradeSide::BUY),
+                trade(2, 100, "0x12", "0xabce", TradeSide::SELL),
+                trade(1, 200, "0x11", "0xabcd", TradeSide::SELL),
+            ],
+            ..Batch::default()
+        };
+        db.write_batch(INDEXER_SYNC_KEY, &batch, 200, None).unwrap();
+        db.write_gamma_markets(&[(market(), gamma(&market()))]).unwrap();
+        db
+    }
+
+    fn log_indexes(body: &Value) -> Vec<(u64, u64)> {
+        body.as_array().unwrap().iter()
+            .map(|t| (t["blockNumber"].as_u64().unwrap(), t["logIndex"].as_u64().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_markets_by_slug_and_condition() {
+        let db = database();
+
+        let market = route(&db, "/markets/will-it-rain", "").unwrap();
+        assert_eq!(market["conditionId"], CONDITION_ID);
+        assert_eq!(market["title"], "Will it rain?");
+        assert_eq!(market["outcomeSlotCount"], 2);
+        assert_eq!(market["outcomes"][1]["positionId"], "0xabce");
+
+        let by_condition = route(&db, &format!("/markets/by-condition/{}", CONDITION_ID.to_uppercase().replace("0X", "0x")), "").unwrap();
+        assert_eq!(by_condition, market);
+
+        let (status, _) = route(&db, "/markets/unknown", "").unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_market_trades_filters_and_pagination() {
+        let db = database();
+        let trades = |query: &str| log_indexes(&route(&db, "/markets/will-it-rain/trades", query).unwrap());
+
+        assert_eq!(trades(""), vec![(100, 1), (100, 2), (200, 1)]);
+        assert_eq!(trades("limit=1&offset=1"), vec![(100, 2)]);
+        assert_eq!(trades("maker=0x11&side=sell"), vec![(200, 1)]);
+        assert_eq!(trades("outcome=no"), vec![(100, 2)]);
+        assert_eq!(trades("outcome=N%6F&side=%53ELL"), vec![(100, 2)]);
+        assert_eq!(trades("from=1700000100&to=1700000500"), vec![(200, 1)]);
+
+        // Stored fills round-trip to the TradeOutput shape
+        let body = route(&db, "/trades/0x64", "").unwrap();
+        let fill = &body[0];
+        assert_eq!(fill["price"], "0.400000");
+        assert_eq!(fill["feeBps"], "20.00");
+        assert_eq!(fill["matchLogIndex"], 11);
+        assert_eq!(fill["venue"], "BINARY");
+        assert_eq!(log_indexes(&body), vec![(100, 1), (100, 2)]);
+
+        let (status, _) = route(&db, "/markets/will-it-rain/trades", "side=hold").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = route(&db, "/trades/0xdead", "").unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_serves_json_over_http() {
+        let (addr, server) = bind(database(), "127.0.0.1:0".parse().unwrap()).unwrap();
+        let server = tokio::spawn(server);
+
+        let response = reqwest::get(format!("http://{}/markets/will-it-rain/trades?limit=2", addr)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(log_indexes(&body), vec![(100, 1), (100, 2)]);
+
+        let missing = reqwest::get(format!("http://{}/nope", addr)).await.unwrap();
+        assert_eq!(missing.status(), 404);
+        let error: Value = missing.json().await.unwrap();
+        assert!(error["error"].as_str().unwrap().contains("/nope"));
+
+        server.abort();
+    }
+}
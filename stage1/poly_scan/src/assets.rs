@@ -0,0 +1,150 @@
+use crate::consts::*;
+use crate::utils::{decode_log_data, format_address, log_topics, token_uint, token_uint_array, uint_array_type};
+use anyhow::Result;
+use ethers::abi::ParamType;
+use ethers::types::{Address, Log, H256, U256};
+use ethers::utils::keccak256;
+use std::str::FromStr;
+
+// The exchanges trade exactly two kinds of asset: asset ID 0 is the collateral (USDC, or the
+// NegRiskAdapter's wrapped USDC on the neg-risk exchange) and any other ID is a CTF ERC-1155
+// position. Positions are minted 1:1 against collateral, so both sides share its decimals and
+// nothing needs to be looked up on-chain to price a fill.
+
+/// One leg of a fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asset {
+    Collateral,
+    /// CTF position ID (the ERC-1155 token ID)
+    Position(U256),
+}
+
+impl Asset {
+    pub fn from_id(id: U256) -> Self {
+        if id.is_zero() { Asset::Collateral } else { Asset::Position(id) }
+    }
+
+    /// The ID as it appears in `OrderFilled` (0 for collateral).
+    pub fn id(&self) -> U256 {
+        match self {
+            Asset::Collateral => U256::zero(),
+            Asset::Position(id) => *id,
+        }
+    }
+
+    /// Base-unit decimals; positions inherit the collateral's.
+    pub fn decimals(&self) -> u32 {
+        COLLATERAL_DECIMALS
+    }
+}
+
+/// The asset fields of an `OrderFilled` log: what the order's maker gave and got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillAssets {
+    pub maker: Address,
+    pub maker_asset: Asset,
+    pub taker_asset: Asset,
+    pub maker_amount: U256,
+    pub taker_amount: U256,
+    /// Charged in the taker asset, out of what the maker receives
+    pub fee: U256,
+}
+
+impl FillAssets {
+    pub fn from_log(log: &Log) -> Result<Self> {
+        let topics = log_topics(log, 3)?;
+        if log.data.len() < 128 {
+            return Err(anyhow::anyhow!("Malformed OrderFilled log"));
+        }
+        let word = |i: usize| U256::from_big_endian(&log.data[i * 32..(i + 1) * 32]);
+        Ok(Self {
+            maker: Address::from(topics[1]),
+            maker_asset: Asset::from_id(word(0)),
+            taker_asset: Asset::from_id(word(1)),
+            maker_amount: word(2),
+            taker_amount: word(3),
+            // Trailing `fee` word, absent on some older deployments
+            fee: if log.data.len() >= 160 { word(4) } else { U256::zero() },
+        })
+    }
+}
+
+/// An ERC-1155 position movement on the CTF, flattened from `TransferSingle`/`TransferBatch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub id: U256,
+    pub value: U256,
+    pub block_number: u64,
+    /// Shared by every entry of one `TransferBatch`
+    pub log_index: u64,
+}
+
+/// CTF position transfers among a receipt's logs; logs of other contracts and events are skipped.
+pub fn position_transfers(logs: &[Log]) -> Result<Vec<PositionTransfer>> {
+    let ctf = Address::from_str(CTF_ADDRESS)?;
+    let single_sig = H256::from(keccak256(TRANSFER_SINGLE_EVENT_SIGNATURE));
+    let batch_sig = H256::from(keccak256(TRANSFER_BATCH_EVENT_SIGNATURE));
+
+    let mut transfers = Vec::new();
+    for log in logs.iter().filter(|log| log.address == ctf) {
+        let Some(topic0) = log.topics.first() else { continue };
+        if *topic0 != single_sig && *topic0 != batch_sig {
+            continue;
+        }
+        let topics = log_topics(log, 3)?;
+        let (from, to) = (Address::from(topics[1]), Address::from(topics[2]));
+        let block_number = log.block_number.unwrap_or_default().as_u64();
+        let log_index = log.log_index.unwrap_or_default().as_u64();
+        let transfer = |id, value| PositionTransfer { from, to, id, value, block_number, log_index };
+
+        if *topic0 == single_sig {
+            let data = decode_log_data(log, &[ParamType::Uint(256), ParamType::Uint(256)])?;
+            transfers.push(transfer(token_uint(&data[0])?, token_uint(&data[1])?));
+        } else {
+            let data = decode_log_data(log, &[uint_array_type(), uint_array_type()])?;
+            let ids = token_uint_array(&data[0])?;
+            let values = token_uint_array(&data[1])?;
+            if ids.len() != values.len() {
+                return Err(anyhow::anyhow!("TransferBatch with {} ids and {} values", ids.len(), values.len()));
+            }
+            transfers.extend(ids.into_iter().zip(values).map(|(id, value)| transfer(id, value)));
+        }
+    }
+    Ok(transfers)
+}
+
+/// Checks a fill's position legs against the transaction's ERC-1155 transfers: the maker must
+/// send exactly the amount it gave and receive what it got net of the fee. Returns one message
+/// per inconsistency; collateral legs are not checked.
+pub fn verify_fill(fill: &FillAssets, transfers: &[PositionTransfer]) -> Vec<String> {
+    let mut issues = Vec::new();
+    if fill.maker_asset != Asset::Collateral && fill.taker_asset != Asset::Collateral {
+        issues.push("neither leg is collateral".to_string());
+    }
+
+    if let Asset::Position(id) = fill.maker_asset
+        && !transfers.iter().any(|t| t.id == id && t.from == fill.maker && t.value == fill.maker_amount)
+    {
+        issues.push(format!(
+            "no transfer of {} of position 0x{:x} from maker {}",
+            fill.maker_amount, id, format_address(fill.maker),
+        ));
+    }
+
+    if let Asset::Position(id) = fill.taker_asset {
+        let received = fill.taker_amount.saturating_sub(fill.fee);
+        if !transfers.iter().any(|t| t.id == id && t.to == fill.maker && t.value == received) {
+            issues.push(format!(
+                "no transfer of {} of position 0x{:x} to maker {}",
+                received, id, format_address(fill.maker),
+            ));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+#[path = "assets_test.rs"]
+mod assets_test;
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::assets::{position_transfers, verify_fill, Asset, FillAssets, PositionTransfer};
+    use crate::consts::*;
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, Bytes, Log, H256, U256};
+    use ethers::utils::keccak256;
+    use std::str::FromStr;
+
+    const MAKER: u8 = 0x11;
+    const EXCHANGE: u8 = 0xee;
+
+    fn transfer_log(contract: &str, signature: &str, (from, to): (u8, u8), data: Vec<Token>) -> Log {
+        Log {
+            address: Address::from_str(contract).unwrap(),
+            topics: vec![
+                H256::from(keccak256(signature)),
+                H256::from(Address::repeat_byte(EXCHANGE)),
+                H256::from(Address::repeat_byte(from)),
+                H256::from(Address::repeat_byte(to)),
+            ],
+            data: Bytes::from(encode(&data)),
+            ..Default::default()
+        }
+    }
+
+    fn single(from: u8, to: u8, id: u64, value: u64) -> Log {
+        let data = vec![Token::Uint(U256::from(id)), Token::Uint(U256::from(value))];
+        transfer_log(CTF_ADDRESS, TRANSFER_SINGLE_EVENT_SIGNATURE, (from, to), data)
+    }
+
+    fn order_filled(maker_asset: u64, taker_asset: u64, maker_amount: u64, taker_amount: u64, fee: u64) -> Log {
+        let words = [maker_asset, taker_asset, maker_amount, taker_amount, fee];
+        Log {
+            address: Address::from_str(EXCHANGE_PROXY_ADDRESS).unwrap(),
+            topics: vec![
+                H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE)),
+                H256::repeat_byte(0xaa),
+                H256::from(Address::repeat_byte(MAKER)),
+                H256::from(Address::repeat_byte(EXCHANGE)),
+            ],
+            data: Bytes::from(encode(&words.map(|w| Token::Uint(U256::from(w))))),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_asset_zero_is_collateral_and_positions_share_its_decimals() {
+        let fill = FillAssets::from_log(&order_filled(0, 0xabcd, 400_000, 1_000_000, 20_000)).unwrap();
+        assert_eq!(fill.maker, Address::repeat_byte(MAKER));
+        assert_eq!(fill.maker_asset, Asset::Collateral);
+        assert_eq!(fill.taker_asset, Asset::Position(U256::from(0xabcd)));
+        assert_eq!(fill.fee, U256::from(20_000));
+        assert_eq!(fill.maker_asset.decimals(), COLLATERAL_DECIMALS);
+        assert_eq!(fill.taker_asset.decimals(), COLLATERAL_DECIMALS);
+
+        let mut short = order_filled(0, 0xabcd, 1, 1, 0);
+        short.data = Bytes::from(short.data[..96].to_vec());
+        assert!(FillAssets::from_log(&short).is_err());
+    }
+
+    #[test]
+    fn test_decodes_single_and_batch_transfers_of_the_ctf_only() {
+        let batch = transfer_log(CTF_ADDRESS, TRANSFER_BATCH_EVENT_SIGNATURE, (0x00, EXCHANGE), vec![
+            Token::Array(vec![Token::Uint(U256::from(0xabcd)), Token::Uint(U256::from(0xabce))]),
+            Token::Array(vec![Token::Uint(U256::from(5)), Token::Uint(U256::from(5))]),
+        ]);
+        // Same event from another contract is not a CTF position
+        let foreign = transfer_log(USDC_ADDRESS, TRANSFER_SINGLE_EVENT_SIGNATURE, (MAKER, EXCHANGE), vec![
+            Token::Uint(U256::from(0xabcd)),
+            Token::Uint(U256::from(9)),
+        ]);
+
+        let transfers = position_transfers(&[single(MAKER, EXCHANGE, 0xabcd, 7), batch, foreign]).unwrap();
+        assert_eq!(transfers.len(), 3);
+        assert_eq!(transfers[0], PositionTransfer {
+            from: Address::repeat_byte(MAKER),
+            to: Address::repeat_byte(EXCHANGE),
+            id: U256::from(0xabcd),
+            value: U256::from(7),
+            block_number: 0,
+            log_index: 0,
+        });
+        assert_eq!((transfers[2].from, transfers[2].id), (Address::zero(), U256::from(0xabce)));
+
+        let mismatched = transfer_log(CTF_ADDRESS, TRANSFER_BATCH_EVENT_SIGNATURE, (0x00, EXCHANGE), vec![
+            Token::Array(vec![Token::Uint(U256::from(0xabcd))]),
+            Token::Array(vec![]),
+        ]);
+        assert!(position_transfers(&[mismatched]).is_err());
+    }
+
+    #[test]
+    fn test_verify_fill_reports_inconsistent_receipts() {
+        // Maker buys 1.0 of 0xabcd for 0.4 USDC and receives it net of a 0.02 token fee
+        let buy = FillAssets::from_log(&order_filled(0, 0xabcd, 400_000, 1_000_000, 20_000)).unwrap();
+        let transfers = position_transfers(&[single(EXCHANGE, MAKER, 0xabcd, 980_000)]).unwrap();
+        assert!(verify_fill(&buy, &transfers).is_empty());
+
+        // An equal amount of a different position no longer passes (the old amount heuristic would)
+        let other = position_transfers(&[single(EXCHANGE, MAKER, 0xabce, 980_000)]).unwrap();
+        let issues = verify_fill(&buy, &other);
+        assert_eq!(issues, vec![format!("no transfer of 980000 of position 0xabcd to maker {:?}", Address::repeat_byte(MAKER))]);
+
+        // Maker sells: the position must leave the maker for the full amount
+        let sell = FillAssets::from_log(&order_filled(0xabcd, 0, 1_000_000, 400_000, 0)).unwrap();
+        assert!(verify_fill(&sell, &position_transfers(&[single(MAKER, EXCHANGE, 0xabcd, 1_000_000)]).unwrap()).is_empty());
+        assert_eq!(verify_fill(&sell, &[]).len(), 1);
+
+        let swap = FillAssets::from_log(&order_filled(0xabcd, 0xabce, 1, 1, 0)).unwrap();
+        assert!(verify_fill(&swap, &[]).contains(&"neither leg is collateral".to_string()));
+    }
+}
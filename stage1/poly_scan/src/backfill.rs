@@ -0,0 +1,114 @@
+use anyhow::Result;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::future::Future;
+
+/// Blocks per eth_getLogs call a backfill starts with.
+pub const DEFAULT_BACKFILL_CHUNK: u64 = 2_000;
+
+/// Largest chunk a backfill grows back to after successful calls.
+pub const MAX_BACKFILL_CHUNK: u64 = 10_000;
+
+/// Provider errors that mean "ask for less", lowercased. Wording differs per node and provider.
+const SIZE_ERRORS: &[&str] = &[
+    "query returned more than",
+    "block range too large",
+    "block range is too large",
+    "exceed maximum block range",
+    "response size exceeded",
+    "too many results",
+];
+
+/// Block-range sizing for `backfill`. Chunks halve (down to `min`) when the provider rejects
+/// a request as too large and double (up to `max`) after each success.
+#[derive(Debug, Clone)]
+pub struct ChunkPolicy {
+    pub initial: u64,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl Default for ChunkPolicy {
+    fn default() -> Self {
+        Self {
+            initial: DEFAULT_BACKFILL_CHUNK,
+            min: 1,
+            max: MAX_BACKFILL_CHUNK,
+        }
+    }
+}
+
+impl ChunkPolicy {
+    /// Starts at `initial` blocks, growing up to at least that.
+    pub fn starting_at(initial: u64) -> Self {
+        let initial = initial.max(1);
+        Self { initial, max: initial.max(MAX_BACKFILL_CHUNK), ..Self::default() }
+    }
+
+    /// For topic-filtered lookups that match a handful of logs (e.g. one token's registration
+    /// from genesis): the whole range in one call, split only if the provider refuses it.
+    pub fn sparse() -> Self {
+        Self { initial: u64::MAX, min: 1, max: u64::MAX }
+    }
+}
+
+/// True if `err` is the provider refusing a range or result set as too large.
+pub fn is_size_error(err: &anyhow::Error) -> bool {
+    let message = format!("{:#}", err).to_lowercase();
+    SIZE_ERRORS.iter().any(|pattern| message.contains(pattern))
+}
+
+/// Runs `fetch` over `[from_block, to_block]` in consecutive chunks sized by `policy` and
+/// concatenates the results in block order. A size error retries the same start with half the
+/// chunk; other errors, or a size error at `policy.min`, are returned. With `progress`, an
+/// indicatif bar on stderr tracks the blocks covered.
+pub async fn backfill<T, F, Fut>(from_block: u64, to_block: u64, policy: &ChunkPolicy, progress: bool, mut fetch: F) -> Result<Vec<T>>
+where
+    F: FnMut(u64, u64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let min = policy.min.max(1);
+    let max = policy.max.max(min);
+    // Never wider than the range itself, so the first size error halves what was actually asked
+    let mut chunk = policy.initial.clamp(min, max).min(to_block.saturating_sub(from_block).saturating_add(1));
+
+    let bar = if progress && to_block >= from_block {
+        ProgressBar::new(to_block - from_block + 1)
+    } else {
+        ProgressBar::hidden()
+    };
+    bar.set_style(
+        ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} blocks ({eta}) {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    let mut results = Vec::new();
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block {
+        let chunk_end = chunk_start.saturating_add(chunk - 1).min(to_block);
+        bar.set_message(format!("chunk {}", chunk));
+
+        match fetch(chunk_start, chunk_end).await {
+            Ok(items) => {
+                results.extend(items);
+                bar.inc(chunk_end - chunk_start + 1);
+                chunk_start = chunk_end + 1;
+                chunk = chunk.saturating_mul(2).min(max);
+            }
+            Err(e) if is_size_error(&e) && chunk > min => {
+                let asked = chunk_end - chunk_start + 1;
+                chunk = (asked / 2).max(min);
+                bar.println(format!("Blocks {}-{} too large for the provider, retrying {} at a time", chunk_start, chunk_end, chunk));
+            }
+            Err(e) => {
+                bar.abandon();
+                return Err(e.context(format!("eth_getLogs failed for blocks {}-{}", chunk_start, chunk_end)));
+            }
+        }
+    }
+    bar.finish_and_clear();
+    Ok(results)
+}
+
+#[cfg(test)]
+#[path = "backfill_test.rs"]
+mod backfill_test;
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::backfill::{backfill, is_size_error, ChunkPolicy};
+
+    /// Provider that rejects any request spanning more than `limit` blocks; returns every block.
+    fn limited(limit: u64, calls: &mut Vec<(u64, u64)>) -> impl FnMut(u64, u64) -> std::future::Ready<anyhow::Result<Vec<u64>>> + '_ {
+        move |from, to| {
+            calls.push((from, to));
+            std::future::ready(if to - from + 1 > limit {
+                Err(anyhow::anyhow!("(code: -32005, message: query returned more than 10000 results, data: None)"))
+            } else {
+                Ok((from..=to).collect())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunks_halve_on_size_errors_and_grow_back() {
+        let mut calls = Vec::new();
+        let policy = ChunkPolicy { initial: 8, min: 1, max: 8 };
+        let blocks = backfill(100, 129, &policy, false, limited(3, &mut calls)).await.unwrap();
+
+        // Every block once, in order
+        assert_eq!(blocks, (100..=129).collect::<Vec<u64>>());
+        // 8 and 4 fail, 2 succeeds and doubles to 4, which fails again
+        assert_eq!(&calls[..5], &[(100, 107), (100, 103), (100, 101), (102, 105), (102, 103)]);
+        assert!(calls.iter().all(|(from, to)| to - from < 8));
+    }
+
+    #[tokio::test]
+    async fn test_sparse_lookups_ask_for_the_whole_range_first() {
+        let mut calls = Vec::new();
+        let blocks = backfill(0, 99, &ChunkPolicy::sparse(), false, limited(100, &mut calls)).await.unwrap();
+        assert_eq!(blocks.len(), 100);
+        assert_eq!(calls, vec![(0, 99)]);
+
+        // A refusal halves the range that was asked, not the unbounded policy
+        let mut calls = Vec::new();
+        backfill(0, 99, &ChunkPolicy::sparse(), false, limited(50, &mut calls)).await.unwrap();
+        assert_eq!(calls, vec![(0, 99), (0, 49), (50, 99)]);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_and_the_minimum_chunk_give_up() {
+        let mut calls = Vec::new();
+        let policy = ChunkPolicy { initial: 4, min: 2, max: 4 };
+        let err = backfill(0, 9, &policy, false, limited(1, &mut calls)).await.unwrap_err();
+        assert!(is_size_error(&err));
+        assert_eq!(calls, vec![(0, 3), (0, 1)]);
+
+        let err = backfill(0, 9, &ChunkPolicy::default(), false, |_, _| std::future::ready(Err::<Vec<u64>, _>(anyhow::anyhow!("connection refused")))).await.unwrap_err();
+        assert!(!is_size_error(&err));
+        assert!(format!("{:#}", err).contains("blocks 0-9"));
+
+        assert!(is_size_error(&anyhow::anyhow!("Block range too large")));
+        assert_eq!(backfill(5, 4, &policy, false, limited(1, &mut Vec::new())).await.unwrap(), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::amount::Amount;
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::consts::COLLATERAL_DECIMALS;
+use crate::models::{Candle, TradeOutput};
+use crate::scanner::Scanner;
+use crate::utils::usdc_notional;
+use anyhow::Result;
+use ethers::types::U256;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// Bar width. Bars start on multiples of the width in unix time (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandleInterval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl CandleInterval {
+    pub fn seconds(self) -> u64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 300,
+            CandleInterval::OneHour => 3_600,
+            CandleInterval::OneDay => 86_400,
+        }
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(CandleInterval::OneMinute),
+            "5m" => Ok(CandleInterval::FiveMinutes),
+            "1h" => Ok(CandleInterval::OneHour),
+            "1d" => Ok(CandleInterval::OneDay),
+            _ => Err(anyhow::anyhow!("Unsupported interval {} (use 1m, 5m, 1h or 1d)", s)),
+        }
+    }
+}
+
+impl fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+            CandleInterval::OneDay => "1d",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Fetches the fills of `token_ids` in `[from_block, to_block]` with their block timestamps
+/// and aggregates them into bars.
+pub async fn fetch_candles(
+    scanner: &Scanner,
+    token_ids: &[U256],
+    from_block: u64,
+    to_block: u64,
+    interval: CandleInterval,
+    fill_gaps: bool,
+    chunk_size: u64,
+) -> Result<Vec<Candle>> {
+    let trades = backfill(from_block, to_block, &ChunkPolicy::starting_at(chunk_size), true, |from, to| {
+        scanner.fetch_token_trades(from, to, token_ids)
+    }).await?;
+
+    let timestamps = scanner.block_timestamps(trades.iter().map(|t| t.block_number)).await?;
+    Ok(build_candles(&trades, &timestamps, interval, fill_gaps))
+}
+
+/// OHLCV bars per token, ordered by token then time. Fills without a known block timestamp are
+/// left out, as are taker-summary fills (taker == exchange), which repeat their match's maker
+/// fills. With `fill_gaps`, bars without fills between a token's first and last carry the
+/// previous close with zero volume.
+pub fn build_candles(
+    trades: &[TradeOutput],
+    timestamps: &HashMap<u64, u64>,
+    interval: CandleInterval,
+    fill_gaps: bool,
+) -> Vec<Candle> {
+    let width = interval.seconds();
+    let mut fills: Vec<(&TradeOutput, u64)> = trades.iter()
+        .filter(|t| !t.is_taker_summary())
+        .filter_map(|t| timestamps.get(&t.block_number).map(|ts| (t, *ts)))
+        .collect();
+    fills.sort_by_key(|(t, _)| (t.block_number, t.log_index));
+
+    let mut bars: BTreeMap<(String, u64), Candle> = BTreeMap::new();
+    for (trade, timestamp) in fills {
+        let start = timestamp - timestamp % width;
+        let notional = usdc_notional(trade).unwrap_or_else(|| Amount::zero(COLLATERAL_DECIMALS));
+        let bar = bars.entry((trade.token_id.clone(), start)).or_insert_with(|| Candle {
+            token_id: trade.token_id.clone(),
+            outcome: trade.maker_action.outcome.clone(),
+            start,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: Amount::zero(trade.size.decimals()),
+            volume_usdc: Amount::zero(COLLATERAL_DECIMALS),
+            trades: 0,
+        });
+        bar.high = bar.high.max(trade.price);
+        bar.low = bar.low.min(trade.price);
+        bar.close = trade.price;
+        bar.volume = bar.volume.checked_add(trade.size).unwrap_or(bar.volume);
+        bar.volume_usdc = bar.volume_usdc.checked_add(notional).unwrap_or(bar.volume_usdc);
+        bar.trades += 1;
+    }
+
+    let mut candles: Vec<Candle> = Vec::with_capacity(bars.len());
+    for bar in bars.into_values() {
+        if fill_gaps && let Some(previous) = candles.last().filter(|c| c.token_id == bar.token_id) {
+            let flat = |start| Candle {
+                start,
+                open: previous.close,
+                high: previous.close,
+                low: previous.close,
+                volume: Amount::zero(previous.volume.decimals()),
+                volume_usdc: Amount::zero(COLLATERAL_DECIMALS),
+                trades: 0,
+                ..previous.clone()
+            };
+            let gaps: Vec<Candle> = (previous.start + width..bar.start).step_by(width as usize).map(flat).collect();
+            candles.extend(gaps);
+        }
+        candles.push(bar);
+    }
+    candles
+}
+
+/// CSV with a header row; times are unix seconds.
+pub fn to_csv(candles: &[Candle]) -> String {
+    let mut csv = String::from("token_id,outcome,start,open,high,low,close,volume,volume_usdc,trades\n");
+    for c in candles {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            c.token_id,
+            c.outcome.as_deref().unwrap_or(""),
+            c.start,
+            c.open.to_trimmed_string(),
+            c.high.to_trimmed_string(),
+            c.low.to_trimmed_string(),
+            c.close.to_trimmed_string(),
+            c.volume.to_trimmed_string(),
+            c.volume_usdc.to_trimmed_string(),
+            c.trades,
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+#[path = "candles_test.rs"]
+mod candles_test;
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::amount::Amount;
+    use crate::candles::{build_candles, to_csv, CandleInterval};
+    use crate::models::TradeOutput;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    /// Maker buys `size` tokens at `price`; fills are one per block.
+    fn fill(block_number: u64, token_id: &str, price: &str, size: &str) -> TradeOutput {
+        let price = Amount::from_str(price).unwrap().rescale(6).unwrap();
+        let size = Amount::from_str(size).unwrap().rescale(6).unwrap();
+        let usdc = price.checked_mul(size, 6).unwrap();
+        TradeOutput::fixture(token_id).at_block(block_number).filled(usdc.raw().as_u64(), size.raw().as_u64())
+    }
+
+    fn timestamps() -> HashMap<u64, u64> {
+        // Blocks 1-3 in the first hour, 4 in the second, 5 in the fourth; block 6 has no timestamp
+        HashMap::from([(1, 3_600), (2, 3_700), (3, 4_000), (4, 7_300), (5, 14_500)])
+    }
+
+    #[test]
+    fn test_bars_aggregate_fills_per_token_and_interval() {
+        let mut summary = fill(3, "0xabc", "0.9", "100");
+        summary.taker = summary.exchange.clone();
+        let trades = vec![
+            fill(2, "0xabc", "0.6", "10"),
+            fill(1, "0xabc", "0.4", "5"),
+            summary,
+            fill(4, "0xabc", "0.5", "1"),
+            fill(1, "0xabd", "0.55", "2"),
+            fill(6, "0xabc", "0.1", "1"),
+        ];
+        let bars = build_candles(&trades, &timestamps(), CandleInterval::OneHour, false);
+        assert_eq!(bars.len(), 3);
+
+        // Opens on block 1 even though it arrived second; the taker summary is not counted
+        let first = &bars[0];
+        assert_eq!((first.token_id.as_str(), first.start), ("0xabc", 3_600));
+        let ohlc: Vec<String> = [first.open, first.high, first.low, first.close].iter().map(|p| p.to_trimmed_string()).collect();
+        assert_eq!(ohlc, vec!["0.4", "0.6", "0.4", "0.6"]);
+        assert_eq!(first.volume.to_trimmed_string(), "15.0");
+        assert_eq!(first.volume_usdc.to_trimmed_string(), "8.0");
+        assert_eq!(first.trades, 2);
+
+        assert_eq!((bars[1].start, bars[1].trades), (7_200, 1));
+        assert_eq!(bars[2].token_id, "0xabd");
+    }
+
+    #[test]
+    fn test_gaps_are_filled_with_the_previous_close() {
+        let trades = vec![fill(1, "0xabc", "0.4", "5"), fill(5, "0xabc", "0.7", "1")];
+        let bars = build_candles(&trades, &timestamps(), CandleInterval::OneHour, true);
+
+        let starts: Vec<u64> = bars.iter().map(|b| b.start).collect();
+        assert_eq!(starts, vec![3_600, 7_200, 10_800, 14_400]);
+        assert_eq!(bars[1].open, bars[0].close);
+        assert_eq!(bars[2].high.to_trimmed_string(), "0.4");
+        assert!(bars[2].volume.is_zero());
+        assert_eq!(bars[2].trades, 0);
+        assert_eq!(bars[3].close.to_trimmed_string(), "0.7");
+    }
+
+    #[test]
+    fn test_intervals_parse_and_csv_renders_one_row_per_bar() {
+        assert_eq!(CandleInterval::from_str("5m").unwrap().seconds(), 300);
+        assert_eq!(CandleInterval::from_str("1d").unwrap().to_string(), "1d");
+        assert!(CandleInterval::from_str("2h").is_err());
+
+        let bars = build_candles(&[fill(1, "0xabc", "0.4", "5")], &timestamps(), CandleInterval::OneMinute, false);
+        assert_eq!(
+            to_csv(&bars),
+            "token_id,outcome,start,open,high,low,close,volume,volume_usdc,trades\n0xabc,,3600,0.4,0.4,0.4,0.4,5.0,2.0,1\n",
+        );
+    }
+}
@@ -1,14 +1,110 @@
 pub const POLYGON_RPC_URL: &str = "https://polygon-rpc.com";
-pub const EXCHANGE_PROXY_ADDRESS: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+pub const POLYGON_WS_URL: &str = "wss://polygon-bor-rpc.publicnode.com";
+pub const POLYGON_CHAIN_ID: u64 = 137; // Served by both URLs above
+pub const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+pub const EXCHANGE_PROXY_ADDRESS: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E"; // CTF Exchange (binary markets)
+pub const NEG_RISK_EXCHANGE_ADDRESS: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a"; // NegRisk_CTFExchange (multi-outcome markets)
 
 // event OrderFilled(bytes32 indexed orderHash, address indexed maker, address indexed taker, uint256 makerAssetId, uint256 takerAssetId, uint256 makerAmountFilled, uint256 takerAmountFilled, uint256 fee);
 pub const ORDER_FILLED_EVENT_SIGNATURE: &str = "OrderFilled(bytes32,address,address,uint256,uint256,uint256,uint256,uint256)";
 
+// event OrdersMatched(bytes32 indexed takerOrderHash, address indexed takerOrderMaker, uint256 makerAssetId, uint256 takerAssetId, uint256 makerAmountFilled, uint256 takerAmountFilled);
+pub const ORDERS_MATCHED_EVENT_SIGNATURE: &str = "OrdersMatched(bytes32,address,uint256,uint256,uint256,uint256)";
+
+// Emitted twice per registration (token/complement and complement/token), so topic1 alone finds a token's condition.
+// event TokenRegistered(uint256 indexed token0, uint256 indexed token1, bytes32 indexed conditionId);
+pub const TOKEN_REGISTERED_EVENT_SIGNATURE: &str = "TokenRegistered(uint256,uint256,bytes32)";
+
 // Common Token Addresses on Polygon (Optional mapping for quick lookup if needed)
 pub const USDC_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"; // USDC.e
 
 // CTF (Conditional Tokens Framework) Address on Polygon
 pub const CTF_ADDRESS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045"; // Mainnet CTF
 
+// ERC-1155 position movements on the CTF (mints come from and burns go to the zero address)
+// event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+pub const TRANSFER_SINGLE_EVENT_SIGNATURE: &str = "TransferSingle(address,address,address,uint256,uint256)";
+// event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)
+pub const TRANSFER_BATCH_EVENT_SIGNATURE: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+// NegRisk: the adapter prepares conditions with itself as oracle, and positions are minted
+// against its WrappedCollateral (a 1:1 USDC.e wrapper) instead of USDC.e directly
+pub const NEG_RISK_ADAPTER_ADDRESS: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+pub const NEG_RISK_WRAPPED_COLLATERAL_ADDRESS: &str = "0x3A3BD7bb9528E159577F7C2e685CC81A765002E2";
+
+// NegRiskAdapter events. A market groups mutually exclusive questions; question `i` has
+// questionId = marketId + i (the low byte of marketId is always zero).
+// event MarketPrepared(bytes32 indexed marketId, address indexed oracle, uint256 feeBips, bytes data)
+pub const NEG_RISK_MARKET_PREPARED_EVENT_SIGNATURE: &str = "MarketPrepared(bytes32,address,uint256,bytes)";
+// event QuestionPrepared(bytes32 indexed marketId, bytes32 indexed questionId, uint256 index, bytes data)
+pub const NEG_RISK_QUESTION_PREPARED_EVENT_SIGNATURE: &str = "QuestionPrepared(bytes32,bytes32,uint256,bytes)";
+// event OutcomeReported(bytes32 indexed marketId, bytes32 indexed questionId, bool outcome)
+pub const NEG_RISK_OUTCOME_REPORTED_EVENT_SIGNATURE: &str = "OutcomeReported(bytes32,bytes32,bool)";
+// event PositionsConverted(address indexed stakeholder, bytes32 indexed marketId, uint256 indexed indexSet, uint256 amount)
+pub const NEG_RISK_POSITIONS_CONVERTED_EVENT_SIGNATURE: &str = "PositionsConverted(address,bytes32,uint256,uint256)";
+// event PositionSplit(address indexed stakeholder, bytes32 indexed conditionId, uint256 amount)
+pub const NEG_RISK_POSITION_SPLIT_EVENT_SIGNATURE: &str = "PositionSplit(address,bytes32,uint256)";
+// event PositionsMerge(address indexed stakeholder, bytes32 indexed conditionId, uint256 amount)
+pub const NEG_RISK_POSITIONS_MERGE_EVENT_SIGNATURE: &str = "PositionsMerge(address,bytes32,uint256)";
+// event PayoutRedemption(address indexed redeemer, bytes32 indexed conditionId, uint256[] amounts, uint256 payout)
+pub const NEG_RISK_PAYOUT_REDEMPTION_EVENT_SIGNATURE: &str = "PayoutRedemption(address,bytes32,uint256[],uint256)";
+
 // event ConditionPreparation(bytes32 indexed conditionId, address indexed oracle, bytes32 indexed questionId, uint256 outcomeSlotCount)
 pub const CONDITION_PREPARATION_EVENT_SIGNATURE: &str = "ConditionPreparation(bytes32,address,bytes32,uint256)";
+
+// CTF lifecycle after preparation: collateral split into / merged out of full position sets,
+// the oracle reporting payouts, and holders redeeming winning positions.
+// event PositionSplit(address indexed stakeholder, address collateralToken, bytes32 indexed parentCollectionId, bytes32 indexed conditionId, uint256[] partition, uint256 amount)
+pub const POSITION_SPLIT_EVENT_SIGNATURE: &str = "PositionSplit(address,address,bytes32,bytes32,uint256[],uint256)";
+// event PositionsMerge(address indexed stakeholder, address collateralToken, bytes32 indexed parentCollectionId, bytes32 indexed conditionId, uint256[] partition, uint256 amount)
+pub const POSITIONS_MERGE_EVENT_SIGNATURE: &str = "PositionsMerge(address,address,bytes32,bytes32,uint256[],uint256)";
+// event ConditionResolution(bytes32 indexed conditionId, address indexed oracle, bytes32 indexed questionId, uint256 outcomeSlotCount, uint256[] payoutNumerators)
+pub const CONDITION_RESOLUTION_EVENT_SIGNATURE: &str = "ConditionResolution(bytes32,address,bytes32,uint256,uint256[])";
+// event PayoutRedemption(address indexed redeemer, address indexed collateralToken, bytes32 indexed parentCollectionId, bytes32 conditionId, uint256[] indexSets, uint256 payout)
+pub const PAYOUT_REDEMPTION_EVENT_SIGNATURE: &str = "PayoutRedemption(address,address,bytes32,bytes32,uint256[],uint256)";
+
+// USDC.e and the NegRisk WrappedCollateral both use 6 decimals
+pub const COLLATERAL_DECIMALS: u32 = 6;
+
+// UMA CTF Adapters: oracle of most binary markets. Each initializes a question with UMA
+// ancillary data ("q: title: ..., description: ... res_data: ...") and prepares the condition.
+pub const UMA_CTF_ADAPTER_ADDRESSES: [&str; 3] = [
+    "0x6A9D222616C90FcA5754cd1333cFD9b7fb6a4F74", // v2
+    "0x157Ce2d672854c848c9b79C49a8Cc6cc89176a49", // v3
+    "0xCB1822859cEF82Cd2Eb4E6276C7916e692995130", // v1
+];
+
+// event QuestionInitialized(bytes32 indexed questionID, uint256 indexed requestTimestamp, address indexed creator, bytes ancillaryData, address rewardToken, uint256 reward, uint256 proposalBond)
+pub const UMA_QUESTION_INITIALIZED_EVENT_SIGNATURE: &str = "QuestionInitialized(bytes32,uint256,address,bytes,address,uint256,uint256)";
+
+// UMA CTF Adapter resolution lifecycle. A question is reset (and re-requested) after its first
+// dispute; flagging and pausing are admin actions that hold it for manual resolution.
+// event QuestionResolved(bytes32 indexed questionID, int256 indexed settledPrice, uint256[] payouts)
+pub const UMA_QUESTION_RESOLVED_EVENT_SIGNATURE: &str = "QuestionResolved(bytes32,int256,uint256[])";
+// event QuestionEmergencyResolved(bytes32 indexed questionID, uint256[] payouts)
+pub const UMA_QUESTION_EMERGENCY_RESOLVED_EVENT_SIGNATURE: &str = "QuestionEmergencyResolved(bytes32,uint256[])";
+// event QuestionReset(bytes32 indexed questionID)
+pub const UMA_QUESTION_RESET_EVENT_SIGNATURE: &str = "QuestionReset(bytes32)";
+// event QuestionFlagged(bytes32 indexed questionID)
+pub const UMA_QUESTION_FLAGGED_EVENT_SIGNATURE: &str = "QuestionFlagged(bytes32)";
+// event QuestionPaused(bytes32 indexed questionID)
+pub const UMA_QUESTION_PAUSED_EVENT_SIGNATURE: &str = "QuestionPaused(bytes32)";
+// event QuestionUnpaused(bytes32 indexed questionID)
+pub const UMA_QUESTION_UNPAUSED_EVENT_SIGNATURE: &str = "QuestionUnpaused(bytes32)";
+
+// UMA Optimistic Oracles the adapters request prices from. Their events carry the request's
+// ancillary data, whose keccak256 is the adapter's question ID.
+pub const UMA_OPTIMISTIC_ORACLE_ADDRESSES: [&str; 2] = [
+    "0xeE3Afe347D5C74317041E2618C49534dAf887c24", // OptimisticOracleV2
+    "0x2C0367a9DB231dDeBd88a94b4f6461a6e47C58B1", // Managed OptimisticOracleV2
+];
+
+// event ProposePrice(address indexed requester, address indexed proposer, bytes32 identifier, uint256 timestamp, bytes ancillaryData, int256 proposedPrice, uint256 expirationTimestamp, address currency)
+pub const UMA_PROPOSE_PRICE_EVENT_SIGNATURE: &str = "ProposePrice(address,address,bytes32,uint256,bytes,int256,uint256,address)";
+// event DisputePrice(address indexed requester, address indexed proposer, address indexed disputer, bytes32 identifier, uint256 timestamp, bytes ancillaryData, int256 proposedPrice)
+pub const UMA_DISPUTE_PRICE_EVENT_SIGNATURE: &str = "DisputePrice(address,address,address,bytes32,uint256,bytes,int256)";
+// event Settle(address indexed requester, address indexed proposer, address indexed disputer, bytes32 identifier, uint256 timestamp, bytes ancillaryData, int256 price, uint256 payout)
+pub const UMA_SETTLE_EVENT_SIGNATURE: &str = "Settle(address,address,address,bytes32,uint256,bytes,int256,uint256)";
+
+// YES_OR_NO_QUERY prices: 1e18 = YES, 0 = NO, 0.5e18 = 50/50
+pub const UMA_PRICE_YES: u128 = 1_000_000_000_000_000_000;
@@ -0,0 +1,93 @@
+use crate::amount::Amount;
+use crate::consts::*;
+use crate::models::{CtfEvent, CtfEventKind};
+use crate::utils::{decode_log_data, format_address, log_topics, token_address, token_uint, token_uint_array, u256_to_string, uint_array_type};
+use anyhow::Result;
+use ethers::abi::{ParamType, Token};
+use ethers::types::{Address, Log, H256};
+use ethers::utils::keccak256;
+
+/// topic0 of every CTF lifecycle event decoded here.
+pub fn ctf_event_topics() -> Vec<H256> {
+    [
+        POSITION_SPLIT_EVENT_SIGNATURE,
+        POSITIONS_MERGE_EVENT_SIGNATURE,
+        CONDITION_RESOLUTION_EVENT_SIGNATURE,
+        PAYOUT_REDEMPTION_EVENT_SIGNATURE,
+    ]
+    .iter()
+    .map(|sig| H256::from(keccak256(sig)))
+    .collect()
+}
+
+/// Decodes a CTF lifecycle log. Ok(None) for logs of any other event.
+pub fn decode_ctf_log(log: &Log) -> Result<Option<CtfEvent>> {
+    let Some(topic0) = log.topics.first() else { return Ok(None) };
+    let is = |sig: &str| *topic0 == H256::from(keccak256(sig));
+
+    let (condition_id, kind) = if is(POSITION_SPLIT_EVENT_SIGNATURE) || is(POSITIONS_MERGE_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[ParamType::Address, uint_array_type(), ParamType::Uint(256)])?;
+        let stakeholder = format_address(Address::from(topics[0]));
+        let collateral_token = format_address(token_address(&data[0])?);
+        let parent_collection_id = format!("{:?}", topics[1]);
+        let partition = as_u64_array(&data[1])?;
+        let amount = Amount::new(token_uint(&data[2])?, COLLATERAL_DECIMALS);
+
+        let kind = if is(POSITION_SPLIT_EVENT_SIGNATURE) {
+            CtfEventKind::PositionSplit { stakeholder, collateral_token, parent_collection_id, partition, amount }
+        } else {
+            CtfEventKind::PositionsMerge { stakeholder, collateral_token, parent_collection_id, partition, amount }
+        };
+        (topics[2], kind)
+    } else if is(CONDITION_RESOLUTION_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[ParamType::Uint(256), uint_array_type()])?;
+        let payout_numerators = token_uint_array(&data[1])?.into_iter().map(u256_to_string).collect();
+
+        let kind = CtfEventKind::ConditionResolution {
+            oracle: format_address(Address::from(topics[1])),
+            question_id: format!("{:?}", topics[2]),
+            outcome_slot_count: token_uint(&data[0])?.low_u64(),
+            payout_numerators,
+        };
+        (topics[0], kind)
+    } else if is(PAYOUT_REDEMPTION_EVENT_SIGNATURE) {
+        // conditionId is not indexed on this event
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[ParamType::FixedBytes(32), uint_array_type(), ParamType::Uint(256)])?;
+        let condition_id = match &data[0] {
+            Token::FixedBytes(bytes) => H256::from_slice(bytes),
+            _ => return Err(anyhow::anyhow!("Malformed PayoutRedemption log")),
+        };
+
+        let kind = CtfEventKind::PayoutRedemption {
+            redeemer: format_address(Address::from(topics[0])),
+            collateral_token: format_address(Address::from(topics[1])),
+            parent_collection_id: format!("{:?}", topics[2]),
+            index_sets: as_u64_array(&data[1])?,
+            payout: Amount::new(token_uint(&data[2])?, COLLATERAL_DECIMALS),
+        };
+        (condition_id, kind)
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(CtfEvent {
+        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+        log_index: log.log_index.unwrap_or_default().as_u64(),
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+        block_hash: format!("{:?}", log.block_hash.unwrap_or_default()),
+        condition_id: format!("{:?}", condition_id),
+        kind,
+    }))
+}
+
+/// Index sets are bitmasks over outcome slots, so they fit in a u64 for any real market.
+fn as_u64_array(token: &Token) -> Result<Vec<u64>> {
+    Ok(token_uint_array(token)?.into_iter().map(|v| v.low_u64()).collect())
+}
+
+#[cfg(test)]
+#[path = "ctf_test.rs"]
+mod ctf_test;
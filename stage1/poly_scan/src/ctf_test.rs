@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::*;
+    use crate::ctf::decode_ctf_log;
+    use crate::models::CtfEventKind;
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, Bytes, Log, H256, U256, U64};
+    use ethers::utils::keccak256;
+    use std::str::FromStr;
+
+    fn ctf_log(signature: &str, topics: Vec<H256>, data: Vec<Token>) -> Log {
+        let mut all_topics = vec![H256::from(keccak256(signature))];
+        all_topics.extend(topics);
+        Log {
+            address: Address::from_str(CTF_ADDRESS).unwrap(),
+            topics: all_topics,
+            data: Bytes::from(encode(&data)),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            log_index: Some(U256::from(5)),
+            block_number: Some(U64::from(200u64)),
+            ..Default::default()
+        }
+    }
+
+    fn uints(values: &[u64]) -> Token {
+        Token::Array(values.iter().map(|v| Token::Uint(U256::from(*v))).collect())
+    }
+
+    #[test]
+    fn test_decode_split_and_merge() {
+        let usdc = Address::from_str(USDC_ADDRESS).unwrap();
+        let topics = vec![H256::from(Address::repeat_byte(0x11)), H256::zero(), H256::repeat_byte(0xc0)];
+        let data = vec![Token::Address(usdc), uints(&[1, 2]), Token::Uint(U256::from(2_500_000))];
+
+        let split = decode_ctf_log(&ctf_log(POSITION_SPLIT_EVENT_SIGNATURE, topics.clone(), data.clone())).unwrap().unwrap();
+        assert_eq!(split.condition_id, format!("{:?}", H256::repeat_byte(0xc0)));
+        assert_eq!(split.block_number, 200);
+        match split.kind {
+            CtfEventKind::PositionSplit { stakeholder, collateral_token, partition, amount, .. } => {
+                assert_eq!(stakeholder, format!("{:?}", Address::repeat_byte(0x11)));
+                assert_eq!(collateral_token, format!("{:?}", usdc));
+                assert_eq!(partition, vec![1, 2]);
+                assert_eq!(amount.to_string(), "2.500000");
+            }
+            other => panic!("Expected split, got {:?}", other),
+        }
+
+        let merge = decode_ctf_log(&ctf_log(POSITIONS_MERGE_EVENT_SIGNATURE, topics, data)).unwrap().unwrap();
+        assert!(matches!(merge.kind, CtfEventKind::PositionsMerge { .. }));
+    }
+
+    #[test]
+    fn test_decode_resolution_and_redemption() {
+        let topics = vec![H256::repeat_byte(0xc0), H256::from(Address::repeat_byte(0x0a)), H256::repeat_byte(0x0b)];
+        let resolution = ctf_log(CONDITION_RESOLUTION_EVENT_SIGNATURE, topics, vec![Token::Uint(U256::from(2)), uints(&[1, 0])]);
+        let resolution = decode_ctf_log(&resolution).unwrap().unwrap();
+        assert_eq!(resolution.condition_id, format!("{:?}", H256::repeat_byte(0xc0)));
+        match resolution.kind {
+            CtfEventKind::ConditionResolution { outcome_slot_count, payout_numerators, question_id, .. } => {
+                assert_eq!(outcome_slot_count, 2);
+                assert_eq!(payout_numerators, vec!["1".to_string(), "0".to_string()]);
+                assert_eq!(question_id, format!("{:?}", H256::repeat_byte(0x0b)));
+            }
+            other => panic!("Expected resolution, got {:?}", other),
+        }
+
+        // conditionId travels in data on PayoutRedemption
+        let usdc = Address::from_str(USDC_ADDRESS).unwrap();
+        let topics = vec![H256::from(Address::repeat_byte(0x22)), H256::from(usdc), H256::zero()];
+        let data = vec![Token::FixedBytes(vec![0xc0; 32]), uints(&[1]), Token::Uint(U256::from(7_000_000))];
+        let redemption = decode_ctf_log(&ctf_log(PAYOUT_REDEMPTION_EVENT_SIGNATURE, topics, data)).unwrap().unwrap();
+        assert_eq!(redemption.condition_id, format!("{:?}", H256::repeat_byte(0xc0)));
+        match redemption.kind {
+            CtfEventKind::PayoutRedemption { redeemer, index_sets, payout, .. } => {
+                assert_eq!(redeemer, format!("{:?}", Address::repeat_byte(0x22)));
+                assert_eq!(index_sets, vec![1]);
+                assert_eq!(payout.to_string(), "7.000000");
+            }
+            other => panic!("Expected redemption, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_other_events_are_skipped_and_malformed_rejected() {
+        let preparation = ctf_log(CONDITION_PREPARATION_EVENT_SIGNATURE, vec![H256::zero(); 3], vec![Token::Uint(U256::from(2))]);
+        assert!(decode_ctf_log(&preparation).unwrap().is_none());
+
+        let truncated = ctf_log(POSITION_SPLIT_EVENT_SIGNATURE, vec![H256::zero(); 3], vec![Token::Uint(U256::one())]);
+        assert!(decode_ctf_log(&truncated).is_err());
+    }
+}
@@ -0,0 +1,737 @@
+use crate::amount::Amount;
+use crate::consts::NEG_RISK_ADAPTER_ADDRESS;
+use crate::models::{outcome_label, ExchangeVenue, GammaMarket, MarketInfo, OutcomePosition, RegisteredToken, TradeAction, TradeOutput, TradeSide, UnresolvedToken};
+use crate::utils::canonical_token_id;
+use anyhow::Result;
+use rusqlite::types::{Type, Value};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, Transaction};
+use std::path::Path;
+use std::str::FromStr;
+
+// Schema follows stage2.md (markets / trades / sync_state).
+// Token IDs are stored in the canonical `0x{:x}` form so trades can be joined to markets.
+// SQLite has no real DECIMAL (NUMERIC affinity would round to REAL), so price/size are exact TEXT.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS markets (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    slug             TEXT,
+    condition_id     TEXT NOT NULL UNIQUE,
+    question_id      TEXT,
+    title            TEXT,
+    description      TEXT,
+    oracle           TEXT,
+    collateral_token TEXT,
+    yes_token_id     TEXT,
+    no_token_id      TEXT,
+    enable_neg_risk  BOOLEAN NOT NULL DEFAULT 0,
+    status           TEXT,
+    created_at       TIMESTAMP,
+    block_number     INTEGER,
+    block_hash       TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_markets_yes_token ON markets(yes_token_id);
+CREATE INDEX IF NOT EXISTS idx_markets_no_token ON markets(no_token_id);
+
+-- One row per outcome slot (and per requested combined index set); yes/no above are the binary view
+CREATE TABLE IF NOT EXISTS market_positions (
+    market_id     INTEGER NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+    position_id   TEXT NOT NULL,
+    index_set     INTEGER NOT NULL,
+    collection_id TEXT NOT NULL,
+    outcome       TEXT,
+    PRIMARY KEY (market_id, index_set)
+);
+CREATE INDEX IF NOT EXISTS idx_market_positions_position ON market_positions(position_id);
+
+CREATE TABLE IF NOT EXISTS trades (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    market_id           INTEGER REFERENCES markets(id),
+    tx_hash             TEXT NOT NULL,
+    log_index           INTEGER NOT NULL,
+    order_hash          TEXT NOT NULL,
+    match_log_index     INTEGER,
+    exchange            TEXT NOT NULL,
+    venue               TEXT NOT NULL,
+    maker               TEXT NOT NULL,
+    taker               TEXT NOT NULL,
+    maker_asset_id      TEXT NOT NULL,
+    taker_asset_id      TEXT NOT NULL,
+    maker_amount_filled TEXT NOT NULL,
+    taker_amount_filled TEXT NOT NULL,
+    maker_decimals      INTEGER NOT NULL,
+    taker_decimals      INTEGER NOT NULL,
+    token_id            TEXT NOT NULL,
+    side                TEXT NOT NULL,
+    taker_side          TEXT NOT NULL,
+    taker_token_id      TEXT NOT NULL,
+    complementary       INTEGER NOT NULL DEFAULT 0,
+    outcome             TEXT,
+    price               TEXT NOT NULL,
+    size                TEXT NOT NULL,
+    fee                 TEXT NOT NULL,
+    fee_usdc            TEXT NOT NULL,
+    fee_bps             TEXT NOT NULL,
+    timestamp           TIMESTAMP,
+    block_number        INTEGER NOT NULL,
+    block_hash          TEXT NOT NULL,
+    UNIQUE (tx_hash, log_index)
+);
+CREATE INDEX IF NOT EXISTS idx_trades_market ON trades(market_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_trades_token ON trades(token_id);
+CREATE INDEX IF NOT EXISTS idx_trades_block ON trades(block_number);
+CREATE INDEX IF NOT EXISTS idx_trades_tx ON trades(tx_hash);
+
+-- Exchange TokenRegistered logs; both directions of each pair are stored
+CREATE TABLE IF NOT EXISTS token_registry (
+    token_id      TEXT PRIMARY KEY,
+    complement_id TEXT NOT NULL,
+    condition_id  TEXT NOT NULL,
+    outcome_index INTEGER,
+    exchange      TEXT NOT NULL,
+    venue         TEXT NOT NULL,
+    block_number  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_token_registry_condition ON token_registry(condition_id);
+
+-- Traded tokens no source could map to a market; rows go away once the token's market is stored
+CREATE TABLE IF NOT EXISTS unresolved_tokens (
+    token_id         TEXT PRIMARY KEY,
+    first_seen_block INTEGER NOT NULL,
+    last_seen_block  INTEGER NOT NULL,
+    trade_count      INTEGER NOT NULL,
+    attempts         INTEGER NOT NULL,
+    reason           TEXT NOT NULL,
+    updated_at       TIMESTAMP
+);
+
+-- One row per batch that tried and failed to resolve a token, so a rollback can take back its attempts
+CREATE TABLE IF NOT EXISTS unresolved_attempts (
+    token_id TEXT NOT NULL REFERENCES unresolved_tokens(token_id) ON DELETE CASCADE,
+    to_block INTEGER NOT NULL,
+    PRIMARY KEY (token_id, to_block)
+);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    key             TEXT PRIMARY KEY,
+    last_block      INTEGER NOT NULL,
+    last_block_hash TEXT,
+    updated_at      TIMESTAMP
+);
+";
+
+/// One scanned block range, as written by `write_batch`.
+#[derive(Debug, Default)]
+pub struct Batch {
+    pub markets: Vec<MarketInfo>,
+    pub trades: Vec<TradeOutput>,
+    /// `TokenRegistered` logs in the range
+    pub registrations: Vec<RegisteredToken>,
+    /// (token ID, reason) for traded tokens whose market could not be found
+    pub unresolved: Vec<(String, String)>,
+}
+
+/// Rows written by a single `write_batch` call.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    pub markets_inserted: usize,
+    pub trades_inserted: usize,
+    /// Trades stored without a `market_id` because their token is not in `markets` yet
+    pub trades_unmatched: usize,
+}
+
+/// Filters for `Database::trades`. Addresses and hashes compare case-insensitively; times are
+/// unix seconds and only match trades whose block timestamp is known.
+#[derive(Debug, Default, Clone)]
+pub struct TradeFilter {
+    pub condition_id: Option<String>,
+    pub tx_hash: Option<String>,
+    pub maker: Option<String>,
+    pub taker: Option<String>,
+    pub side: Option<TradeSide>,
+    /// "YES", "NO" or "OUTCOME_<slot>"
+    pub outcome: Option<String>,
+    pub from_time: Option<u64>,
+    pub to_time: Option<u64>,
+    pub limit: Option<u64>,
+    pub offset: u64,
+}
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn last_block(&self, key: &str) -> Result<Option<u64>> {
+        let block: Option<i64> = self.conn
+            .query_row("SELECT last_block FROM sync_state WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()?;
+        Ok(block.map(|b| b as u64))
+    }
+
+    /// `last_block` together with the block hash recorded when it was indexed.
+    pub fn sync_tip(&self, key: &str) -> Result<Option<(u64, Option<String>)>> {
+        let tip: Option<(i64, Option<String>)> = self.conn
+            .query_row(
+                "SELECT last_block, last_block_hash FROM sync_state WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(tip.map(|(block, hash)| (block as u64, hash)))
+    }
+
+    /// Distinct (block_number, block_hash) pairs stored for trades and markets in
+    /// `[from_block, to_block]`, newest first.
+    pub fn stored_block_hashes(&self, from_block: u64, to_block: u64) -> Result<Vec<(u64, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT block_number, block_hash FROM trades WHERE block_number BETWEEN ?1 AND ?2
+             UNION
+             SELECT block_number, block_hash FROM markets
+             WHERE block_number BETWEEN ?1 AND ?2 AND block_hash IS NOT NULL
+             ORDER BY block_number DESC",
+        )?;
+        let rows = stmt.query_map(params![from_block as i64, to_block as i64], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Undoes everything indexed above `block` (reorged away) and rewinds `last_block` to it,
+    /// so the next run re-ingests the range. Returns (trades_deleted, markets_deleted).
+    pub fn rollback_to(&mut self, sync_key: &str, block: u64, block_hash: Option<&str>) -> Result<(usize, usize)> {
+        let tx = self.conn.transaction()?;
+
+        // Trades first: they reference markets
+        let trades = tx.execute("DELETE FROM trades WHERE block_number > ?1", params![block as i64])?;
+        let markets = tx.execute("DELETE FROM markets WHERE block_number > ?1", params![block as i64])?;
+        tx.execute("DELETE FROM unresolved_tokens WHERE first_seen_block > ?1", params![block as i64])?;
+        // Surviving queue entries forget the attempts and fills of the rolled-back batches; the
+        // rescan counts them again. Entries left have been tried at least once.
+        tx.execute("DELETE FROM unresolved_attempts WHERE to_block > ?1", params![block as i64])?;
+        tx.execute(
+            "UPDATE unresolved_tokens SET
+                attempts = MAX(1, (SELECT COUNT(*) FROM unresolved_attempts a WHERE a.token_id = unresolved_tokens.token_id)),
+                trade_count = (SELECT COUNT(*) FROM trades t WHERE t.market_id IS NULL AND t.token_id = unresolved_tokens.token_id),
+                last_seen_block = (SELECT MAX(t.block_number) FROM trades t WHERE t.market_id IS NULL AND t.token_id = unresolved_tokens.token_id)
+             WHERE last_seen_block > ?1",
+            params![block as i64],
+        )?;
+        tx.execute("DELETE FROM token_registry WHERE block_number > ?1", params![block as i64])?;
+        tx.execute(
+            "UPDATE sync_state SET last_block = ?2, last_block_hash = ?3, updated_at = CURRENT_TIMESTAMP
+             WHERE key = ?1 AND last_block > ?2",
+            params![sync_key, block as i64, block_hash],
+        )?;
+
+        tx.commit()?;
+        Ok((trades, markets))
+    }
+
+    /// Stores one scanned block range atomically: markets, trades, the unresolved-token queue
+    /// and the new `last_block` either all land or none do, so a resumed run never skips or
+    /// repeats a range. `to_block_hash` is kept to detect reorgs of the sync tip.
+    pub fn write_batch(&mut self, sync_key: &str, batch: &Batch, to_block: u64, to_block_hash: Option<&str>) -> Result<BatchStats> {
+        let tx = self.conn.transaction()?;
+        let mut stats = BatchStats::default();
+
+        // Markets first, so trades in the same batch can be linked to them
+        for market in &batch.markets {
+            stats.markets_inserted += insert_market(&tx, market)?;
+        }
+        if stats.markets_inserted > 0 {
+            link_unmatched_trades(&tx)?;
+        }
+        for token in &batch.registrations {
+            insert_registration(&tx, token)?;
+        }
+
+        for trade in &batch.trades {
+            let (inserted, matched) = insert_trade(&tx, trade)?;
+            if inserted {
+                stats.trades_inserted += 1;
+                if !matched {
+                    stats.trades_unmatched += 1;
+                }
+            }
+        }
+
+        for (token_id, reason) in &batch.unresolved {
+            queue_unresolved(&tx, token_id, reason, &batch.trades, to_block)?;
+        }
+
+        tx.execute(
+            "INSERT INTO sync_state (key, last_block, last_block_hash, updated_at) VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+             ON CONFLICT(key) DO UPDATE SET
+                last_block = excluded.last_block,
+                last_block_hash = excluded.last_block_hash,
+                updated_at = excluded.updated_at",
+            params![sync_key, to_block as i64, to_block_hash],
+        )?;
+
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    /// Stores markets discovered through Gamma (already cross-validated by the caller): inserts
+    /// unknown ones, sets `slug`/`status` and fills a missing title on known ones, then links
+    /// previously unmatched trades to the new positions. Returns the number of markets inserted.
+    pub fn write_gamma_markets(&mut self, markets: &[(MarketInfo, GammaMarket)]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut inserted = 0;
+
+        for (market, gamma) in markets {
+            inserted += insert_market(&tx, market)?;
+            tx.execute(
+                "UPDATE markets SET slug = ?2, status = ?3,
+                    title = COALESCE(title, ?4), description = COALESCE(description, ?5)
+                 WHERE condition_id = ?1",
+                params![market.condition_id, gamma.slug, gamma.status.as_str(), market.title, market.description],
+            )?;
+        }
+
+        link_unmatched_trades(&tx)?;
+
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// Stores registrations scanned outside the indexer. Returns how many were new.
+    pub fn write_registrations(&mut self, tokens: &[RegisteredToken]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut inserted = 0;
+        for token in tokens {
+            inserted += insert_registration(&tx, token)?;
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// Every stored registration, to seed `Scanner::register_tokens`.
+    pub fn registered_tokens(&self) -> Result<Vec<RegisteredToken>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT token_id, complement_id, condition_id, outcome_index, exchange, venue, block_number
+             FROM token_registry ORDER BY block_number, token_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RegisteredToken {
+                token_id: row.get(0)?,
+                complement_id: row.get(1)?,
+                condition_id: row.get(2)?,
+                outcome_index: row.get(3)?,
+                exchange: row.get(4)?,
+                venue: venue_from_name(&row.get::<_, String>(5)?),
+                block_number: row.get::<_, i64>(6)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// True when `token_id` (hex or decimal) is a position of a stored market.
+    pub fn is_known_token(&self, token_id: &str) -> Result<bool> {
+        let token_id = canonical_token_id(token_id).unwrap_or_else(|| token_id.to_string());
+        let found: Option<i64> = self.conn
+            .query_row("SELECT 1 FROM market_positions WHERE position_id = ?1 LIMIT 1", params![token_id], |row| row.get(0))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// The unresolved-token queue, most traded first.
+    pub fn unresolved_tokens(&self) -> Result<Vec<UnresolvedToken>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT token_id, first_seen_block, last_seen_block, trade_count, attempts, reason
+             FROM unresolved_tokens ORDER BY trade_count DESC, token_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(UnresolvedToken {
+                token_id: row.get(0)?,
+                first_seen_block: row.get::<_, i64>(1)? as u64,
+                last_seen_block: row.get::<_, i64>(2)? as u64,
+                trade_count: row.get::<_, i64>(3)? as u64,
+                attempts: row.get::<_, i64>(4)? as u64,
+                reason: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn market_by_slug(&self, slug: &str) -> Result<Option<MarketInfo>> {
+        self.load_market("slug", slug)
+    }
+
+    pub fn market_by_condition_id(&self, condition_id: &str) -> Result<Option<MarketInfo>> {
+        self.load_market("condition_id", &condition_id.to_lowercase())
+    }
+
+    /// Rebuilds a stored market and its positions. `column` is a trusted column name.
+    fn load_market(&self, column: &str, value: &str) -> Result<Option<MarketInfo>> {
+        let row = self.conn
+            .query_row(
+                &format!(
+                    "SELECT id, condition_id, question_id, title, description, oracle, collateral_token,
+                            enable_neg_risk, block_number, block_hash, created_at
+                     FROM markets WHERE {} = ?1",
+                    column,
+                ),
+                params![value],
+                |row| {
+                    let neg_risk: bool = row.get(7)?;
+                    Ok((row.get::<_, i64>(0)?, MarketInfo {
+                        condition_id: row.get(1)?,
+                        question_id: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                        title: row.get(3)?,
+                        description: row.get(4)?,
+                        oracle: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                        outcome_slot_count: 0,
+                        collateral_token: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                        neg_risk,
+                        adapter: neg_risk.then(|| NEG_RISK_ADAPTER_ADDRESS.to_lowercase()),
+                        outcomes: Vec::new(),
+                        combined_positions: Vec::new(),
+                        block_number: row.get::<_, Option<i64>>(8)?.map(|b| b as u64),
+                        block_hash: row.get(9)?,
+                        timestamp: row.get::<_, Option<i64>>(10)?.map(|t| t as u64),
+                    }))
+                },
+            )
+            .optional()?;
+        let Some((market_id, mut market)) = row else { return Ok(None) };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT position_id, index_set, collection_id FROM market_positions WHERE market_id = ?1 ORDER BY index_set",
+        )?;
+        let positions = stmt.query_map(params![market_id], |row| {
+            Ok(OutcomePosition {
+                position_id: row.get(0)?,
+                index_set: row.get::<_, i64>(1)? as u64,
+                collection_id: row.get(2)?,
+            })
+        })?;
+        for position in positions {
+            let position = position?;
+            // Single outcomes have one bit set; ordered by index set they are in slot order
+            if position.index_set.is_power_of_two() {
+                market.outcomes.push(position);
+            } else {
+                market.combined_positions.push(position);
+            }
+        }
+        market.outcome_slot_count = market.outcomes.len() as u64;
+        Ok(Some(market))
+    }
+
+    /// Stored fills matching `filter`, in chain order.
+    pub fn trades(&self, filter: &TradeFilter) -> Result<Vec<TradeOutput>> {
+        let mut sql = String::from(
+            "SELECT t.tx_hash, t.log_index, t.order_hash, t.match_log_index, t.block_number, t.block_hash,
+                    t.exchange, t.venue, t.maker, t.taker, t.maker_asset_id, t.taker_asset_id,
+                    t.maker_amount_filled, t.taker_amount_filled, t.maker_decimals, t.taker_decimals,
+                    t.price, t.size, t.fee, t.fee_usdc, t.fee_bps, t.token_id, t.side,
+                    COALESCE(r.condition_id, m.condition_id), r.complement_id, r.outcome_index,
+                    t.taker_side, t.taker_token_id, t.complementary, t.timestamp
+             FROM trades t
+             LEFT JOIN markets m ON m.id = t.market_id
+             LEFT JOIN token_registry r ON r.token_id = t.token_id
+             WHERE 1 = 1",
+        );
+        let mut values: Vec<Value> = Vec::new();
+        let mut condition = |clause: &str, value: Value| {
+            values.push(value);
+            sql.push_str(&format!(" AND {} ?{}", clause, values.len()));
+        };
+
+        if let Some(id) = &filter.condition_id {
+            condition("m.condition_id =", Value::Text(id.to_lowercase()));
+        }
+        if let Some(hash) = &filter.tx_hash {
+            condition("t.tx_hash =", Value::Text(hash.to_lowercase()));
+        }
+        if let Some(maker) = &filter.maker {
+            condition("t.maker =", Value::Text(maker.to_lowercase()));
+        }
+        if let Some(taker) = &filter.taker {
+            condition("t.taker =", Value::Text(taker.to_lowercase()));
+        }
+        if let Some(side) = filter.side {
+            condition("t.side =", Value::Text(format!("{:?}", side)));
+        }
+        if let Some(outcome) = &filter.outcome {
+            condition("t.outcome =", Value::Text(outcome.to_uppercase()));
+        }
+        if let Some(from) = filter.from_time {
+            condition("t.timestamp >=", Value::Integer(from as i64));
+        }
+        if let Some(to) = filter.to_time {
+            condition("t.timestamp <=", Value::Integer(to as i64));
+        }
+
+        sql.push_str(" ORDER BY t.block_number, t.log_index");
+        // SQLite needs a LIMIT before OFFSET; -1 means unbounded
+        values.push(Value::Integer(filter.limit.map_or(-1, |l| l as i64)));
+        values.push(Value::Integer(filter.offset as i64));
+        sql.push_str(&format!(" LIMIT ?{} OFFSET ?{}", values.len() - 1, values.len()));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values), trade_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    #[cfg(test)]
+    pub fn count_trades(&self) -> Result<u64> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM trades", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+}
+
+fn insert_market(tx: &Transaction, market: &MarketInfo) -> Result<usize> {
+    let canonical = |id: &str| canonical_token_id(id).unwrap_or_else(|| id.to_string());
+
+    let inserted = tx.execute(
+        "INSERT INTO markets (
+            condition_id, question_id, title, description, oracle, collateral_token, yes_token_id, no_token_id,
+            enable_neg_risk, status, created_at, block_number, block_hash
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'active', ?10, ?11, ?12)
+         ON CONFLICT(condition_id) DO NOTHING",
+        params![
+            market.condition_id,
+            market.question_id,
+            market.title,
+            market.description,
+            market.oracle,
+            market.collateral_token,
+            market.yes_token_id().map(canonical),
+            market.no_token_id().map(canonical),
+            market.neg_risk,
+            market.timestamp.map(|t| t as i64),
+            market.block_number.map(|b| b as i64),
+            market.block_hash,
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(0);
+    }
+
+    let market_id = tx.last_insert_rowid();
+    let singles = market.outcomes.iter().enumerate().map(|(slot, p)| (p, Some(outcome_label(slot, market.is_binary()))));
+    let combined = market.combined_positions.iter().map(|p| (p, None));
+    for (position, outcome) in singles.chain(combined) {
+        tx.execute(
+            "INSERT INTO market_positions (market_id, position_id, index_set, collection_id, outcome)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![market_id, canonical(&position.position_id), position.index_set as i64, position.collection_id, outcome],
+        )?;
+    }
+    Ok(inserted)
+}
+
+/// Returns (inserted, linked_to_market). Duplicate `(tx_hash, log_index)` rows are ignored.
+fn insert_trade(tx: &Transaction, trade: &TradeOutput) -> Result<(bool, bool)> {
+    let token_id = canonical_token_id(&trade.token_id).unwrap_or_else(|| trade.token_id.clone());
+
+    let market: Option<(i64, Option<String>)> = tx
+        .query_row(
+            "SELECT market_id, outcome FROM market_positions WHERE position_id = ?1 LIMIT 1",
+            params![token_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let (market_id, outcome) = match &market {
+        Some((id, outcome)) => (Some(*id), outcome.as_deref()),
+        None => (None, None),
+    };
+
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO trades (
+            market_id, tx_hash, log_index, order_hash, match_log_index, exchange, venue, maker, taker,
+            maker_asset_id, taker_asset_id, maker_amount_filled, taker_amount_filled, maker_decimals, taker_decimals,
+            token_id, side, taker_side, taker_token_id, complementary, outcome, price, size, fee, fee_usdc, fee_bps,
+            timestamp, block_number, block_hash
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
+        params![
+            market_id,
+            trade.tx_hash,
+            trade.log_index as i64,
+            trade.order_hash,
+            trade.match_log_index.map(|i| i as i64),
+            trade.exchange,
+            venue_name(trade.venue),
+            trade.maker,
+            trade.taker,
+            trade.maker_asset_id,
+            trade.taker_asset_id,
+            trade.maker_amount_filled,
+            trade.taker_amount_filled,
+            trade.maker_decimals,
+            trade.taker_decimals,
+            token_id,
+            format!("{:?}", trade.side),
+            format!("{:?}", trade.taker_action.side),
+            canonical_token_id(&trade.taker_action.token_id).unwrap_or_else(|| trade.taker_action.token_id.clone()),
+            trade.complementary,
+            outcome,
+            trade.price.to_string(),
+            trade.size.to_string(),
+            trade.fee,
+            trade.fee_usdc.to_string(),
+            trade.fee_bps.to_string(),
+            trade.timestamp.map(|t| t as i64),
+            trade.block_number as i64,
+            trade.block_hash,
+        ],
+    )? > 0;
+
+    // A fill on the NegRisk exchange marks its market as negative risk
+    if inserted && let Some(id) = market_id && trade.venue == crate::models::ExchangeVenue::NegRisk {
+        tx.execute("UPDATE markets SET enable_neg_risk = 1 WHERE id = ?1", params![id])?;
+    }
+
+    Ok((inserted, market_id.is_some()))
+}
+
+/// Attaches stored-but-unmatched trades to newly known positions and drops their queue entries.
+fn link_unmatched_trades(tx: &Transaction) -> Result<()> {
+    tx.execute(
+        "UPDATE trades SET market_id = p.market_id, outcome = p.outcome
+         FROM market_positions p
+         WHERE trades.market_id IS NULL AND trades.token_id = p.position_id",
+        [],
+    )?;
+    tx.execute("DELETE FROM unresolved_tokens WHERE token_id IN (SELECT position_id FROM market_positions)", [])?;
+    Ok(())
+}
+
+/// Adds `token_id`'s fills from `trades` to its queue entry, creating it on first sight.
+/// `to_block` is the last block of the batch making the attempt.
+fn queue_unresolved(tx: &Transaction, token_id: &str, reason: &str, trades: &[TradeOutput], to_block: u64) -> Result<()> {
+    let token_id = canonical_token_id(token_id).unwrap_or_else(|| token_id.to_string());
+    let blocks: Vec<u64> = trades.iter()
+        .filter(|t| canonical_token_id(&t.token_id).as_deref() == Some(token_id.as_str()))
+        .map(|t| t.block_number)
+        .collect();
+    let (Some(first), Some(last)) = (blocks.iter().min(), blocks.iter().max()) else { return Ok(()) };
+
+    tx.execute(
+        "INSERT INTO unresolved_tokens (token_id, first_seen_block, last_seen_block, trade_count, attempts, reason, updated_at)
+         VALUES (?1, ?2, ?3, ?4, 1, ?5, CURRENT_TIMESTAMP)
+         ON CONFLICT(token_id) DO UPDATE SET
+            first_seen_block = MIN(first_seen_block, excluded.first_seen_block),
+            last_seen_block = MAX(last_seen_block, excluded.last_seen_block),
+            trade_count = trade_count + excluded.trade_count,
+            attempts = attempts + 1,
+            reason = excluded.reason,
+            updated_at = excluded.updated_at",
+        params![token_id, *first as i64, *last as i64, blocks.len() as i64, reason],
+    )?;
+    tx.execute(
+        "INSERT OR IGNORE INTO unresolved_attempts (token_id, to_block) VALUES (?1, ?2)",
+        params![token_id, to_block as i64],
+    )?;
+    Ok(())
+}
+
+/// Returns 1 when the token was not registered yet.
+fn insert_registration(tx: &Transaction, token: &RegisteredToken) -> Result<usize> {
+    let canonical = |id: &str| canonical_token_id(id).unwrap_or_else(|| id.to_string());
+    Ok(tx.execute(
+        "INSERT INTO token_registry (token_id, complement_id, condition_id, outcome_index, exchange, venue, block_number)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(token_id) DO NOTHING",
+        params![
+            canonical(&token.token_id),
+            canonical(&token.complement_id),
+            token.condition_id.to_lowercase(),
+            token.outcome_index,
+            token.exchange,
+            venue_name(token.venue),
+            token.block_number as i64,
+        ],
+    )?)
+}
+
+fn venue_name(venue: ExchangeVenue) -> &'static str {
+    match venue {
+        ExchangeVenue::Binary => "BINARY",
+        ExchangeVenue::NegRisk => "NEG_RISK",
+    }
+}
+
+fn venue_from_name(name: &str) -> ExchangeVenue {
+    match name {
+        "NEG_RISK" => ExchangeVenue::NegRisk,
+        _ => ExchangeVenue::Binary,
+    }
+}
+
+/// Inverse of `insert_trade`, for rows selected in `Database::trades` column order.
+fn trade_from_row(row: &Row) -> rusqlite::Result<TradeOutput> {
+    let amount = |idx: usize| -> rusqlite::Result<Amount> {
+        let text: String = row.get(idx)?;
+        Amount::from_str(&text).map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, e.into()))
+    };
+    let venue = venue_from_name(&row.get::<_, String>(7)?);
+    let side_at = |idx: usize| -> rusqlite::Result<TradeSide> {
+        Ok(match row.get::<_, String>(idx)?.as_str() {
+            "BUY" => TradeSide::BUY,
+            "SELL" => TradeSide::SELL,
+            _ => TradeSide::UNKNOWN,
+        })
+    };
+    let side = side_at(22)?;
+    let token_id: String = row.get(21)?;
+    let taker_token_id: String = row.get(27)?;
+
+    let mut trade = TradeOutput {
+        tx_hash: row.get(0)?,
+        log_index: row.get::<_, i64>(1)? as u64,
+        order_hash: row.get(2)?,
+        match_log_index: row.get::<_, Option<i64>>(3)?.map(|i| i as u64),
+        block_number: row.get::<_, i64>(4)? as u64,
+        block_hash: row.get(5)?,
+        timestamp: row.get::<_, Option<i64>>(29)?.map(|t| t as u64),
+        exchange: row.get(6)?,
+        venue,
+        maker: row.get(8)?,
+        taker: row.get(9)?,
+        maker_asset_id: row.get(10)?,
+        taker_asset_id: row.get(11)?,
+        maker_amount_filled: row.get(12)?,
+        taker_amount_filled: row.get(13)?,
+        maker_decimals: row.get(14)?,
+        taker_decimals: row.get(15)?,
+        price: amount(16)?,
+        size: amount(17)?,
+        fee: row.get(18)?,
+        fee_usdc: amount(19)?,
+        fee_bps: amount(20)?,
+        maker_action: TradeAction::new(side, &token_id),
+        taker_action: TradeAction::new(side_at(26)?, &taker_token_id),
+        complementary: row.get(28)?,
+        token_id,
+        side,
+        condition_id: row.get(23)?,
+        complement_token_id: row.get(24)?,
+        outcome_index: row.get(25)?,
+        asset_issues: Vec::new(),
+    };
+    trade.label_actions();
+    Ok(trade)
+}
+
+#[cfg(test)]
+#[path = "db_test.rs"]
+mod db_test;
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::{Batch, BatchStats, Database};
+    use crate::indexer::INDEXER_SYNC_KEY;
+    use crate::db::TradeFilter;
+    use crate::models::{ExchangeVenue, MarketInfo, RegisteredToken, TradeOutput};
+
+    fn market() -> MarketInfo {
+        // Zero-padded like H256 formatting; trades use the unpadded U256 form
+        MarketInfo::fixture(
+            "0x000000000000000000000000000000000000000000000000000000000000abcd",
+            "0x000000000000000000000000000000000000000000000000000000000000abce",
+        )
+    }
+
+    fn trade(log_index: u64, token_id: &str) -> TradeOutput {
+        trade_at(log_index, token_id, 95)
+    }
+
+    fn trade_at(log_index: u64, token_id: &str, block_number: u64) -> TradeOutput {
+        TradeOutput { log_index, ..TradeOutput::fixture(token_id).at_block(block_number) }
+    }
+
+    fn batch(markets: &[MarketInfo], trades: &[TradeOutput]) -> Batch {
+        Batch { markets: markets.to_vec(), trades: trades.to_vec(), ..Batch::default() }
+    }
+
+    #[test]
+    fn test_write_batch_links_trades_and_updates_sync_state() {
+        let mut db = Database::open_in_memory().unwrap();
+        assert_eq!(db.last_block(INDEXER_SYNC_KEY).unwrap(), None);
+
+        let stats = db.write_batch(INDEXER_SYNC_KEY, &batch(&[market()], &[trade(1, "0xabcd"), trade(2, "0xffff")]), 100, Some("0xb100")).unwrap();
+        assert_eq!(stats, BatchStats { markets_inserted: 1, trades_inserted: 2, trades_unmatched: 1 });
+        assert_eq!(db.last_block(INDEXER_SYNC_KEY).unwrap(), Some(100));
+
+        let (outcome, size): (String, String) = db.conn
+            .query_row("SELECT outcome, size FROM trades WHERE log_index = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(outcome, "YES");
+        assert_eq!(size, "1.250000");
+    }
+
+    #[test]
+    fn test_replaying_a_batch_is_idempotent() {
+        let mut db = Database::open_in_memory().unwrap();
+        let trades = [trade(1, "0xabcd"), trade(2, "0xabce")];
+
+        db.write_batch(INDEXER_SYNC_KEY, &batch(&[market()], &trades), 100, None).unwrap();
+        let replay = db.write_batch(INDEXER_SYNC_KEY, &batch(&[market()], &trades), 100, None).unwrap();
+
+        assert_eq!(replay, BatchStats::default());
+        assert_eq!(db.count_trades().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rollback_removes_reorged_range_and_rewinds_sync_state() {
+        let mut db = Database::open_in_memory().unwrap();
+        let trades = [
+            trade_at(1, "0xabcd", 95),
+            trade_at(2, "0xabcd", 120),
+            trade_at(3, "0xabce", 150),
+        ];
+        db.write_batch(INDEXER_SYNC_KEY, &batch(&[market()], &trades), 150, Some("0xb150")).unwrap();
+
+        assert_eq!(
+            db.stored_block_hashes(0, 150).unwrap(),
+            vec![(150, "0xb150".to_string()), (120, "0xb120".to_string()), (95, "0xb95".to_string()), (90, "0xb90".to_string())],
+        );
+
+        let (trades_deleted, markets_deleted) = db.rollback_to(INDEXER_SYNC_KEY, 120, Some("0xb120")).unwrap();
+        assert_eq!((trades_deleted, markets_deleted), (1, 0));
+        assert_eq!(db.sync_tip(INDEXER_SYNC_KEY).unwrap(), Some((120, Some("0xb120".to_string()))));
+        assert_eq!(db.count_trades().unwrap(), 2);
+
+        // Rolling back below the market's block drops it too
+        let (trades_deleted, markets_deleted) = db.rollback_to(INDEXER_SYNC_KEY, 80, None).unwrap();
+        assert_eq!((trades_deleted, markets_deleted), (2, 1));
+        assert_eq!(db.last_block(INDEXER_SYNC_KEY).unwrap(), Some(80));
+    }
+
+    #[test]
+    fn test_unresolved_tokens_queue_until_their_market_arrives() {
+        let mut db = Database::open_in_memory().unwrap();
+        let unresolved = |reason: &str| vec![("0xabcd".to_string(), reason.to_string())];
+
+        let first = Batch { unresolved: unresolved("no TokenRegistered log"), ..batch(&[], &[trade_at(1, "0xabcd", 95)]) };
+        db.write_batch(INDEXER_SYNC_KEY, &first, 100, None).unwrap();
+        let second = Batch {
+            unresolved: unresolved("not listed on Gamma"),
+            ..batch(&[], &[trade_at(2, "0xabcd", 120), trade_at(3, "0xabcd", 130)])
+        };
+        db.write_batch(INDEXER_SYNC_KEY, &second, 200, None).unwrap();
+
+        let queue = db.unresolved_tokens().unwrap();
+        assert_eq!(queue.len(), 1);
+        let token = &queue[0];
+        assert_eq!((token.first_seen_block, token.last_seen_block), (95, 130));
+        assert_eq!((token.trade_count, token.attempts), (3, 2));
+        assert_eq!(token.reason, "not listed on Gamma");
+        assert!(!db.is_known_token("43981").unwrap());
+
+        // The market showing up later links the stored trades and clears the queue entry
+        let stats = db.write_batch(INDEXER_SYNC_KEY, &batch(&[market()], &[]), 300, None).unwrap();
+        assert_eq!(stats.markets_inserted, 1);
+        assert!(db.is_known_token("43981").unwrap());
+        assert!(db.unresolved_tokens().unwrap().is_empty());
+        let linked: i64 = db.conn
+            .query_row("SELECT COUNT(*) FROM trades WHERE market_id IS NOT NULL AND outcome = 'YES'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(linked, 3);
+    }
+
+    #[test]
+    fn test_rollback_takes_back_unresolved_attempts() {
+        let mut db = Database::open_in_memory().unwrap();
+        let unresolved = vec![("0xabcd".to_string(), "no TokenRegistered log".to_string())];
+        let first = Batch { unresolved: unresolved.clone(), ..batch(&[], &[trade_at(1, "0xabcd", 95)]) };
+        db.write_batch(INDEXER_SYNC_KEY, &first, 100, None).unwrap();
+        let second = Batch {
+            unresolved,
+            ..batch(&[], &[trade_at(2, "0xabcd", 120), trade_at(3, "0xabcd", 130)])
+        };
+        db.write_batch(INDEXER_SYNC_KEY, &second, 200, None).unwrap();
+
+        db.rollback_to(INDEXER_SYNC_KEY, 125, None).unwrap();
+        let token = &db.unresolved_tokens().unwrap()[0];
+        assert_eq!((token.first_seen_block, token.last_seen_block), (95, 120));
+        assert_eq!((token.trade_count, token.attempts), (2, 1));
+    }
+
+    #[test]
+    fn test_registrations_persist_and_label_stored_trades() {
+        let mut db = Database::open_in_memory().unwrap();
+        let registered = RegisteredToken {
+            token_id: "0xabce".to_string(),
+            complement_id: "0x000000000000000000000000000000000000000000000000000000000000abcd".to_string(),
+            condition_id: "0xC0".to_string(),
+            outcome_index: Some(1),
+            exchange: "0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e".to_string(),
+            venue: ExchangeVenue::Binary,
+            block_number: 90,
+        };
+        let registered_batch = Batch { registrations: vec![registered], ..batch(&[], &[trade(1, "0xabce"), trade(2, "0xffff")]) };
+        db.write_batch(INDEXER_SYNC_KEY, &registered_batch, 100, None).unwrap();
+
+        let stored = db.registered_tokens().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].complement_id, "0xabcd");
+        assert_eq!(stored[0].condition_id, "0xc0");
+
+        // Labels come from the registry even though no market is stored
+        let trades = db.trades(&TradeFilter::default()).unwrap();
+        assert_eq!(trades[0].condition_id.as_deref(), Some("0xc0"));
+        assert_eq!(trades[0].complement_token_id.as_deref(), Some("0xabcd"));
+        assert_eq!(trades[0].outcome_index, Some(1));
+        assert_eq!(trades[0].maker_action.to_string(), "BUY NO");
+        assert_eq!(trades[0].taker_action.to_string(), "SELL NO");
+        assert_eq!(trades[1].condition_id, None);
+
+        db.rollback_to(INDEXER_SYNC_KEY, 80, None).unwrap();
+        assert!(db.registered_tokens().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::gamma::{cross_validate, market_info, GammaClient};
+use crate::models::MarketInfo;
+use crate::scanner::Scanner;
+use crate::utils::parse_token_id;
+use anyhow::Result;
+
+/// Where an unknown token's market came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionSource {
+    /// Condition from the exchange's `TokenRegistered`, market from its `ConditionPreparation`
+    TokenRegistered,
+    /// A `ConditionPreparation` in the range whose derived position IDs include the token
+    ConditionPreparation,
+    /// Gamma listing, accepted only if its IDs match on-chain derivation
+    Gamma,
+}
+
+#[derive(Debug)]
+pub enum TokenResolution {
+    Resolved { market: Box<MarketInfo>, source: ResolutionSource },
+    /// Every source failed; the reason names each one tried
+    Unresolved(String),
+}
+
+/// Maps token IDs seen in trades to their markets, trying in order: the exchange's
+/// `TokenRegistered` (condition, then its `ConditionPreparation`), position IDs derived from every
+/// `ConditionPreparation` in the range, and Gamma.
+pub struct TokenResolver<'a> {
+    scanner: &'a Scanner,
+    gamma: Option<GammaClient>,
+    /// First block searched for `TokenRegistered` / `ConditionPreparation`
+    from_block: u64,
+}
+
+impl<'a> TokenResolver<'a> {
+    pub fn new(scanner: &'a Scanner, from_block: u64) -> Self {
+        Self { scanner, gamma: None, from_block }
+    }
+
+    pub fn with_gamma(mut self, gamma: GammaClient) -> Self {
+        self.gamma = Some(gamma);
+        self
+    }
+
+    pub async fn resolve(&self, token_id: &str) -> Result<TokenResolution> {
+        let Some(token) = parse_token_id(token_id) else {
+            return Ok(TokenResolution::Unresolved(format!("invalid token ID {}", token_id)));
+        };
+
+        // RPC failures (e.g. a provider refusing even a split-down eth_getLogs) are recorded in
+        // the reason and fall through to Gamma instead of aborting the batch
+        let registered = match self.scanner.fetch_token_condition(token, self.from_block).await {
+            Ok(Some(condition_id)) => match self.scanner.fetch_market_info_by_condition_id(condition_id, Some(self.from_block)).await {
+                Ok(Some(market)) if market.outcome_label(token_id).is_some() => {
+                    return Ok(TokenResolution::Resolved { market: Box::new(market), source: ResolutionSource::TokenRegistered });
+                }
+                Ok(Some(_)) => format!("token is not a position of condition {:?}", condition_id),
+                Ok(None) => format!("no ConditionPreparation for condition {:?}", condition_id),
+                Err(e) => format!("ConditionPreparation lookup failed: {}", e),
+            },
+            Ok(None) => "no TokenRegistered log".to_string(),
+            Err(e) => format!("TokenRegistered lookup failed: {}", e),
+        };
+
+        // Tokens traded before (or without) their registration: derive every prepared market's positions
+        let prepared = match self.scanner.fetch_market_by_position_id(token_id, self.from_block).await {
+            Ok(Some(market)) => {
+                return Ok(TokenResolution::Resolved { market: Box::new(market), source: ResolutionSource::ConditionPreparation });
+            }
+            Ok(None) => "no prepared condition derives the token".to_string(),
+            Err(e) => format!("ConditionPreparation scan failed: {}", e),
+        };
+        let reason = format!("{}; {}", registered, prepared);
+
+        let Some(gamma) = &self.gamma else {
+            return Ok(TokenResolution::Unresolved(reason));
+        };
+        let listed = match gamma.fetch_market_by_token(token_id).await {
+            Ok(Some(listed)) => listed,
+            Ok(None) => return Ok(TokenResolution::Unresolved(format!("{}; not listed on Gamma", reason))),
+            Err(e) => return Ok(TokenResolution::Unresolved(format!("{}; Gamma lookup failed: {}", reason, e))),
+        };
+        let check = cross_validate(&listed);
+        if !check.is_valid() {
+            return Ok(TokenResolution::Unresolved(format!("{}; Gamma market {} failed validation: {}", reason, listed.slug, check.issues.join("; "))));
+        }
+        match market_info(&listed) {
+            Ok(market) if market.outcome_label(token_id).is_some() => {
+                Ok(TokenResolution::Resolved { market: Box::new(market), source: ResolutionSource::Gamma })
+            }
+            Ok(_) => Ok(TokenResolution::Unresolved(format!("{}; Gamma market {} does not contain the token", reason, listed.slug))),
+            Err(e) => Ok(TokenResolution::Unresolved(format!("{}; Gamma market {}: {}", reason, listed.slug, e))),
+        }
+    }
+}
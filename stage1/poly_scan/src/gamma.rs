@@ -0,0 +1,258 @@
+use crate::consts::{NEG_RISK_ADAPTER_ADDRESS, NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, USDC_ADDRESS};
+use crate::models::{GammaCheck, GammaEvent, GammaMarket, GammaStatus, MarketInfo};
+use crate::utils::{derive_market_info, get_condition_id, outcome_position, parse_token_id};
+use anyhow::Result;
+use ethers::types::{Address, H256, U256};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Markets (or events) requested per Gamma page.
+pub const DEFAULT_GAMMA_PAGE_SIZE: usize = 100;
+
+/// Filters for `/markets` and `/events`. None leaves the API default.
+#[derive(Debug, Clone, Default)]
+pub struct GammaQuery {
+    pub active: Option<bool>,
+    pub closed: Option<bool>,
+    /// Only markets listing these decimal token IDs
+    pub clob_token_ids: Vec<String>,
+    /// Stop after this many records
+    pub max_results: Option<usize>,
+}
+
+/// Client for the Gamma market-discovery API (`/markets`, `/events`), paging with limit/offset.
+pub struct GammaClient {
+    http: reqwest::Client,
+    base_url: String,
+    page_size: usize,
+}
+
+impl GammaClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            page_size: DEFAULT_GAMMA_PAGE_SIZE,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    pub async fn fetch_markets(&self, query: &GammaQuery) -> Result<Vec<GammaMarket>> {
+        let raw: Vec<RawMarket> = self.paginate("markets", query).await?;
+        Ok(raw.into_iter().map(|m| m.normalize(None)).collect())
+    }
+
+    /// The market listing `token_id` (hex or decimal), open or closed.
+    pub async fn fetch_market_by_token(&self, token_id: &str) -> Result<Option<GammaMarket>> {
+        let token = parse_token_id(token_id).ok_or_else(|| anyhow::anyhow!("Invalid token ID {}", token_id))?;
+        let query = GammaQuery { clob_token_ids: vec![token.to_string()], max_results: Some(1), ..GammaQuery::default() };
+        Ok(self.fetch_markets(&query).await?.into_iter().next())
+    }
+
+    /// Events with their nested markets; each market's `event_slug` points back to its event.
+    pub async fn fetch_events(&self, query: &GammaQuery) -> Result<Vec<GammaEvent>> {
+        let raw: Vec<RawEvent> = self.paginate("events", query).await?;
+        Ok(raw.into_iter().map(RawEvent::normalize).collect())
+    }
+
+    /// Requests pages until one comes back short or `max_results` is reached.
+    async fn paginate<T: DeserializeOwned>(&self, path: &str, query: &GammaQuery) -> Result<Vec<T>> {
+        let mut results = Vec::new();
+        loop {
+            let remaining = query.max_results.map_or(usize::MAX, |max| max.saturating_sub(results.len()));
+            if remaining == 0 {
+                break;
+            }
+            let limit = self.page_size.min(remaining);
+
+            let mut params = vec![("limit", limit.to_string()), ("offset", results.len().to_string())];
+            if let Some(active) = query.active {
+                params.push(("active", active.to_string()));
+            }
+            if let Some(closed) = query.closed {
+                params.push(("closed", closed.to_string()));
+            }
+            params.extend(query.clob_token_ids.iter().map(|id| ("clob_token_ids", id.clone())));
+
+            let url = format!("{}/{}", self.base_url, path);
+            let page: Vec<T> = self.http.get(&url).query(&params).send().await?
+                .error_for_status()?
+                .json().await?;
+            let short = page.len() < limit;
+            results.extend(page);
+            if short {
+                break;
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Re-derives the condition ID (when the oracle and question ID are known) and every outcome's
+/// position ID, and reports any disagreement with what Gamma lists.
+pub fn cross_validate(market: &GammaMarket) -> GammaCheck {
+    let mut issues = Vec::new();
+    let mut expected_token_ids = Vec::new();
+
+    if market.clob_token_ids.len() != market.outcomes.len() {
+        issues.push(format!("{} clobTokenIds for {} outcomes", market.clob_token_ids.len(), market.outcomes.len()));
+    }
+
+    match H256::from_str(&market.condition_id) {
+        Err(_) => issues.push(format!("invalid conditionId {}", market.condition_id)),
+        Ok(condition_id) => {
+            let question_id = market.question_id.as_deref().and_then(|q| H256::from_str(q).ok());
+            if let (Some(oracle), Some(question_id)) = (market_oracle(market), question_id) {
+                let derived = get_condition_id(oracle, question_id, U256::from(market.outcomes.len()));
+                if derived != condition_id {
+                    issues.push(format!("conditionId {} != derived {:?}", market.condition_id, derived));
+                }
+            }
+
+            let collateral = if market.neg_risk { NEG_RISK_WRAPPED_COLLATERAL_ADDRESS } else { USDC_ADDRESS };
+            let collateral = Address::from_str(collateral).unwrap_or_default();
+            for (slot, listed) in market.clob_token_ids.iter().enumerate() {
+                let position = outcome_position(collateral, condition_id, 1u64 << slot.min(63));
+                let derived = parse_token_id(&position.position_id).unwrap_or_default();
+                expected_token_ids.push(derived.to_string());
+                if parse_token_id(listed) != Some(derived) {
+                    issues.push(format!("token {} ({}) != derived {}", slot, listed, derived));
+                }
+            }
+        }
+    }
+
+    GammaCheck { market: market.clone(), expected_token_ids, issues }
+}
+
+/// On-chain view of a Gamma market, for storage. Requires an oracle (the NegRiskAdapter for
+/// neg-risk markets, `resolvedBy` otherwise) and a question ID.
+pub fn market_info(market: &GammaMarket) -> Result<MarketInfo> {
+    let condition_id = H256::from_str(&market.condition_id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?;
+    let question_id = market.question_id.as_deref()
+        .and_then(|q| H256::from_str(q).ok())
+        .ok_or_else(|| anyhow::anyhow!("Market {} has no question ID", market.slug))?;
+    let oracle = market_oracle(market).ok_or_else(|| anyhow::anyhow!("Market {} has no oracle", market.slug))?;
+
+    let mut info = derive_market_info(condition_id, question_id, oracle, market.outcomes.len() as u64, market.neg_risk)?;
+    info.title = market.question.clone();
+    info.description = market.description.clone();
+    Ok(info)
+}
+
+/// The condition's oracle: the NegRiskAdapter prepares every neg-risk question itself.
+fn market_oracle(market: &GammaMarket) -> Option<Address> {
+    if market.neg_risk {
+        return Address::from_str(NEG_RISK_ADAPTER_ADDRESS).ok();
+    }
+    market.resolved_by.as_deref().and_then(|a| Address::from_str(a).ok())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMarket {
+    #[serde(default)]
+    id: Value,
+    slug: Option<String>,
+    question: Option<String>,
+    description: Option<String>,
+    condition_id: Option<String>,
+    #[serde(rename = "questionID")]
+    question_id: Option<String>,
+    clob_token_ids: Option<Value>,
+    outcomes: Option<Value>,
+    neg_risk: Option<bool>,
+    active: Option<bool>,
+    closed: Option<bool>,
+    archived: Option<bool>,
+    resolved_by: Option<String>,
+}
+
+impl RawMarket {
+    fn normalize(self, event_slug: Option<&str>) -> GammaMarket {
+        let status = if self.archived == Some(true) {
+            GammaStatus::Archived
+        } else if self.closed == Some(true) {
+            GammaStatus::Closed
+        } else if self.active == Some(true) {
+            GammaStatus::Active
+        } else {
+            GammaStatus::Inactive
+        };
+
+        GammaMarket {
+            id: value_string(&self.id),
+            slug: self.slug.unwrap_or_default(),
+            question: self.question,
+            description: self.description,
+            condition_id: self.condition_id.unwrap_or_default(),
+            question_id: self.question_id.filter(|q| !q.is_empty()),
+            clob_token_ids: string_list(self.clob_token_ids),
+            outcomes: string_list(self.outcomes),
+            neg_risk: self.neg_risk.unwrap_or(false),
+            status,
+            resolved_by: self.resolved_by.filter(|a| !a.is_empty()),
+            event_slug: event_slug.map(str::to_string),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+    #[serde(default)]
+    id: Value,
+    slug: Option<String>,
+    title: Option<String>,
+    neg_risk: Option<bool>,
+    #[serde(default)]
+    markets: Vec<RawMarket>,
+}
+
+impl RawEvent {
+    fn normalize(self) -> GammaEvent {
+        let slug = self.slug.unwrap_or_default();
+        let neg_risk = self.neg_risk.unwrap_or(false);
+        let markets = self.markets.into_iter()
+            .map(|m| {
+                let mut market = m.normalize(Some(&slug));
+                // Nested markets sometimes omit the flag their event carries
+                market.neg_risk |= neg_risk;
+                market
+            })
+            .collect();
+        GammaEvent { id: value_string(&self.id), slug, title: self.title, neg_risk, markets }
+    }
+}
+
+/// Gamma encodes lists such as `clobTokenIds` as JSON strings ("[\"1\", \"2\"]"); accepts real arrays too.
+fn string_list(value: Option<Value>) -> Vec<String> {
+    let value = match value {
+        Some(Value::String(s)) => serde_json::from_str(&s).unwrap_or(Value::Null),
+        Some(v) => v,
+        None => Value::Null,
+    };
+    match value {
+        Value::Array(items) => items.iter().map(value_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn value_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "gamma_test.rs"]
+mod gamma_test;
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::{CONDITION_PREPARATION_EVENT_SIGNATURE, CTF_ADDRESS, NEG_RISK_ADAPTER_ADDRESS, UMA_CTF_ADAPTER_ADDRESSES};
+    use crate::db::Database;
+    use crate::discovery::{ResolutionSource, TokenResolution, TokenResolver};
+    use crate::gamma::{cross_validate, market_info, GammaClient, GammaQuery};
+    use crate::models::GammaStatus;
+    use crate::scanner::Scanner;
+    use crate::utils::{derive_market_info, get_condition_id, parse_token_id};
+    use crate::timestamps::TimestampedLog;
+    use ethers::types::{Address, Bytes, Log, H256, U256, U64};
+    use ethers::utils::keccak256;
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Gamma-shaped market whose IDs are derived exactly as on-chain.
+    fn gamma_market(slug: &str, question_byte: u8, neg_risk: bool) -> Value {
+        let oracle = Address::from_str(if neg_risk { NEG_RISK_ADAPTER_ADDRESS } else { UMA_CTF_ADAPTER_ADDRESSES[0] }).unwrap();
+        let question_id = H256::repeat_byte(question_byte);
+        let condition_id = get_condition_id(oracle, question_id, U256::from(2));
+        let info = derive_market_info(condition_id, question_id, oracle, 2, neg_risk).unwrap();
+        let token_ids: Vec<String> = info.outcomes.iter()
+            .map(|o| parse_token_id(&o.position_id).unwrap().to_string())
+            .collect();
+
+        json!({
+            "id": "501",
+            "slug": slug,
+            "question": format!("Question {}?", slug),
+            "conditionId": format!("{:?}", condition_id),
+            "questionID": format!("{:?}", question_id),
+            // Gamma sends these lists JSON-encoded
+            "clobTokenIds": serde_json::to_string(&token_ids).unwrap(),
+            "outcomes": "[\"Yes\", \"No\"]",
+            "negRisk": neg_risk,
+            "active": true,
+            "closed": false,
+            "resolvedBy": format!("{:?}", Address::from_str(UMA_CTF_ADAPTER_ADDRESSES[0]).unwrap()),
+        })
+    }
+
+    /// Minimal HTTP/1.1 server: answers `GET /markets` with `pages[offset / page_size]` (empty
+    /// past the end) and records every request target.
+    async fn serve(pages: Vec<Value>, page_size: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let target = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                seen.lock().unwrap().push(target.clone());
+
+                let offset: usize = target.split(['?', '&'])
+                    .find_map(|p| p.strip_prefix("offset="))
+                    .and_then(|o| o.parse().ok())
+                    .unwrap_or(0);
+                let body = pages.get(offset / page_size).cloned().unwrap_or_else(|| json!([])).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body,
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    /// Minimal JSON-RPC over HTTP: `eth_getLogs` returns `preparations` for `ConditionPreparation`
+    /// filters and nothing for any other event.
+    async fn serve_rpc(preparations: Vec<TimestampedLog>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let preparation_topic = format!("{:?}", H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE)));
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = vec![0u8; 8192];
+                // Read until the JSON body is complete
+                let body: Value = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((_, body)) = text.split_once("\r\n\r\n")
+                        && let Ok(body) = serde_json::from_str(body) {
+                        break body;
+                    }
+                };
+
+                let result = match body["method"].as_str() {
+                    Some("eth_blockNumber") => json!("0x200"),
+                    Some("eth_getLogs") => {
+                        let topic0 = &body["params"][0]["topics"][0];
+                        let topic0 = topic0.as_array().and_then(|t| t.first()).unwrap_or(topic0);
+                        if topic0.as_str() == Some(preparation_topic.as_str()) { json!(preparations) } else { json!([]) }
+                    }
+                    _ => Value::Null,
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(), reply,
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_pages_markets_and_flags_mismatches() {
+        let mut tampered = gamma_market("tampered", 0x0c, false);
+        tampered["clobTokenIds"] = json!("[\"1\", \"2\"]");
+        let pages = vec![
+            json!([gamma_market("first", 0x0a, false), gamma_market("neg-risk", 0x0b, true)]),
+            json!([tampered]),
+        ];
+        let (url, requests) = serve(pages, 2).await;
+
+        let client = GammaClient::new(&url).with_page_size(2);
+        let query = GammaQuery { closed: Some(false), ..GammaQuery::default() };
+        let markets = client.fetch_markets(&query).await.unwrap();
+
+        // The short second page ends paging
+        assert_eq!(markets.len(), 3);
+        assert_eq!(*requests.lock().unwrap(), vec![
+            "/markets?limit=2&offset=0&closed=false".to_string(),
+            "/markets?limit=2&offset=2&closed=false".to_string(),
+        ]);
+
+        let first = &markets[0];
+        assert_eq!(first.slug, "first");
+        assert_eq!(first.outcomes, vec!["Yes".to_string(), "No".to_string()]);
+        assert_eq!(first.status, GammaStatus::Active);
+        assert!(markets[1].neg_risk);
+
+        let checks: Vec<_> = markets.iter().map(cross_validate).collect();
+        assert!(checks[0].is_valid(), "{:?}", checks[0].issues);
+        assert!(checks[1].is_valid(), "{:?}", checks[1].issues);
+        assert_eq!(checks[0].expected_token_ids, first.clob_token_ids);
+        assert_eq!(checks[2].issues.len(), 2);
+        assert!(checks[2].issues[0].starts_with("token 0 (1) != derived"));
+    }
+
+    #[tokio::test]
+    async fn test_valid_markets_are_stored_with_slug() {
+        let (url, _) = serve(vec![json!([gamma_market("stored", 0x0d, false)])], 10).await;
+        let markets = GammaClient::new(&url).fetch_markets(&GammaQuery::default()).await.unwrap();
+        let market = markets.into_iter().next().unwrap();
+        let info = market_info(&market).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Question stored?"));
+
+        let mut db = Database::open_in_memory().unwrap();
+        assert_eq!(db.write_gamma_markets(&[(info.clone(), market.clone())]).unwrap(), 1);
+        // Re-discovery only refreshes the Gamma fields
+        assert_eq!(db.write_gamma_markets(&[(info, market)]).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_resolver_derives_positions_of_prepared_conditions() {
+        let oracle = Address::repeat_byte(0x0a);
+        let question_id = H256::repeat_byte(0x0f);
+        let condition_id = get_condition_id(oracle, question_id, U256::from(2));
+        let info = derive_market_info(condition_id, question_id, oracle, 2, false).unwrap();
+        let token_id = info.outcomes[1].position_id.clone();
+
+        let other = get_condition_id(oracle, H256::repeat_byte(0x10), U256::from(2));
+        let preparation = |condition_id: H256, question_id: H256, block: u64| TimestampedLog {
+            log: Log {
+                address: Address::from_str(CTF_ADDRESS).unwrap(),
+                topics: vec![H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE)), condition_id, H256::from(oracle), question_id],
+                data: Bytes::from(ethers::abi::encode(&[ethers::abi::Token::Uint(U256::from(2))])),
+                block_number: Some(U64::from(block)),
+                ..Default::default()
+            },
+            block_timestamp: Some(U64::from(1_700_000_000 + block)),
+        };
+        let url = serve_rpc(vec![
+            preparation(other, H256::repeat_byte(0x10), 10),
+            preparation(condition_id, question_id, 20),
+        ]).await;
+
+        // No TokenRegistered log: the preparation scan finds the condition
+        let scanner = Scanner::new(&url).unwrap();
+        match TokenResolver::new(&scanner, 0).resolve(&token_id).await.unwrap() {
+            TokenResolution::Resolved { market, source } => {
+                assert_eq!(source, ResolutionSource::ConditionPreparation);
+                assert_eq!(market.condition_id, format!("{:?}", condition_id));
+                assert_eq!(market.block_number, Some(20));
+                assert_eq!(market.timestamp, Some(1_700_000_020));
+            }
+            other => panic!("Expected a ConditionPreparation resolution, got {:?}", other),
+        }
+
+        let unknown = format!("0x{:x}", U256::from(12345));
+        let unresolved = TokenResolver::new(&scanner, 0).resolve(&unknown).await.unwrap();
+        assert!(matches!(unresolved, TokenResolution::Unresolved(reason) if reason == "no TokenRegistered log; no prepared condition derives the token"));
+    }
+
+    #[tokio::test]
+    async fn test_resolver_falls_back_to_gamma_when_rpc_fails() {
+        let listed = gamma_market("fallback", 0x0e, false);
+        let token_ids: Vec<String> = serde_json::from_str(listed["clobTokenIds"].as_str().unwrap()).unwrap();
+        let token_id = format!("0x{:x}", U256::from_dec_str(&token_ids[0]).unwrap());
+        let (url, _) = serve(vec![json!([listed])], 10).await;
+
+        // Nothing listens on port 1: every eth_getLogs fails
+        let scanner = Scanner::new("http://127.0.0.1:1").unwrap();
+        let resolver = TokenResolver::new(&scanner, 0).with_gamma(GammaClient::new(&url));
+        match resolver.resolve(&token_id).await.unwrap() {
+            TokenResolution::Resolved { market, source } => {
+                assert_eq!(source, ResolutionSource::Gamma);
+                assert_eq!(market.title.as_deref(), Some("Question fallback?"));
+            }
+            other => panic!("Expected a Gamma resolution, got {:?}", other),
+        }
+
+        // Without Gamma the RPC failure ends up in the reason
+        let unresolved = TokenResolver::new(&scanner, 0).resolve(&token_id).await.unwrap();
+        assert!(matches!(unresolved, TokenResolution::Unresolved(reason) if reason.starts_with("TokenRegistered lookup failed")));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::db::{Batch, Database};
+use crate::discovery::{ResolutionSource, TokenResolution, TokenResolver};
+use crate::scanner::Scanner;
+use crate::utils::canonical_token_id;
+use anyhow::Result;
+use colored::*;
+use std::time::Duration;
+
+/// `sync_state` key tracking the combined market + trade scan.
+pub const INDEXER_SYNC_KEY: &str = "global_indexer";
+
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    /// Blocks per eth_getLogs batch / database transaction
+    pub batch_size: u64,
+    /// Only index blocks at least this deep below the chain head
+    pub confirmations: u64,
+    /// How far below the sync tip to look for the fork point after a reorg
+    pub reorg_window: u64,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            confirmations: 10,
+            reorg_window: 128,
+        }
+    }
+}
+
+pub struct Indexer<'a> {
+    scanner: &'a Scanner,
+    db: Database,
+    config: IndexerConfig,
+    /// Looks up markets for traded tokens neither the batch nor the database knows
+    resolver: Option<TokenResolver<'a>>,
+}
+
+impl<'a> Indexer<'a> {
+    pub fn new(scanner: &'a Scanner, db: Database, config: IndexerConfig) -> Self {
+        let config = IndexerConfig {
+            batch_size: config.batch_size.max(1),
+            ..config
+        };
+        Self { scanner, db, config, resolver: None }
+    }
+
+    pub fn with_resolver(mut self, resolver: TokenResolver<'a>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// First block still to be scanned: one past `sync_state.last_block`, or `from_block` on a fresh
+    /// database. `from_block` is ignored once there is sync state, so a resumed run never leaves a gap.
+    pub fn resume_block(&self, from_block: Option<u64>) -> Result<u64> {
+        match (self.db.last_block(INDEXER_SYNC_KEY)?, from_block) {
+            (Some(last), _) => Ok(last + 1),
+            (None, Some(from)) => Ok(from),
+            (None, None) => Err(anyhow::anyhow!("Empty database: a start block (--from) is required on the first run")),
+        }
+    }
+
+    /// Latest block with at least `confirmations` blocks on top of it.
+    pub async fn safe_head(&self) -> Result<u64> {
+        let head = self.scanner.latest_block().await?;
+        Ok(head.saturating_sub(self.config.confirmations))
+    }
+
+    /// Compares the stored sync tip hash with the canonical chain. On divergence, walks back through
+    /// stored trade/market block hashes to the newest one still canonical, deletes everything above
+    /// it and rewinds `last_block`, so the next `run` re-ingests the affected range.
+    ///
+    /// Returns the block rolled back to, or None when no reorg was found.
+    pub async fn check_reorg(&mut self) -> Result<Option<u64>> {
+        let Some((last_block, Some(stored_hash))) = self.db.sync_tip(INDEXER_SYNC_KEY)? else {
+            return Ok(None);
+        };
+
+        if self.canonical_hash(last_block).await?.as_deref() == Some(stored_hash.as_str()) {
+            return Ok(None);
+        }
+
+        // If nothing in the window is still canonical, drop the whole window
+        let floor = last_block.saturating_sub(self.config.reorg_window);
+        let mut fork_block = floor;
+        let mut fork_hash = None;
+
+        for (block, hash) in self.db.stored_block_hashes(floor, last_block)? {
+            if self.canonical_hash(block).await?.as_deref() == Some(hash.as_str()) {
+                fork_block = block;
+                fork_hash = Some(hash);
+                break;
+            }
+        }
+
+        let (trades, markets) = self.db.rollback_to(INDEXER_SYNC_KEY, fork_block, fork_hash.as_deref())?;
+        // The rolled-back registrations must not keep labelling fills until the next restart
+        self.scanner.unregister_tokens_after(fork_block);
+        eprintln!(
+            "{} tip {} diverged from the canonical chain; rolled back to {} ({} trades, {} markets removed)",
+            "Reorg detected:".red().bold(),
+            last_block.to_string().cyan(),
+            fork_block.to_string().cyan(),
+            trades,
+            markets,
+        );
+
+        Ok(Some(fork_block))
+    }
+
+    async fn canonical_hash(&self, block: u64) -> Result<Option<String>> {
+        Ok(self.scanner.block_hash(block).await?.map(|h| format!("{:?}", h)))
+    }
+
+    /// Indexes `[start, to_block]` in batches. Each batch is committed together with its
+    /// `last_block`, so an interrupted run resumes at the first unfinished batch.
+    pub async fn run(&mut self, from_block: Option<u64>, to_block: u64) -> Result<()> {
+        self.check_reorg().await?;
+        let mut start = self.resume_block(from_block)?;
+
+        if start > to_block {
+            eprintln!("{}", format!("Already synced up to block {}.", start - 1).green());
+            return Ok(());
+        }
+
+        while start <= to_block {
+            let end = (start + self.config.batch_size - 1).min(to_block);
+
+            // Hash taken before the logs: if the tip reorgs in between, the next check_reorg
+            // sees a mismatch and re-ingests rather than silently keeping stale logs.
+            let end_hash = self.canonical_hash(end).await?;
+            // Each log query splits the batch further if the provider objects to its size
+            let (scanner, policy) = (self.scanner, ChunkPolicy::starting_at(self.config.batch_size));
+            let markets = backfill(start, end, &policy, false, |from, to| scanner.fetch_market_events(from, to)).await?;
+            // Registrations first, so this range's fills already carry their condition
+            let registrations = backfill(start, end, &policy, false, |from, to| scanner.fetch_token_registrations(from, to)).await?;
+            let trades = backfill(start, end, &policy, false, |from, to| scanner.fetch_events(from, to)).await?;
+            let mut batch = Batch { markets, trades, registrations, unresolved: Vec::new() };
+            self.discover_markets(&mut batch).await?;
+            let stats = self.db.write_batch(INDEXER_SYNC_KEY, &batch, end, end_hash.as_deref())?;
+
+            eprintln!(
+                "{} {}-{}: {} markets, {} tokens registered, {} trades ({} unmatched, {} tokens unresolved)",
+                "Indexed".green().bold(),
+                start.to_string().cyan(),
+                end.to_string().cyan(),
+                stats.markets_inserted,
+                batch.registrations.len(),
+                stats.trades_inserted,
+                stats.trades_unmatched,
+                batch.unresolved.len(),
+            );
+
+            start = end + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Resolves every traded token with no market in the batch or the database. Found markets
+    /// join the batch, so its trades are stored already linked; the rest are queued with the
+    /// reason the last source gave.
+    async fn discover_markets(&self, batch: &mut Batch) -> Result<()> {
+        let Some(resolver) = &self.resolver else {
+            return Ok(());
+        };
+
+        let mut tokens: Vec<String> = Vec::new();
+        for trade in &batch.trades {
+            let token_id = canonical_token_id(&trade.token_id).unwrap_or_else(|| trade.token_id.clone());
+            if !tokens.contains(&token_id) && !self.db.is_known_token(&token_id)? {
+                tokens.push(token_id);
+            }
+        }
+
+        for token_id in tokens {
+            // A market found earlier in this loop may already cover the other outcome
+            if batch.markets.iter().any(|m| m.outcome_label(&token_id).is_some()) {
+                continue;
+            }
+            let resolution = resolver.resolve(&token_id).await
+                .unwrap_or_else(|e| TokenResolution::Unresolved(format!("lookup failed: {}", e)));
+
+            match resolution {
+                TokenResolution::Resolved { market, source } => {
+                    let source = match source {
+                        ResolutionSource::TokenRegistered => "TokenRegistered",
+                        ResolutionSource::ConditionPreparation => "ConditionPreparation",
+                        ResolutionSource::Gamma => "Gamma",
+                    };
+                    eprintln!(
+                        "{} token {} -> market {} {}",
+                        "Discovered".green(),
+                        token_id.dimmed(),
+                        market.condition_id.cyan(),
+                        format!("(via {})", source).dimmed(),
+                    );
+                    batch.markets.push(*market);
+                }
+                TokenResolution::Unresolved(reason) => {
+                    eprintln!("{} token {}: {}", "Unresolved".yellow(), token_id.dimmed(), reason);
+                    batch.unresolved.push((token_id, reason));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps indexing up to the confirmed head, checking for reorgs every `poll_interval`.
+    pub async fn follow(&mut self, from_block: Option<u64>, poll_interval: Duration) -> Result<()> {
+        let mut from_block = from_block;
+
+        loop {
+            let safe_head = self.safe_head().await?;
+            if self.resume_block(from_block)? <= safe_head {
+                self.run(from_block, safe_head).await?;
+                from_block = None; // Only needed to seed an empty database
+            } else {
+                self.check_reorg().await?;
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "indexer_test.rs"]
+mod indexer_test;
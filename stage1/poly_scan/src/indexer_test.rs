@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod tests {
+    use crate::db::{Batch, Database};
+    use crate::indexer::{Indexer, IndexerConfig, INDEXER_SYNC_KEY};
+    use crate::scanner::Scanner;
+
+    #[test]
+    fn test_resume_ignores_from_once_synced() {
+        let scanner = Scanner::new("http://127.0.0.1:1").unwrap();
+        let indexer = Indexer::new(&scanner, Database::open_in_memory().unwrap(), IndexerConfig::default());
+        assert!(indexer.resume_block(None).is_err());
+        assert_eq!(indexer.resume_block(Some(50)).unwrap(), 50);
+
+        let mut db = Database::open_in_memory().unwrap();
+        db.write_batch(INDEXER_SYNC_KEY, &Batch::default(), 100, None).unwrap();
+        let indexer = Indexer::new(&scanner, db, IndexerConfig::default());
+        // A later --from must not skip blocks 101..500, nor an earlier one re-scan stored blocks
+        assert_eq!(indexer.resume_block(Some(500)).unwrap(), 101);
+        assert_eq!(indexer.resume_block(Some(10)).unwrap(), 101);
+        assert_eq!(indexer.resume_block(None).unwrap(), 101);
+    }
+}
@@ -1,7 +1,15 @@
-use crate::consts::POLYGON_RPC_URL;
+use crate::amount::{Amount, DEFAULT_PRICE_SCALE, MAX_PRICE_SCALE};
+use crate::backfill::{backfill, ChunkPolicy, DEFAULT_BACKFILL_CHUNK};
+use crate::candles::CandleInterval;
+use crate::consts::{GAMMA_API_URL, POLYGON_CHAIN_ID, POLYGON_RPC_URL, POLYGON_WS_URL};
+use crate::db::Database;
+use crate::discovery::TokenResolver;
+use crate::gamma::{GammaClient, GammaQuery};
+use crate::indexer::{Indexer, IndexerConfig};
 use crate::scanner::Scanner;
+use crate::timestamps::{TimestampCache, DEFAULT_TIMESTAMP_CACHE};
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::*;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Attribute, Cell, ContentArrangement, Table, Color as ComfyColor};
@@ -9,9 +17,28 @@ use dialoguer::{theme::ColorfulTheme, Input, Select, FuzzySelect};
 use ethers::types::{Address, H256, U256};
 use std::str::FromStr;
 
+mod amount;
+mod api;
+mod assets;
+mod backfill;
+mod candles;
 mod consts;
+mod ctf;
+mod db;
+mod discovery;
+mod gamma;
+mod indexer;
+mod matches;
 mod models;
+mod neg_risk;
+mod pnl;
+mod positions;
+mod registry;
 mod scanner;
+mod stream;
+mod timeline;
+mod timestamps;
+mod uma;
 mod utils;
 
 #[derive(Parser, Debug)]
@@ -29,6 +56,31 @@ struct Args {
     #[arg(short, long)]
     json: bool,
 
+    /// With --json, print the bare fill array instead of {trades, totals}
+    #[arg(long)]
+    fills_only: bool,
+
+    /// Group fills by their OrdersMatched event (one row per match instead of per fill)
+    #[arg(long)]
+    matches: bool,
+
+    /// Scan CTF lifecycle events (split, merge, resolution, redemption) instead of trades
+    #[arg(long)]
+    ctf: bool,
+
+    /// Blocks per eth_getLogs call (halved when the provider rejects the range, grown back after)
+    #[arg(long, default_value_t = DEFAULT_BACKFILL_CHUNK)]
+    chunk_size: u64,
+
+    /// Decimal places kept when computing prices (truncated beyond), at most 18
+    #[arg(long, default_value_t = DEFAULT_PRICE_SCALE, value_parser = clap::value_parser!(u32).range(0..=MAX_PRICE_SCALE as i64))]
+    price_scale: u32,
+
+    /// SQLite file caching block timestamps across runs, created once a timestamp is resolved
+    /// (default: the `index` database for `index`, else block_timestamps.db)
+    #[arg(long)]
+    timestamp_cache: Option<String>,
+
     /// Helper mode to force interactive (optional, but default is auto-detect if no args)
     #[arg(long)]
     interactive: bool,
@@ -44,20 +96,331 @@ struct Args {
     /// Manual Mode: Oracle Address
     #[arg(long)]
     oracle: Option<String>,
+
+    /// Manual Mode: Number of outcome slots of the condition
+    #[arg(long, default_value_t = 2)]
+    outcome_slots: u64,
+
+    /// Manual Mode: Treat the condition as neg-risk (WrappedCollateral) even if the oracle is not the NegRiskAdapter
+    #[arg(long)]
+    neg_risk: bool,
+
+    /// Manual Mode: Extra combined index sets to derive positions for (e.g. 3 = slots 0|1)
+    #[arg(long, value_delimiter = ',')]
+    index_sets: Vec<u64>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Index markets and trades into a local SQLite database (resumes from sync_state)
+    Index {
+        /// SQLite database path
+        #[arg(long, default_value = "poly_scan.db")]
+        db: String,
+
+        /// Start block (only used when the database has no sync state yet)
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest confirmed block)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Blocks per eth_getLogs batch / database transaction
+        #[arg(long, default_value_t = 1000)]
+        batch_size: u64,
+
+        /// Blocks to wait below the chain head before indexing (reorg safety)
+        #[arg(long, default_value_t = 10)]
+        confirmations: u64,
+
+        /// Keep following the chain head after catching up (checks for reorgs on every poll)
+        #[arg(long)]
+        follow: bool,
+
+        /// Seconds between polls in --follow mode
+        #[arg(long, default_value_t = 5)]
+        poll_secs: u64,
+
+        /// Store trades of unknown tokens unmatched instead of looking up their markets
+        #[arg(long)]
+        no_discovery: bool,
+
+        /// First block searched for a token's TokenRegistered / ConditionPreparation logs
+        #[arg(long, default_value_t = 0)]
+        discovery_from: u64,
+
+        /// Gamma API base URL, the last resort for unknown tokens
+        #[arg(long, default_value = GAMMA_API_URL)]
+        gamma_url: String,
+    },
+
+    /// List traded tokens the indexer could not map to a market
+    Unresolved {
+        /// SQLite database path (as written by `index`)
+        #[arg(long, default_value = "poly_scan.db")]
+        db: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Chronological on-chain history of one market: preparation, splits/merges, trades, resolution, redemptions
+    Timeline {
+        /// Condition ID (0x-prefixed bytes32)
+        condition_id: String,
+
+        /// Start block (default: the market's ConditionPreparation block)
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Blocks per eth_getLogs call (halved when the provider rejects the range, grown back after)
+        #[arg(long, default_value_t = DEFAULT_BACKFILL_CHUNK)]
+        chunk_size: u64,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Position holdings replayed from CTF transfers: who holds a market's tokens, or what a wallet holds
+    Positions {
+        /// Holders of every outcome of this condition (scans from its ConditionPreparation block)
+        #[arg(long)]
+        condition_id: Option<String>,
+
+        /// Holders of this position ID (hex or decimal)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Positions held by this address (narrowed to the market/token when one is given)
+        #[arg(long)]
+        holder: Option<String>,
+
+        /// Start block (default: the market's ConditionPreparation block)
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Report balances as of this block (default: --to)
+        #[arg(long)]
+        at: Option<u64>,
+
+        /// Blocks per eth_getLogs call (halved when the provider rejects the range, grown back after)
+        #[arg(long, default_value_t = DEFAULT_BACKFILL_CHUNK)]
+        chunk_size: u64,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// OHLCV bars of a token's fills, or of every outcome of a market
+    Candles {
+        /// Bars of this position ID (hex or decimal)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Bars of every outcome of this condition
+        #[arg(long)]
+        condition_id: Option<String>,
+
+        /// Bar width: 1m, 5m, 1h or 1d
+        #[arg(long, default_value = "1h")]
+        interval: CandleInterval,
+
+        /// Emit zero-volume bars at the previous close for intervals without fills
+        #[arg(long)]
+        fill_gaps: bool,
+
+        /// Start block (default: the market's ConditionPreparation block)
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Blocks per eth_getLogs call (halved when the provider rejects the range, grown back after)
+        #[arg(long, default_value_t = DEFAULT_BACKFILL_CHUNK)]
+        chunk_size: u64,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Output as CSV
+        #[arg(long, conflicts_with = "json")]
+        csv: bool,
+    },
+
+    /// Realized and unrealized PnL of a wallet per market, from its fills, splits, merges and redemptions
+    Pnl {
+        /// Wallet address
+        address: String,
+
+        /// Start block; positions acquired earlier count as free
+        #[arg(long)]
+        from: u64,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Blocks per eth_getLogs call (halved when the provider rejects the range, grown back after)
+        #[arg(long, default_value_t = DEFAULT_BACKFILL_CHUNK)]
+        chunk_size: u64,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Neg-risk markets from the NegRiskAdapter: every question of a market grouped together
+    NegRisk {
+        /// Show a single market by its neg-risk market ID (scans from --from, default genesis)
+        #[arg(long)]
+        market_id: Option<String>,
+
+        /// Start block
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// UMA optimistic oracle resolution status (proposed, disputed, settled, reset) of UMA-resolved markets
+    Uma {
+        /// Show a single market by condition ID (scans from its ConditionPreparation block)
+        #[arg(long)]
+        condition_id: Option<String>,
+
+        /// Start block
+        #[arg(long)]
+        from: Option<u64>,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Only questions that have been disputed at least once
+        #[arg(long)]
+        disputed: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Scan exchange TokenRegistered logs: every tradeable token with its complement and condition
+    Registry {
+        /// Start block
+        #[arg(long)]
+        from: u64,
+
+        /// End block (default: latest)
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Store the registrations into this SQLite database (`index` labels trades with them)
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Discover markets from the Gamma API and cross-check their IDs against on-chain derivation
+    Gamma {
+        /// Gamma API base URL
+        #[arg(long, default_value = GAMMA_API_URL)]
+        url: String,
+
+        /// Page through /events (markets grouped by event) instead of /markets
+        #[arg(long)]
+        events: bool,
+
+        /// Maximum number of markets (events with --events) to fetch
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+
+        /// Include closed markets
+        #[arg(long)]
+        closed: bool,
+
+        /// Store markets that pass validation into this SQLite database
+        #[arg(long)]
+        db: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Serve markets and trades from the index database as a read-only JSON API
+    Serve {
+        /// SQLite database path (as written by `index`)
+        #[arg(long, default_value = "poly_scan.db")]
+        db: String,
+
+        /// Listen address
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+    },
+
+    /// Follow new trades and markets live over a WebSocket subscription
+    #[command(alias = "follow")]
+    Watch {
+        /// WebSocket JSON-RPC endpoint
+        #[arg(long, default_value = POLYGON_WS_URL)]
+        ws_url: String,
+
+        /// Emit one JSON object per line instead of colored lines
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let scanner = Scanner::new(POLYGON_RPC_URL)?;
+    let timestamp_cache = match (&args.timestamp_cache, &args.command) {
+        (Some(path), _) => path.as_str(),
+        (None, Some(Command::Index { db, .. })) => db.as_str(),
+        (None, _) => DEFAULT_TIMESTAMP_CACHE,
+    };
+    let scanner = Scanner::new(POLYGON_RPC_URL)?
+        .with_price_scale(args.price_scale)
+        .with_timestamp_cache(TimestampCache::on_disk(timestamp_cache, POLYGON_CHAIN_ID));
 
+    if let Some(command) = args.command {
+        return run_command(&scanner, command).await;
+    }
 
     // Mode: Manual CLI Verification
     if let (Some(cond_id), Some(q_id), Some(oracle)) = (args.condition_id.clone(), args.question_id.clone(), args.oracle.clone()) {
         let oracle_addr = Address::from_str(&oracle).expect("Invalid Oracle Address");
         let question_id = H256::from_str(&q_id).expect("Invalid Question ID");
         let condition_id_hash = H256::from_str(&cond_id).expect("Invalid Condition ID");
-        let slot_count = 2; // Default to 2 for CLI manual mode
+        let slot_count = args.outcome_slots;
 
         // Verification (optional - just for information)
         let calculated_condition_id = utils::get_condition_id(oracle_addr, question_id, U256::from(slot_count));
@@ -70,30 +433,15 @@ async fn main() -> Result<()> {
         }
 
         // Use the user-provided condition ID (not the calculated one)
-        let collateral_token_addr = Address::from_str(crate::consts::USDC_ADDRESS).unwrap();
-        let parent_collection_id = H256::zero();
-        let collection_id_yes = utils::get_collection_id(parent_collection_id, condition_id_hash, U256::from(1));
-        let collection_id_no = utils::get_collection_id(parent_collection_id, condition_id_hash, U256::from(2));
-
-        let yes_token_id = utils::get_position_id(collateral_token_addr, collection_id_yes);
-        let no_token_id = utils::get_position_id(collateral_token_addr, collection_id_no);
-
-        let info = models::MarketInfo {
-             condition_id: format!("{:?}", condition_id_hash),
-             question_id: format!("{:?}", question_id),
-             oracle: utils::format_address(oracle_addr),
-             outcome_slot_count: slot_count,
-             collateral_token: utils::format_address(collateral_token_addr),
-             yes_token_id: format!("0x{:x}", yes_token_id),
-             no_token_id: format!("0x{:x}", no_token_id),
-        };
+        let mut info = utils::derive_market_info(condition_id_hash, question_id, oracle_addr, slot_count, args.neg_risk)?;
+        utils::add_combined_positions(&mut info, &args.index_sets)?;
         
         print_market_info(&info);
         return Ok(());
     }
 
     // If args are provided, run in non-interactive mode (Script mode)
-    if args.from.is_some() || args.range.is_some() || args.json {
+    if args.from.is_some() || args.range.is_some() || args.json || args.matches || args.ctf {
         let from_block = args.from.unwrap_or(66000000); // Default fallback if only one arg provided
         let range = args.range.unwrap_or(10);
         let to_block = from_block + range;
@@ -106,9 +454,33 @@ async fn main() -> Result<()> {
             "...".bold()
         );
 
-        let trades = scanner.fetch_events(from_block, to_block).await?;
-        if args.json {
+        let policy = ChunkPolicy::starting_at(args.chunk_size);
+        if args.ctf {
+            let events = backfill(from_block, to_block, &policy, true, |start, end| scanner.fetch_ctf_events(start, end)).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&events)?);
+            } else {
+                print_ctf_events_table(&events);
+            }
+            return Ok(());
+        }
+
+        if args.matches {
+            let matches = backfill(from_block, to_block, &policy, true, |start, end| scanner.fetch_matches(start, end)).await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&matches)?);
+            } else {
+                print_matches_table(&matches);
+            }
+            return Ok(());
+        }
+
+        let trades = backfill(from_block, to_block, &policy, true, |start, end| scanner.fetch_events(start, end)).await?;
+        if args.json && args.fills_only {
             println!("{}", serde_json::to_string_pretty(&trades)?);
+        } else if args.json {
+            let report = models::TradesReport { trades: &trades, totals: utils::fee_totals(&trades) };
+            println!("{}", serde_json::to_string_pretty(&report)?);
         } else {
             print_trades_table(&trades);
         }
@@ -120,6 +492,356 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+async fn run_command(scanner: &Scanner, command: Command) -> Result<()> {
+    match command {
+        Command::Index { db, from, to, batch_size, confirmations, follow, poll_secs, no_discovery, discovery_from, gamma_url } => {
+            let config = IndexerConfig { batch_size, confirmations, ..IndexerConfig::default() };
+            let database = Database::open(&db)?;
+            scanner.register_tokens(database.registered_tokens()?);
+            if scanner.registered_token_count() > 0 {
+                eprintln!("{} {} registered tokens", "Loaded".bold().green(), scanner.registered_token_count());
+            }
+            let mut indexer = Indexer::new(scanner, database, config);
+            if !no_discovery {
+                let resolver = TokenResolver::new(scanner, discovery_from).with_gamma(GammaClient::new(&gamma_url));
+                indexer = indexer.with_resolver(resolver);
+            }
+
+            if follow {
+                eprintln!("{} {} {}", "Following chain head into".bold().green(), db.cyan(), format!("({} confirmations)", confirmations).dimmed());
+                return indexer.follow(from, std::time::Duration::from_secs(poll_secs)).await;
+            }
+
+            let to_block = match to {
+                Some(b) => b,
+                None => indexer.safe_head().await?,
+            };
+            eprintln!("{} {} {} {}", "Indexing into".bold().green(), db.cyan(), "up to block".bold(), to_block.to_string().cyan());
+            indexer.run(from, to_block).await
+        }
+        Command::Timeline { condition_id, from, to, chunk_size, json } => {
+            let condition_id = H256::from_str(&condition_id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?;
+            eprintln!("{} {}", "Building timeline for".bold().green(), format!("{:?}", condition_id).cyan());
+
+            let Some(timeline) = timeline::fetch_timeline(scanner, condition_id, from, to, chunk_size).await? else {
+                return Err(anyhow::anyhow!("No ConditionPreparation found for {:?}", condition_id));
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&timeline)?);
+            } else {
+                print_timeline(&timeline);
+            }
+            Ok(())
+        }
+        Command::Positions { condition_id, token, holder, from, to, at, chunk_size, json } => {
+            let holder = holder.map(|h| Address::from_str(&h).map_err(|_| anyhow::anyhow!("Invalid holder address"))).transpose()?;
+            let token = token.map(|t| utils::parse_token_id(&t).ok_or_else(|| anyhow::anyhow!("Invalid token ID"))).transpose()?;
+
+            // The market fixes the tokens to report and where balances start from zero
+            let condition_id = match (condition_id, token) {
+                (Some(id), _) => Some(H256::from_str(&id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?),
+                (None, Some(token)) => scanner.fetch_token_condition(token, 0).await?,
+                (None, None) => None,
+            };
+            let market = match condition_id {
+                // From genesis: --from only bounds the scanned window, which may start after preparation
+                Some(id) => scanner.fetch_market_info_by_condition_id(id, None).await?,
+                None => None,
+            };
+            if market.is_none() && token.is_none() && holder.is_none() {
+                return Err(anyhow::anyhow!("Pass --condition-id, --token or --holder"));
+            }
+
+            let from_block = from.or(market.as_ref().and_then(|m| m.block_number))
+                .ok_or_else(|| anyhow::anyhow!("--from is required when the market's preparation block is unknown"))?;
+            let to_block = match to {
+                Some(b) => b,
+                None => scanner.latest_block().await?,
+            };
+            let at_block = at.unwrap_or(to_block);
+            if at_block < from_block || at_block > to_block {
+                return Err(anyhow::anyhow!("--at must be within {}..={}", from_block, to_block));
+            }
+
+            eprintln!("{} {}-{}", "Replaying CTF transfers".bold().green(), from_block.to_string().cyan(), to_block.to_string().cyan());
+            let ledger = positions::fetch_ledger(scanner, from_block, to_block, holder, chunk_size).await?;
+            eprintln!("{} transfers replayed", ledger.transfer_count().to_string().cyan());
+
+            let tokens: Vec<U256> = match (&market, token) {
+                (_, Some(token)) => vec![token],
+                (Some(market), None) => market.position_ids().filter_map(utils::parse_token_id).collect(),
+                (None, None) => Vec::new(),
+            };
+            let mut balances = match holder {
+                Some(holder) => {
+                    let mut held = ledger.holdings(holder, at_block);
+                    if !tokens.is_empty() {
+                        held.retain(|b| utils::parse_token_id(&b.token_id).is_some_and(|id| tokens.contains(&id)));
+                    }
+                    held
+                }
+                None => tokens.iter().flat_map(|token| ledger.holders(*token, at_block)).collect(),
+            };
+            if let Some(market) = &market {
+                positions::label_outcomes(&mut balances, market);
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&balances)?);
+            } else if balances.is_empty() {
+                println!("{}", format!("No positions held at block {}.", at_block).yellow());
+            } else {
+                print_position_balances(&balances, at_block);
+            }
+
+            let incomplete = ledger.incomplete(at_block);
+            if incomplete > 0 {
+                eprintln!("{} {} balances went negative: positions acquired before block {} are missing (use an earlier --from)",
+                    "⚠️".yellow(), incomplete.to_string().red().bold(), from_block);
+            }
+            Ok(())
+        }
+        Command::NegRisk { market_id, from, to, json } => {
+            let markets = match market_id {
+                Some(id) => {
+                    let id = H256::from_str(&id).map_err(|_| anyhow::anyhow!("Invalid market ID"))?;
+                    scanner.fetch_neg_risk_market(id, from).await?.into_iter().collect()
+                }
+                None => {
+                    let from = from.ok_or_else(|| anyhow::anyhow!("--from is required without --market-id"))?;
+                    let to = match to {
+                        Some(b) => b,
+                        None => scanner.latest_block().await?,
+                    };
+                    let events = backfill(from, to, &ChunkPolicy::default(), true, |start, end| scanner.fetch_neg_risk_events(start, end)).await?;
+                    neg_risk::group_markets(&events)?
+                }
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&markets)?);
+            } else if markets.is_empty() {
+                println!("{}", "No neg-risk markets found.".yellow());
+            } else {
+                markets.iter().for_each(print_neg_risk_market);
+            }
+            Ok(())
+        }
+        Command::Uma { condition_id, from, to, disputed, json } => {
+            let mut states = match condition_id {
+                Some(id) => {
+                    let id = H256::from_str(&id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?;
+                    let Some(market) = scanner.fetch_market_info_by_condition_id(id, None).await? else {
+                        return Err(anyhow::anyhow!("No ConditionPreparation found for {:?}", id));
+                    };
+                    if !uma::is_uma_adapter(&market.oracle) {
+                        return Err(anyhow::anyhow!("Oracle {} is not a UMA CTF Adapter", market.oracle));
+                    }
+                    let question_id = H256::from_str(&market.question_id).map_err(|_| anyhow::anyhow!("Invalid question ID"))?;
+                    let start = market.block_number.or(from).unwrap_or(0);
+                    scanner.fetch_uma_resolutions(start, to, Some(question_id)).await?
+                }
+                None => {
+                    let from = from.ok_or_else(|| anyhow::anyhow!("--from is required without --condition-id"))?;
+                    scanner.fetch_uma_resolutions(from, to, None).await?
+                }
+            };
+            if disputed {
+                states.retain(|s| s.disputes > 0);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&states)?);
+            } else if states.is_empty() {
+                println!("{}", "No UMA questions found.".yellow());
+            } else {
+                print_uma_resolutions(&states);
+            }
+            Ok(())
+        }
+        Command::Candles { token, condition_id, interval, fill_gaps, from, to, chunk_size, json, csv } => {
+            let token = token.map(|t| utils::parse_token_id(&t).ok_or_else(|| anyhow::anyhow!("Invalid token ID"))).transpose()?;
+            let condition_id = match (condition_id, token) {
+                (Some(id), _) => Some(H256::from_str(&id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?),
+                (None, Some(token)) => scanner.fetch_token_condition(token, 0).await?,
+                (None, None) => return Err(anyhow::anyhow!("Pass --token or --condition-id")),
+            };
+            let market = match condition_id {
+                // From genesis: --from only bounds the scanned window, which may start after preparation
+                Some(id) => scanner.fetch_market_info_by_condition_id(id, None).await?,
+                None => None,
+            };
+            let tokens: Vec<U256> = match (&market, token) {
+                (_, Some(token)) => vec![token],
+                (Some(market), None) => market.position_ids().filter_map(utils::parse_token_id).collect(),
+                (None, None) => return Err(anyhow::anyhow!("No ConditionPreparation found for the condition")),
+            };
+
+            let from_block = from.or(market.as_ref().and_then(|m| m.block_number))
+                .ok_or_else(|| anyhow::anyhow!("--from is required when the market's preparation block is unknown"))?;
+            let to_block = match to {
+                Some(b) => b,
+                None => scanner.latest_block().await?,
+            };
+            eprintln!("{} {} {}-{}", "Building".bold().green(), format!("{} candles", interval).cyan(), from_block.to_string().cyan(), to_block.to_string().cyan());
+
+            let mut bars = candles::fetch_candles(scanner, &tokens, from_block, to_block, interval, fill_gaps, chunk_size).await?;
+            if let Some(market) = &market {
+                for bar in &mut bars {
+                    bar.outcome = bar.outcome.take().or_else(|| market.outcome_label(&bar.token_id));
+                }
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&bars)?);
+            } else if csv {
+                print!("{}", candles::to_csv(&bars));
+            } else if bars.is_empty() {
+                println!("{}", "No fills in range.".yellow());
+            } else {
+                print_candles(&bars);
+            }
+            Ok(())
+        }
+        Command::Pnl { address, from, to, chunk_size, json } => {
+            let wallet = Address::from_str(&address).map_err(|_| anyhow::anyhow!("Invalid wallet address"))?;
+            let to_block = match to {
+                Some(b) => b,
+                None => scanner.latest_block().await?,
+            };
+            eprintln!("{} {} {}-{}", "Computing PnL for".bold().green(), utils::format_address(wallet).cyan(), from.to_string().cyan(), to_block.to_string().cyan());
+
+            let report = pnl::fetch_wallet_pnl(scanner, wallet, from, to_block, chunk_size).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_wallet_pnl(&report);
+            }
+            Ok(())
+        }
+        Command::Registry { from, to, db, json } => {
+            let to_block = match to {
+                Some(b) => b,
+                None => scanner.latest_block().await?,
+            };
+            let tokens = backfill(from, to_block, &ChunkPolicy::default(), true, |start, end| scanner.fetch_token_registrations(start, end)).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tokens)?);
+            } else if tokens.is_empty() {
+                println!("{}", "No TokenRegistered logs found.".yellow());
+            } else {
+                print_registered_tokens(&tokens);
+            }
+
+            if let Some(db) = db {
+                let inserted = Database::open(&db)?.write_registrations(&tokens)?;
+                eprintln!("{} {} registrations ({} new) into {}", "Stored".bold().green(), tokens.len(), inserted, db.cyan());
+            }
+            Ok(())
+        }
+        Command::Gamma { url, events, limit, closed, db, json } => {
+            let client = GammaClient::new(&url);
+            let query = GammaQuery { closed: (!closed).then_some(false), max_results: Some(limit), ..GammaQuery::default() };
+            let markets = if events {
+                client.fetch_events(&query).await?.into_iter().flat_map(|e| e.markets).collect()
+            } else {
+                client.fetch_markets(&query).await?
+            };
+            let checks: Vec<models::GammaCheck> = markets.iter().map(gamma::cross_validate).collect();
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&checks)?);
+            } else {
+                print_gamma_checks(&checks);
+            }
+
+            let mismatched = checks.iter().filter(|c| !c.is_valid()).count();
+            if mismatched > 0 {
+                eprintln!("{} {} of {} markets disagree with on-chain derivation and were not stored",
+                    "⚠️".yellow(), mismatched.to_string().red().bold(), checks.len());
+            }
+
+            if let Some(db) = db {
+                let mut rows = Vec::new();
+                for check in checks.into_iter().filter(|c| c.is_valid()) {
+                    match gamma::market_info(&check.market) {
+                        Ok(info) => rows.push((info, check.market)),
+                        Err(e) => eprintln!("Skipping {}: {}", check.market.slug, e),
+                    }
+                }
+                let inserted = Database::open(&db)?.write_gamma_markets(&rows)?;
+                eprintln!("{} {} markets ({} new) into {}", "Stored".bold().green(), rows.len(), inserted, db.cyan());
+            }
+            Ok(())
+        }
+        Command::Unresolved { db, json } => {
+            let tokens = Database::open(&db)?.unresolved_tokens()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tokens)?);
+            } else if tokens.is_empty() {
+                println!("{}", "No unresolved tokens.".green());
+            } else {
+                print_unresolved_tokens(&tokens);
+            }
+            Ok(())
+        }
+        Command::Serve { db, addr } => {
+            let (local_addr, server) = api::bind(Database::open(&db)?, addr)?;
+            eprintln!("{} {} {}", "Serving".bold().green(), db.cyan(), format!("on http://{}", local_addr).bold());
+            server.await
+        }
+        Command::Watch { ws_url, json } => {
+            let (tx, mut rx) = tokio::sync::mpsc::channel(256);
+            let timestamps = scanner.timestamp_cache();
+            let follower = tokio::spawn(async move {
+                stream::follow(&ws_url, timestamps, tx, stream::ReconnectPolicy::default()).await
+            });
+
+            while let Some(event) = rx.recv().await {
+                if json {
+                    println!("{}", serde_json::to_string(&event)?);
+                } else {
+                    print_stream_event(&event);
+                }
+            }
+            follower.await?
+        }
+    }
+}
+
+fn print_stream_event(event: &models::StreamEvent) {
+    match event {
+        models::StreamEvent::Trade(trade) => {
+            let side = match trade.side {
+                models::TradeSide::BUY => "BUY ".green().bold(),
+                models::TradeSide::SELL => "SELL".red().bold(),
+                _ => "UNK ".yellow(),
+            };
+            println!("{} {} {} {:<7} P: {} | Token: {} | Tx: {}",
+                "TRADE ".cyan().bold(),
+                time_label(trade.timestamp).dimmed(),
+                side,
+                venue_label(trade.venue),
+                trade.price.to_string().cyan(),
+                utils::truncate_str(&trade.token_id, 6, 4).magenta(),
+                utils::truncate_str(&trade.tx_hash, 6, 4).dimmed(),
+            );
+        }
+        models::StreamEvent::Market(market) => {
+            println!("{} {} Condition: {} | Oracle: {} | Outcomes: {}",
+                "MARKET".magenta().bold(),
+                time_label(market.timestamp).dimmed(),
+                market.condition_id,
+                market.oracle,
+                market.outcome_slot_count,
+            );
+        }
+    }
+}
+
+/// Block time for display; `-` when the block's timestamp is unknown.
+fn time_label(timestamp: Option<u64>) -> String {
+    timestamp.map(utils::format_timestamp).unwrap_or_else(|| "-".to_string())
+}
+
 async fn run_interactive_mode(scanner: &Scanner) -> Result<()> {
     print_ascii_art();
 
@@ -179,6 +901,11 @@ async fn run_interactive_mode(scanner: &Scanner) -> Result<()> {
                              if trades.is_empty() {
                                  println!("{}", "No OrderFilled events found in this transaction.".yellow());
                              } else {
+                                 // Show how the fills group into matches before drilling into single fills
+                                 if let Ok(matches) = scanner.fetch_tx_matches(tx_hash).await
+                                     && !matches.is_empty() {
+                                     print_matches_table(&matches);
+                                 }
                                  // Usually separate TX lookup has 1 trade, but technically can be multiple.
                                  // Reuse the same interaction logic.
                                  interact_with_trades(&trades)?;
@@ -264,25 +991,10 @@ async fn run_interactive_mode(scanner: &Scanner) -> Result<()> {
                                      }
 
                                      // Use the user-provided condition ID (not the calculated one)
-                                     let collateral_token_addr = Address::from_str(crate::consts::USDC_ADDRESS).unwrap();
-                                     let parent_collection_id = H256::zero();
-                                     let collection_id_yes = utils::get_collection_id(parent_collection_id, hash, U256::from(1));
-                                     let collection_id_no = utils::get_collection_id(parent_collection_id, hash, U256::from(2));
-
-                                     let yes_token_id = utils::get_position_id(collateral_token_addr, collection_id_yes);
-                                     let no_token_id = utils::get_position_id(collateral_token_addr, collection_id_no);
-
-                                     let info = models::MarketInfo {
-                                         condition_id: format!("{:?}", hash),
-                                         question_id: format!("{:?}", question_id),
-                                         oracle: utils::format_address(oracle_addr),
-                                         outcome_slot_count: slot_count,
-                                         collateral_token: utils::format_address(collateral_token_addr),
-                                         yes_token_id: format!("0x{:x}", yes_token_id),
-                                         no_token_id: format!("0x{:x}", no_token_id),
-                                     };
-                                     
-                                     print_market_info(&info);
+                                     match utils::derive_market_info(hash, question_id, oracle_addr, slot_count, false) {
+                                         Ok(info) => print_market_info(&info),
+                                         Err(e) => println!("{} {}", "Error:".red(), e),
+                                     }
 
                                  } else {
                                      println!("{}", "Invalid Oracle or Question ID format.".red());
@@ -343,8 +1055,7 @@ fn interact_with_trades(trades: &[models::TradeOutput]) -> Result<()> {
              };
              // Truncate for list view to keep it clean
              let short_tx = utils::truncate_str(&trade.tx_hash, 4, 4);
-             let price_display = if trade.price.len() > 8 { &trade.price[..8] } else { &trade.price };
-             format!("{:<4} | {} | P: {} | Tx: {}", i+1, side_icon, price_display, short_tx)
+             format!("{:<4} | {} | {:<7} | P: {} | Tx: {}", i+1, side_icon, venue_label(trade.venue), trade.price.to_trimmed_string(), short_tx)
         }).collect();
         
         selections.push("🔙 Back to Main Menu".to_string());
@@ -376,13 +1087,11 @@ fn print_trade_detail(trade: &models::TradeOutput) {
         _ => "UNK".yellow(),
     };
 
-    // Helper to format raw amount strings to float strings roughly
-    let maker_amt = trade.maker_amount_filled.parse::<f64>().unwrap_or(0.0);
-    let taker_amt = trade.taker_amount_filled.parse::<f64>().unwrap_or(0.0);
-    
-    // Calculate human amounts using the fetched decimals
-    let maker_human = maker_amt / (10f64.powi(trade.maker_decimals as i32));
-    let taker_human = taker_amt / (10f64.powi(trade.taker_decimals as i32));
+    // Human amounts using the fetched decimals (exact, no float rounding)
+    let maker_amt = U256::from_dec_str(&trade.maker_amount_filled).unwrap_or_default();
+    let taker_amt = U256::from_dec_str(&trade.taker_amount_filled).unwrap_or_default();
+    let maker_human = Amount::new(maker_amt, trade.maker_decimals);
+    let taker_human = Amount::new(taker_amt, trade.taker_decimals);
 
     // Format amounts: Raw (Human)
     let maker_display = format!("{} ({})", trade.maker_amount_filled, utils::format_token_amount(maker_human));
@@ -391,7 +1100,17 @@ fn print_trade_detail(trade: &models::TradeOutput) {
     // Order and Naming per User Request (JSON-like structure)
     table.add_row(vec![Cell::new("txHash").add_attribute(Attribute::Bold), Cell::new(&trade.tx_hash)]);
     table.add_row(vec![Cell::new("logIndex").add_attribute(Attribute::Bold), Cell::new(trade.log_index.to_string())]);
+    table.add_row(vec![Cell::new("orderHash").add_attribute(Attribute::Bold), Cell::new(&trade.order_hash)]);
+    if let Some(match_index) = trade.match_log_index {
+        table.add_row(vec![Cell::new("matchLogIndex").add_attribute(Attribute::Bold), Cell::new(match_index.to_string())]);
+    }
+    table.add_row(vec![Cell::new("blockNumber").add_attribute(Attribute::Bold), Cell::new(trade.block_number.to_string())]);
+    table.add_row(vec![Cell::new("blockHash").add_attribute(Attribute::Bold), Cell::new(&trade.block_hash)]);
+    if let Some(timestamp) = trade.timestamp {
+        table.add_row(vec![Cell::new("timestamp").add_attribute(Attribute::Bold), Cell::new(utils::format_timestamp(timestamp))]);
+    }
     table.add_row(vec![Cell::new("exchange").add_attribute(Attribute::Bold), Cell::new(&trade.exchange)]);
+    table.add_row(vec![Cell::new("venue").add_attribute(Attribute::Bold), Cell::new(venue_label(trade.venue))]);
     table.add_row(vec![Cell::new("maker").add_attribute(Attribute::Bold), Cell::new(&trade.maker)]);
     table.add_row(vec![Cell::new("taker").add_attribute(Attribute::Bold), Cell::new(&trade.taker)]);
     table.add_row(vec![Cell::new("makerAssetId").add_attribute(Attribute::Bold), Cell::new(&trade.maker_asset_id)]);
@@ -402,9 +1121,30 @@ fn print_trade_detail(trade: &models::TradeOutput) {
 
     let price_str = format!("{} USDC", trade.price);
     table.add_row(vec![Cell::new("price").add_attribute(Attribute::Bold), Cell::new(&price_str).fg(ComfyColor::Cyan)]);
+    table.add_row(vec![Cell::new("size").add_attribute(Attribute::Bold), Cell::new(trade.size.to_string())]);
+    table.add_row(vec![Cell::new("fee").add_attribute(Attribute::Bold), Cell::new(&trade.fee)]);
+    table.add_row(vec![Cell::new("feeUsdc").add_attribute(Attribute::Bold), Cell::new(trade.fee_usdc.to_string())]);
+    table.add_row(vec![Cell::new("feeBps").add_attribute(Attribute::Bold), Cell::new(trade.fee_bps.to_string())]);
     
     table.add_row(vec![Cell::new("tokenId").add_attribute(Attribute::Bold), Cell::new(&trade.token_id).fg(ComfyColor::Magenta)]);
-    table.add_row(vec![Cell::new("side").add_attribute(Attribute::Bold), Cell::new(&side_color.to_string())]);
+    if let Some(condition_id) = &trade.condition_id {
+        table.add_row(vec![Cell::new("conditionId").add_attribute(Attribute::Bold), Cell::new(condition_id)]);
+    }
+    if let Some(complement) = &trade.complement_token_id {
+        table.add_row(vec![Cell::new("complementTokenId").add_attribute(Attribute::Bold), Cell::new(complement)]);
+    }
+    if let Some(slot) = trade.outcome_index {
+        table.add_row(vec![Cell::new("outcome").add_attribute(Attribute::Bold), Cell::new(models::outcome_label(slot as usize, true))]);
+    }
+    table.add_row(vec![Cell::new("side").add_attribute(Attribute::Bold), Cell::new(side_color.to_string())]);
+    table.add_row(vec![Cell::new("makerAction").add_attribute(Attribute::Bold), Cell::new(trade.maker_action.to_string())]);
+    table.add_row(vec![Cell::new("takerAction").add_attribute(Attribute::Bold), Cell::new(trade.taker_action.to_string())]);
+    if trade.complementary {
+        table.add_row(vec![Cell::new("complementary").add_attribute(Attribute::Bold), Cell::new("yes (minted or merged by the exchange)").fg(ComfyColor::Yellow)]);
+    }
+    if !trade.asset_issues.is_empty() {
+        table.add_row(vec![Cell::new("assetIssues").add_attribute(Attribute::Bold), Cell::new(trade.asset_issues.join("\n")).fg(ComfyColor::Red)]);
+    }
 
     println!("\n{}", table);
     println!("Type 'q' or Enter to continue selection...");
@@ -436,45 +1176,516 @@ fn print_trades_table(trades: &[models::TradeOutput]) {
     let mut table = Table::new();
     table.load_preset(UTF8_FULL)
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_width(120) // Constraint width to avoid crazy wrapping if possible
+        .set_width(140) // Constraint width to avoid crazy wrapping if possible
         .set_header(vec![
-            Cell::new("Side").add_attribute(Attribute::Bold),
+            Cell::new("Time").add_attribute(Attribute::Bold),
+            Cell::new("Maker").add_attribute(Attribute::Bold),
+            Cell::new("Taker").add_attribute(Attribute::Bold),
+            Cell::new("Venue").add_attribute(Attribute::Bold),
             Cell::new("Price").add_attribute(Attribute::Bold),
             Cell::new("Maker Amt (USDC)").add_attribute(Attribute::Bold),
             Cell::new("Taker Amt (Token)").add_attribute(Attribute::Bold),
+            Cell::new("Fee (USDC)").add_attribute(Attribute::Bold),
             Cell::new("Token ID").add_attribute(Attribute::Bold),
             Cell::new("Tx Hash").add_attribute(Attribute::Bold),
         ]);
 
-    for trade in trades {
-        let side_color = match trade.side {
-            models::TradeSide::BUY => "BUY".green().bold(),
-            models::TradeSide::SELL => "SELL".red().bold(),
-            _ => "UNKNOWN".yellow(),
-        };
+    let action_cell = |action: &models::TradeAction| {
+        let cell = Cell::new(action.to_string());
+        match action.side {
+            models::TradeSide::BUY => cell.fg(ComfyColor::Green),
+            models::TradeSide::SELL => cell.fg(ComfyColor::Red),
+            models::TradeSide::UNKNOWN => cell.fg(ComfyColor::Yellow),
+        }
+    };
 
+    for trade in trades {
         // Format Amounts roughly (just simple string check or use util if we want commas)
         // For now, raw string is okay, but let's truncate hashes.
         let short_token = utils::truncate_str(&trade.token_id, 6, 4);
         let short_tx = utils::truncate_str(&trade.tx_hash, 6, 4);
-        
-        // Price formatting: ensure it doesn't have too many zeros if not needed, or fixed.
-        let pretty_price = if trade.price.len() > 10 {
-            &trade.price[..10] 
+        let taker = if trade.complementary {
+            Cell::new(format!("{} (complementary)", trade.taker_action)).fg(ComfyColor::Yellow)
         } else {
-             &trade.price
+            action_cell(&trade.taker_action)
         };
 
         table.add_row(vec![
-            Cell::new(&side_color.to_string()),
-            Cell::new(pretty_price).fg(ComfyColor::Cyan),
+            Cell::new(time_label(trade.timestamp)).add_attribute(Attribute::Dim),
+            action_cell(&trade.maker_action),
+            taker,
+            Cell::new(venue_label(trade.venue)),
+            Cell::new(trade.price.to_string()).fg(ComfyColor::Cyan),
             Cell::new(&trade.maker_amount_filled),
             Cell::new(&trade.taker_amount_filled),
+            Cell::new(format!("{} ({} bps)", trade.fee_usdc.to_trimmed_string(), trade.fee_bps)),
             Cell::new(short_token).fg(ComfyColor::Magenta),
             Cell::new(short_tx).add_attribute(Attribute::Dim),
         ]);
     }
+
+    // Footer: fee totals across all fills
+    let totals = utils::fee_totals(trades);
+    table.add_row(vec![
+        Cell::new("TOTAL").add_attribute(Attribute::Bold),
+        Cell::new(""),
+        Cell::new(""),
+        Cell::new(format!("{} fills", totals.fills)),
+        Cell::new(""),
+        Cell::new(format!("{} notional", totals.notional_usdc.to_trimmed_string())),
+        Cell::new(""),
+        Cell::new(format!("{} ({} bps)", totals.fee_usdc.to_trimmed_string(), totals.fee_bps)).add_attribute(Attribute::Bold),
+        Cell::new(""),
+        Cell::new(""),
+    ]);
     println!("{}", table);
+
+    for trade in trades.iter().filter(|t| !t.asset_issues.is_empty()) {
+        eprintln!("{} {} log {}: {}", "⚠️ Asset mismatch".yellow(), trade.tx_hash, trade.log_index, trade.asset_issues.join("; "));
+    }
+}
+
+fn print_ctf_events_table(events: &[models::CtfEvent]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(120)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Event").add_attribute(Attribute::Bold),
+            Cell::new("Condition ID").add_attribute(Attribute::Bold),
+            Cell::new("Account").add_attribute(Attribute::Bold),
+            Cell::new("Details").add_attribute(Attribute::Bold),
+            Cell::new("Tx Hash").add_attribute(Attribute::Bold),
+        ]);
+
+    for event in events {
+        let (name, account, details) = ctf_event_summary(&event.kind);
+        table.add_row(vec![
+            Cell::new(event.block_number.to_string()),
+            Cell::new(name).fg(ComfyColor::Yellow),
+            Cell::new(utils::truncate_str(&event.condition_id, 6, 4)).fg(ComfyColor::Magenta),
+            Cell::new(utils::truncate_str(account, 6, 4)),
+            Cell::new(details),
+            Cell::new(utils::truncate_str(&event.tx_hash, 6, 4)).add_attribute(Attribute::Dim),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// (event name, acting account, one-line details) for table output.
+fn ctf_event_summary(kind: &models::CtfEventKind) -> (&'static str, &str, String) {
+    use models::CtfEventKind::*;
+    match kind {
+        PositionSplit { stakeholder, partition, amount, .. } =>
+            ("Split", stakeholder, format!("{} USDC -> {:?}", amount.to_trimmed_string(), partition)),
+        PositionsMerge { stakeholder, partition, amount, .. } =>
+            ("Merge", stakeholder, format!("{:?} -> {} USDC", partition, amount.to_trimmed_string())),
+        ConditionResolution { oracle, payout_numerators, .. } =>
+            ("Resolution", oracle, format!("payouts [{}]", payout_numerators.join(", "))),
+        PayoutRedemption { redeemer, index_sets, payout, .. } =>
+            ("Redemption", redeemer, format!("{:?} -> {} USDC", index_sets, payout.to_trimmed_string())),
+    }
+}
+
+fn print_timeline(timeline: &models::MarketTimeline) {
+    let market = &timeline.market;
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Time (UTC)").add_attribute(Attribute::Bold),
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Event").add_attribute(Attribute::Bold),
+            Cell::new("Account").add_attribute(Attribute::Bold),
+            Cell::new("Details").add_attribute(Attribute::Bold),
+            Cell::new("Tx Hash").add_attribute(Attribute::Bold),
+        ]);
+
+    for entry in &timeline.entries {
+        let (name, color, account, details) = match &entry.event {
+            models::TimelineEvent::Preparation(info) => (
+                "Preparation", ComfyColor::Cyan, info.oracle.clone(),
+                format!("{} outcome slots, question {}", info.outcome_slot_count, utils::truncate_str(&info.question_id, 6, 4)),
+            ),
+            models::TimelineEvent::Ctf(event) => {
+                let (name, account, details) = ctf_event_summary(&event.kind);
+                (name, ComfyColor::Yellow, account.to_string(), details)
+            }
+            models::TimelineEvent::Trade(trade) => {
+                let outcome = market.outcome_label(&trade.token_id).unwrap_or_else(|| "?".to_string());
+                let color = if trade.side == models::TradeSide::BUY { ComfyColor::Green } else { ComfyColor::Red };
+                (
+                    "Trade", color, trade.maker.clone(),
+                    format!("{:?} {} {} @ {}", trade.side, trade.size.to_trimmed_string(), outcome, trade.price.to_trimmed_string()),
+                )
+            }
+        };
+        table.add_row(vec![
+            Cell::new(time_label(entry.timestamp)),
+            Cell::new(entry.block_number.to_string()),
+            Cell::new(name).fg(color),
+            Cell::new(utils::truncate_str(&account, 6, 4)),
+            Cell::new(details),
+            Cell::new(entry.tx_hash.as_deref().map(|h| utils::truncate_str(h, 6, 4)).unwrap_or_default()).add_attribute(Attribute::Dim),
+        ]);
+    }
+
+    let summary = &timeline.summary;
+    let mut stats = Table::new();
+    stats.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Summary", "Value"]);
+    let block_label = |block: Option<u64>, ts: Option<u64>| match block {
+        Some(b) => format!("{} ({})", time_label(ts), b),
+        None => "-".to_string(),
+    };
+    stats.add_row(vec![Cell::new("conditionId").add_attribute(Attribute::Bold), Cell::new(&market.condition_id)]);
+    if let Some(title) = &market.title {
+        stats.add_row(vec![Cell::new("title").add_attribute(Attribute::Bold), Cell::new(title)]);
+    }
+    stats.add_row(vec![Cell::new("firstTrade").add_attribute(Attribute::Bold), Cell::new(block_label(summary.first_trade_block, summary.first_trade_timestamp))]);
+    stats.add_row(vec![Cell::new("lastTrade").add_attribute(Attribute::Bold), Cell::new(block_label(summary.last_trade_block, summary.last_trade_timestamp))]);
+    stats.add_row(vec![Cell::new("trades").add_attribute(Attribute::Bold), Cell::new(summary.trade_count.to_string())]);
+    stats.add_row(vec![Cell::new("volumeUsdc").add_attribute(Attribute::Bold), Cell::new(summary.volume_usdc.to_trimmed_string()).fg(ComfyColor::Cyan)]);
+    stats.add_row(vec![Cell::new("volumeShares").add_attribute(Attribute::Bold), Cell::new(summary.volume_shares.to_trimmed_string())]);
+    stats.add_row(vec![Cell::new("splits / merges").add_attribute(Attribute::Bold), Cell::new(format!("{} / {}", summary.splits, summary.merges))]);
+    stats.add_row(vec![Cell::new("redemptions").add_attribute(Attribute::Bold), Cell::new(format!("{} ({} USDC)", summary.redemptions, summary.redeemed_usdc.to_trimmed_string()))]);
+    let payouts = summary.payout_numerators.as_ref()
+        .map(|p| format!("[{}]", p.join(", ")))
+        .unwrap_or_else(|| "unresolved".to_string());
+    stats.add_row(vec![Cell::new("payoutVector").add_attribute(Attribute::Bold), Cell::new(payouts).fg(ComfyColor::Yellow)]);
+
+    println!("{}", table);
+    println!("{}", stats);
+}
+
+fn print_gamma_checks(checks: &[models::GammaCheck]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Slug").add_attribute(Attribute::Bold),
+            Cell::new("Condition ID").add_attribute(Attribute::Bold),
+            Cell::new("Tokens").add_attribute(Attribute::Bold),
+            Cell::new("NegRisk").add_attribute(Attribute::Bold),
+            Cell::new("Status").add_attribute(Attribute::Bold),
+            Cell::new("Check").add_attribute(Attribute::Bold),
+        ]);
+
+    for check in checks {
+        let market = &check.market;
+        let verdict = if check.is_valid() {
+            Cell::new("OK").fg(ComfyColor::Green)
+        } else {
+            Cell::new(check.issues.join("\n")).fg(ComfyColor::Red)
+        };
+        table.add_row(vec![
+            Cell::new(utils::truncate_str(&market.slug, 40, 0)),
+            Cell::new(utils::truncate_str(&market.condition_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(market.clob_token_ids.len().to_string()),
+            Cell::new(market.neg_risk.to_string()),
+            Cell::new(market.status.as_str()),
+            verdict,
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_position_balances(balances: &[models::PositionBalance], block: u64) {
+    println!("\n{} {}", "Balances at block".bold(), block.to_string().cyan());
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(120)
+        .set_header(vec![
+            Cell::new("Holder").add_attribute(Attribute::Bold),
+            Cell::new("Token ID").add_attribute(Attribute::Bold),
+            Cell::new("Outcome").add_attribute(Attribute::Bold),
+            Cell::new("Balance").add_attribute(Attribute::Bold),
+        ]);
+
+    for balance in balances {
+        table.add_row(vec![
+            Cell::new(&balance.holder),
+            Cell::new(utils::truncate_str(&balance.token_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(balance.outcome.as_deref().unwrap_or("-")),
+            Cell::new(balance.balance.to_trimmed_string()).fg(ComfyColor::Cyan),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_candles(bars: &[models::Candle]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Start (UTC)").add_attribute(Attribute::Bold),
+            Cell::new("Token ID").add_attribute(Attribute::Bold),
+            Cell::new("Outcome").add_attribute(Attribute::Bold),
+            Cell::new("Open").add_attribute(Attribute::Bold),
+            Cell::new("High").add_attribute(Attribute::Bold),
+            Cell::new("Low").add_attribute(Attribute::Bold),
+            Cell::new("Close").add_attribute(Attribute::Bold),
+            Cell::new("Volume").add_attribute(Attribute::Bold),
+            Cell::new("Volume (USDC)").add_attribute(Attribute::Bold),
+            Cell::new("Trades").add_attribute(Attribute::Bold),
+        ]);
+
+    for bar in bars {
+        let close_color = if bar.close >= bar.open { ComfyColor::Green } else { ComfyColor::Red };
+        table.add_row(vec![
+            Cell::new(utils::format_timestamp(bar.start)),
+            Cell::new(utils::truncate_str(&bar.token_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(bar.outcome.as_deref().unwrap_or("-")),
+            Cell::new(bar.open.to_trimmed_string()),
+            Cell::new(bar.high.to_trimmed_string()),
+            Cell::new(bar.low.to_trimmed_string()),
+            Cell::new(bar.close.to_trimmed_string()).fg(close_color),
+            Cell::new(bar.volume.to_trimmed_string()).fg(ComfyColor::Cyan),
+            Cell::new(bar.volume_usdc.to_trimmed_string()),
+            Cell::new(bar.trades.to_string()),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_wallet_pnl(report: &models::WalletPnl) {
+    let signed = |amount: amount::SignedAmount| {
+        let cell = Cell::new(amount.to_trimmed_string());
+        if amount.is_negative() { cell.fg(ComfyColor::Red) } else { cell.fg(ComfyColor::Green) }
+    };
+
+    for market in &report.markets {
+        let name = market.title.as_deref()
+            .or(market.condition_id.as_deref())
+            .unwrap_or("Unknown market");
+        let status = if market.resolved { "resolved".green() } else { "open".yellow() };
+        println!("\n{} [{}]", name.bold(), status);
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(140)
+            .set_header(vec![
+                Cell::new("Token ID").add_attribute(Attribute::Bold),
+                Cell::new("Outcome").add_attribute(Attribute::Bold),
+                Cell::new("Shares").add_attribute(Attribute::Bold),
+                Cell::new("Avg Price").add_attribute(Attribute::Bold),
+                Cell::new("Mark").add_attribute(Attribute::Bold),
+                Cell::new("Realized").add_attribute(Attribute::Bold),
+                Cell::new("Unrealized").add_attribute(Attribute::Bold),
+            ]);
+
+        for position in &market.positions {
+            let price = |p: Option<Amount>| p.map_or("-".to_string(), |p| p.to_trimmed_string());
+            table.add_row(vec![
+                Cell::new(utils::truncate_str(&position.token_id, 8, 6)).fg(ComfyColor::Magenta),
+                Cell::new(position.outcome.as_deref().unwrap_or("-")),
+                Cell::new(position.shares.to_trimmed_string()).fg(ComfyColor::Cyan),
+                Cell::new(price(position.avg_price)),
+                Cell::new(price(position.mark_price)),
+                signed(position.realized),
+                position.unrealized.map_or(Cell::new("-"), signed),
+            ]);
+        }
+        println!("{}", table);
+        println!("  {} {}  {} {}  {} {}",
+            "Realized:".bold(), market.realized.to_trimmed_string(),
+            "Unrealized:".bold(), market.unrealized.to_trimmed_string(),
+            "Total:".bold(), market.total.to_trimmed_string());
+    }
+
+    println!("\n{} {} ({} events, blocks {}-{})", "Wallet".bold(), report.wallet.cyan(), report.events, report.from_block, report.to_block);
+    println!("  {} {}", "Realized PnL:  ".bold(), report.realized.to_trimmed_string());
+    println!("  {} {}", "Unrealized PnL:".bold(), report.unrealized.to_trimmed_string());
+    println!("  {} {}", "Total PnL:     ".bold(), report.total.to_trimmed_string().bold());
+}
+
+fn print_registered_tokens(tokens: &[models::RegisteredToken]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Token ID").add_attribute(Attribute::Bold),
+            Cell::new("Complement").add_attribute(Attribute::Bold),
+            Cell::new("Condition ID").add_attribute(Attribute::Bold),
+            Cell::new("Outcome").add_attribute(Attribute::Bold),
+            Cell::new("Venue").add_attribute(Attribute::Bold),
+        ]);
+
+    for token in tokens {
+        let outcome = token.outcome_index.map_or("?".to_string(), |slot| models::outcome_label(slot as usize, true));
+        table.add_row(vec![
+            Cell::new(token.block_number.to_string()),
+            Cell::new(utils::truncate_str(&token.token_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(utils::truncate_str(&token.complement_id, 8, 6)),
+            Cell::new(utils::truncate_str(&token.condition_id, 8, 6)).fg(ComfyColor::Cyan),
+            Cell::new(outcome),
+            Cell::new(venue_label(token.venue)),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_unresolved_tokens(tokens: &[models::UnresolvedToken]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Token ID").add_attribute(Attribute::Bold),
+            Cell::new("First Block").add_attribute(Attribute::Bold),
+            Cell::new("Last Block").add_attribute(Attribute::Bold),
+            Cell::new("Trades").add_attribute(Attribute::Bold),
+            Cell::new("Attempts").add_attribute(Attribute::Bold),
+            Cell::new("Reason").add_attribute(Attribute::Bold),
+        ]);
+
+    for token in tokens {
+        table.add_row(vec![
+            Cell::new(utils::truncate_str(&token.token_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(token.first_seen_block.to_string()),
+            Cell::new(token.last_seen_block.to_string()),
+            Cell::new(token.trade_count.to_string()),
+            Cell::new(token.attempts.to_string()),
+            Cell::new(&token.reason).fg(ComfyColor::Yellow),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_neg_risk_market(market: &models::NegRiskMarket) {
+    println!("\n{} {}", "Neg-risk market".magenta().bold(), market.market_id.cyan());
+    if let (Some(oracle), Some(fee_bips)) = (&market.oracle, market.fee_bips) {
+        println!("  oracle {} | fee {} bips | {} conversions", oracle, fee_bips, market.conversions);
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("#").add_attribute(Attribute::Bold),
+            Cell::new("Question ID").add_attribute(Attribute::Bold),
+            Cell::new("Condition ID").add_attribute(Attribute::Bold),
+            Cell::new("YES Token").add_attribute(Attribute::Bold),
+            Cell::new("NO Token").add_attribute(Attribute::Bold),
+            Cell::new("Outcome").add_attribute(Attribute::Bold),
+        ]);
+
+    for question in &market.questions {
+        let outcome = match question.outcome {
+            Some(true) => Cell::new("YES").fg(ComfyColor::Green),
+            Some(false) => Cell::new("NO").fg(ComfyColor::Red),
+            None => Cell::new("-"),
+        };
+        let token = |id: Option<&str>| utils::truncate_str(id.unwrap_or("-"), 8, 6);
+        table.add_row(vec![
+            Cell::new(question.index.to_string()),
+            Cell::new(utils::truncate_str(&question.question_id, 8, 6)),
+            Cell::new(utils::truncate_str(&question.condition.condition_id, 8, 6)).fg(ComfyColor::Magenta),
+            Cell::new(token(question.condition.yes_token_id())).fg(ComfyColor::Green),
+            Cell::new(token(question.condition.no_token_id())).fg(ComfyColor::Red),
+            outcome,
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_uma_resolutions(states: &[models::UmaResolution]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(140)
+        .set_header(vec![
+            Cell::new("Question ID").add_attribute(Attribute::Bold),
+            Cell::new("Title").add_attribute(Attribute::Bold),
+            Cell::new("Status").add_attribute(Attribute::Bold),
+            Cell::new("Disputes").add_attribute(Attribute::Bold),
+            Cell::new("Proposed").add_attribute(Attribute::Bold),
+            Cell::new("Payouts").add_attribute(Attribute::Bold),
+            Cell::new("Last Block").add_attribute(Attribute::Bold),
+        ]);
+
+    let payouts = |p: Option<&Vec<String>>| p.map(|p| format!("[{}]", p.join(", "))).unwrap_or_else(|| "-".to_string());
+    for state in states {
+        let (label, color) = match state.status {
+            models::UmaStatus::Unproposed => ("UNPROPOSED", ComfyColor::Grey),
+            models::UmaStatus::Proposed => ("PROPOSED", ComfyColor::Cyan),
+            models::UmaStatus::Disputed => ("DISPUTED", ComfyColor::Red),
+            models::UmaStatus::Settled => ("SETTLED", ComfyColor::Green),
+            models::UmaStatus::Reset => ("RESET", ComfyColor::Yellow),
+        };
+        let mut status = label.to_string();
+        if state.flagged {
+            status.push_str(" (flagged)");
+        }
+        if state.paused {
+            status.push_str(" (paused)");
+        }
+        table.add_row(vec![
+            Cell::new(utils::truncate_str(&state.question_id, 8, 6)),
+            Cell::new(state.title.as_deref().unwrap_or("-")),
+            Cell::new(status).fg(color),
+            Cell::new(state.disputes.to_string()).fg(if state.disputes > 0 { ComfyColor::Red } else { ComfyColor::Reset }),
+            Cell::new(payouts(state.proposed_payouts.as_ref())),
+            Cell::new(payouts(state.payouts.as_ref())).fg(ComfyColor::Yellow),
+            Cell::new(state.last_block.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string())),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn print_matches_table(matches: &[models::MatchOutput]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_width(120)
+        .set_header(vec![
+            Cell::new("Taker Side").add_attribute(Attribute::Bold),
+            Cell::new("Venue").add_attribute(Attribute::Bold),
+            Cell::new("VWAP").add_attribute(Attribute::Bold),
+            Cell::new("Size").add_attribute(Attribute::Bold),
+            Cell::new("Maker Fills").add_attribute(Attribute::Bold),
+            Cell::new("Token ID").add_attribute(Attribute::Bold),
+            Cell::new("Taker").add_attribute(Attribute::Bold),
+            Cell::new("Tx Hash").add_attribute(Attribute::Bold),
+        ]);
+
+    for m in matches {
+        let side_color = match m.side {
+            models::TradeSide::BUY => "BUY".green().bold(),
+            models::TradeSide::SELL => "SELL".red().bold(),
+            _ => "UNKNOWN".yellow(),
+        };
+
+        table.add_row(vec![
+            Cell::new(side_color.to_string()),
+            Cell::new(venue_label(m.matched.venue)),
+            Cell::new(m.price.to_string()).fg(ComfyColor::Cyan),
+            Cell::new(m.size.to_string()),
+            Cell::new(m.maker_fills.len().to_string()),
+            Cell::new(utils::truncate_str(&m.token_id, 6, 4)).fg(ComfyColor::Magenta),
+            Cell::new(utils::truncate_str(&m.matched.taker_order_maker, 6, 4)),
+            Cell::new(utils::truncate_str(&m.matched.tx_hash, 6, 4)).add_attribute(Attribute::Dim),
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn venue_label(venue: models::ExchangeVenue) -> &'static str {
+    match venue {
+        models::ExchangeVenue::Binary => "Binary",
+        models::ExchangeVenue::NegRisk => "NegRisk",
+    }
 }
 
 fn print_market_info(info: &models::MarketInfo) {
@@ -485,12 +1696,42 @@ fn print_market_info(info: &models::MarketInfo) {
 
     table.add_row(vec![Cell::new("conditionId").add_attribute(Attribute::Bold), Cell::new(&info.condition_id)]);
     table.add_row(vec![Cell::new("questionId").add_attribute(Attribute::Bold), Cell::new(&info.question_id)]);
+    if let Some(title) = &info.title {
+        table.add_row(vec![Cell::new("title").add_attribute(Attribute::Bold), Cell::new(title).fg(ComfyColor::Cyan)]);
+    }
+    if let Some(description) = &info.description {
+        table.add_row(vec![Cell::new("description").add_attribute(Attribute::Bold), Cell::new(description)]);
+    }
     table.add_row(vec![Cell::new("oracle").add_attribute(Attribute::Bold), Cell::new(&info.oracle)]);
     table.add_row(vec![Cell::new("outcomeSlotCount").add_attribute(Attribute::Bold), Cell::new(info.outcome_slot_count.to_string())]);
     table.add_row(vec![Cell::new("collateralToken").add_attribute(Attribute::Bold), Cell::new(&info.collateral_token)]);
+    table.add_row(vec![Cell::new("negRisk").add_attribute(Attribute::Bold), Cell::new(info.neg_risk.to_string())]);
+    if let Some(adapter) = &info.adapter {
+        table.add_row(vec![Cell::new("adapter").add_attribute(Attribute::Bold), Cell::new(adapter)]);
+    }
     
-    table.add_row(vec![Cell::new("yesTokenId").add_attribute(Attribute::Bold), Cell::new(&info.yes_token_id).fg(ComfyColor::Green)]);
-    table.add_row(vec![Cell::new("noTokenId").add_attribute(Attribute::Bold), Cell::new(&info.no_token_id).fg(ComfyColor::Red)]);
+    for (slot, outcome) in info.outcomes.iter().enumerate() {
+        let label = models::outcome_label(slot, info.is_binary());
+        let color = match label.as_str() {
+            "YES" => ComfyColor::Green,
+            "NO" => ComfyColor::Red,
+            _ => ComfyColor::Magenta,
+        };
+        let value = format!("{}\nindexSet {} | collection {}", outcome.position_id, outcome.index_set, outcome.collection_id);
+        table.add_row(vec![Cell::new(label).add_attribute(Attribute::Bold), Cell::new(value).fg(color)]);
+    }
+    for combined in &info.combined_positions {
+        let value = format!("{}\ncollection {}", combined.position_id, combined.collection_id);
+        table.add_row(vec![Cell::new(format!("indexSet {}", combined.index_set)).add_attribute(Attribute::Bold), Cell::new(value)]);
+    }
+
+    if let (Some(number), Some(hash)) = (info.block_number, &info.block_hash) {
+        table.add_row(vec![Cell::new("blockNumber").add_attribute(Attribute::Bold), Cell::new(number.to_string())]);
+        table.add_row(vec![Cell::new("blockHash").add_attribute(Attribute::Bold), Cell::new(hash)]);
+    }
+    if let Some(timestamp) = info.timestamp {
+        table.add_row(vec![Cell::new("timestamp").add_attribute(Attribute::Bold), Cell::new(utils::format_timestamp(timestamp))]);
+    }
 
     println!("\n{}", "Market Decoder Result 🧩".cyan().bold());
     println!("{}", table);
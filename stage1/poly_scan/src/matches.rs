@@ -0,0 +1,89 @@
+use crate::amount::Amount;
+use crate::models::{MatchOutput, OrdersMatchedEvent, TradeAction, TradeOutput, TradeSide};
+use crate::utils::calculate_price;
+use ethers::types::U256;
+use std::collections::HashMap;
+
+// The exchange emits, per match and in log order: one OrderFilled per maker order, one
+// taker-summary OrderFilled (taker == exchange), then OrdersMatched. So a fill belongs to the
+// first OrdersMatched after it in the same transaction and exchange.
+
+/// Sets `match_log_index` on every fill that is followed by an `OrdersMatched` in its transaction.
+///
+/// A match's taker order trades a single token. A maker fill of another token traded against
+/// it through the complementary outcome: both sides bought (the exchange minted the pair) or
+/// both sold (it merged them), so the taker's action is the maker's side of the taker's token.
+pub fn link_fills(trades: &mut [TradeOutput], matched: &[OrdersMatchedEvent]) {
+    for trade in trades.iter_mut() {
+        let parent = matched.iter()
+            .filter(|m| m.tx_hash == trade.tx_hash && m.exchange == trade.exchange && m.log_index > trade.log_index)
+            .min_by_key(|m| m.log_index);
+        trade.match_log_index = parent.map(|m| m.log_index);
+
+        let taker_token = parent
+            .and_then(|m| [&m.maker_asset_id, &m.taker_asset_id].into_iter().find(|id| id.as_str() != "0"))
+            .filter(|token| trade.side != TradeSide::UNKNOWN && **token != trade.token_id);
+        trade.complementary = taker_token.is_some();
+        trade.taker_action = match taker_token {
+            Some(token) => TradeAction::new(trade.side, token),
+            None => TradeAction::new(trade.side.opposite(), &trade.token_id),
+        };
+        trade.label_actions();
+    }
+}
+
+/// Groups linked fills under their `OrdersMatched`. Fills without a parent match are dropped.
+/// `price_scale` is the number of decimals kept in the VWAP.
+pub fn group_matches(trades: Vec<TradeOutput>, matched: Vec<OrdersMatchedEvent>, price_scale: u32) -> Vec<MatchOutput> {
+    let mut fills_by_match: HashMap<(String, u64), Vec<TradeOutput>> = HashMap::new();
+    for trade in trades {
+        if let Some(match_index) = trade.match_log_index {
+            fills_by_match.entry((trade.tx_hash.clone(), match_index)).or_default().push(trade);
+        }
+    }
+
+    let mut matches: Vec<MatchOutput> = matched.into_iter().map(|m| {
+        let mut fills = fills_by_match.remove(&(m.tx_hash.clone(), m.log_index)).unwrap_or_default();
+        fills.sort_by_key(|f| f.log_index);
+
+        let taker_pos = fills.iter().position(|f| f.order_hash == m.taker_order_hash && f.taker == m.exchange);
+        let taker_fill = taker_pos.map(|i| fills.remove(i));
+
+        build_match(m, taker_fill, fills, price_scale)
+    }).collect();
+
+    matches.sort_by_key(|m| (m.matched.block_number, m.matched.log_index));
+    matches
+}
+
+fn build_match(matched: OrdersMatchedEvent, taker_fill: Option<TradeOutput>, maker_fills: Vec<TradeOutput>, price_scale: u32) -> MatchOutput {
+    let maker_amount = U256::from_dec_str(&matched.maker_amount_filled).unwrap_or_default();
+    let taker_amount = U256::from_dec_str(&matched.taker_amount_filled).unwrap_or_default();
+
+    // Taker order pays USDC (asset 0) => taker BUYs the outcome token
+    let (side, token_id, usdc, tokens) = if matched.maker_asset_id == "0" {
+        (TradeSide::BUY, matched.taker_asset_id.clone(), maker_amount, taker_amount)
+    } else if matched.taker_asset_id == "0" {
+        (TradeSide::SELL, matched.maker_asset_id.clone(), taker_amount, maker_amount)
+    } else {
+        (TradeSide::UNKNOWN, matched.maker_asset_id.clone(), maker_amount, taker_amount)
+    };
+
+    // The taker order's totals are the sum of the maker fills, so this ratio is the VWAP
+    let price = calculate_price(usdc, 6, tokens, 6, price_scale);
+    let size = Amount::new(tokens, 6);
+
+    MatchOutput {
+        matched,
+        token_id,
+        side,
+        size,
+        price,
+        taker_fill,
+        maker_fills,
+    }
+}
+
+#[cfg(test)]
+#[path = "matches_test.rs"]
+mod matches_test;
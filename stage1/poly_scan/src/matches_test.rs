@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::matches::{group_matches, link_fills};
+    use crate::models::{ExchangeVenue, OrdersMatchedEvent, TradeAction, TradeOutput, TradeSide};
+
+    const EXCHANGE: &str = "0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e";
+
+    /// `gives`/`gets` are the maker's (asset ID, amount) pairs.
+    fn fill(log_index: u64, order_hash: &str, (maker, taker): (&str, &str), gives: (&str, &str), gets: (&str, &str)) -> TradeOutput {
+        let ((maker_asset, maker_amt), (taker_asset, taker_amt)) = (gives, gets);
+        TradeOutput {
+            tx_hash: "0xt1".to_string(),
+            log_index,
+            order_hash: order_hash.to_string(),
+            maker: maker.to_string(),
+            taker: taker.to_string(),
+            maker_asset_id: maker_asset.to_string(),
+            taker_asset_id: taker_asset.to_string(),
+            maker_amount_filled: maker_amt.to_string(),
+            taker_amount_filled: taker_amt.to_string(),
+            ..TradeOutput::fixture("0xabc").with_side(TradeSide::SELL)
+        }
+    }
+
+    fn matched(log_index: u64, taker_order_hash: &str) -> OrdersMatchedEvent {
+        // Taker buys 300 tokens for 150 USDC
+        OrdersMatchedEvent {
+            tx_hash: "0xt1".to_string(),
+            log_index,
+            block_number: 100,
+            exchange: EXCHANGE.to_string(),
+            venue: ExchangeVenue::Binary,
+            taker_order_hash: taker_order_hash.to_string(),
+            taker_order_maker: "0xtaker".to_string(),
+            maker_asset_id: "0".to_string(),
+            taker_asset_id: "0xabc".to_string(),
+            maker_amount_filled: "150000000".to_string(),
+            taker_amount_filled: "300000000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_fills_are_grouped_under_their_match() {
+        // Two makers sell 100 @ 0.40 and 200 @ 0.55, then the taker summary, then OrdersMatched.
+        // A second, unrelated match follows in the same transaction.
+        let mut trades = vec![
+            fill(1, "0xm1", ("0xmaker1", "0xtaker"), ("0xabc", "100000000"), ("0", "40000000")),
+            fill(2, "0xm2", ("0xmaker2", "0xtaker"), ("0xabc", "200000000"), ("0", "110000000")),
+            fill(3, "0xt", ("0xtaker", EXCHANGE), ("0", "150000000"), ("0xabc", "300000000")),
+            fill(6, "0xm3", ("0xmaker3", "0xother"), ("0xabc", "10000000"), ("0", "5000000")),
+        ];
+        let events = vec![matched(4, "0xt"), matched(8, "0xt2")];
+
+        link_fills(&mut trades, &events);
+        let links: Vec<Option<u64>> = trades.iter().map(|t| t.match_log_index).collect();
+        assert_eq!(links, vec![Some(4), Some(4), Some(4), Some(8)]);
+
+        let grouped = group_matches(trades, events, 6);
+        assert_eq!(grouped.len(), 2);
+
+        let first = &grouped[0];
+        assert_eq!(first.matched.log_index, 4);
+        assert_eq!(first.side, TradeSide::BUY);
+        assert_eq!(first.maker_fills.len(), 2);
+        assert_eq!(first.taker_fill.as_ref().map(|f| f.log_index), Some(3));
+        assert_eq!(first.size.to_string(), "300.000000");
+        assert_eq!(first.price.to_string(), "0.500000");
+
+        // No taker-summary fill in the second match
+        assert!(grouped[1].taker_fill.is_none());
+        assert_eq!(grouped[1].maker_fills.len(), 1);
+    }
+
+    #[test]
+    fn test_fills_of_the_other_outcome_are_complementary() {
+        // The taker buys 0xabc (YES); a maker buying 0xabd (NO) is filled by minting the pair
+        let no_buy = TradeOutput {
+            token_id: "0xabd".to_string(),
+            side: TradeSide::BUY,
+            maker_action: TradeAction::new(TradeSide::BUY, "0xabd"),
+            complement_token_id: Some("0xabc".to_string()),
+            outcome_index: Some(1),
+            ..fill(1, "0xm1", ("0xmaker1", "0xtaker"), ("0", "60000000"), ("0xabd", "100000000"))
+        };
+        let yes_sell = fill(2, "0xm2", ("0xmaker2", "0xtaker"), ("0xabc", "200000000"), ("0", "110000000"));
+        let mut trades = vec![no_buy, yes_sell];
+        link_fills(&mut trades, &[matched(4, "0xt")]);
+
+        assert!(trades[0].complementary);
+        assert_eq!(trades[0].maker_action.to_string(), "BUY NO");
+        assert_eq!(trades[0].taker_action, TradeAction { side: TradeSide::BUY, token_id: "0xabc".to_string(), outcome: Some("YES".to_string()) });
+
+        // Same token as the taker: an ordinary trade between the two
+        assert!(!trades[1].complementary);
+        assert_eq!(trades[1].taker_action, TradeAction::new(TradeSide::BUY, "0xabc"));
+    }
+}
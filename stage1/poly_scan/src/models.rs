@@ -1,20 +1,77 @@
+use crate::amount::{Amount, SignedAmount};
+use crate::utils::canonical_token_id;
 use serde::Serialize;
 
 
-#[derive(Serialize, Debug)]
-#[allow(dead_code)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TradeSide {
     BUY,
     SELL,
     UNKNOWN,
 }
 
-#[derive(Serialize, Debug)]
+impl TradeSide {
+    /// The counterparty's side of the same token.
+    pub fn opposite(self) -> Self {
+        match self {
+            TradeSide::BUY => TradeSide::SELL,
+            TradeSide::SELL => TradeSide::BUY,
+            TradeSide::UNKNOWN => TradeSide::UNKNOWN,
+        }
+    }
+}
+
+/// What one participant of a fill did: bought or sold `token_id`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeAction {
+    pub side: TradeSide,
+    pub token_id: String,
+    /// YES/NO once the token's registration is known
+    pub outcome: Option<String>,
+}
+
+impl TradeAction {
+    pub fn new(side: TradeSide, token_id: &str) -> Self {
+        Self { side, token_id: token_id.to_string(), outcome: None }
+    }
+}
+
+impl std::fmt::Display for TradeAction {
+    /// "BUY YES", or the shortened token ID while the outcome is unknown.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Some(outcome) => write!(f, "{:?} {}", self.side, outcome),
+            None => write!(f, "{:?} {}", self.side, crate::utils::truncate_str(&self.token_id, 6, 4)),
+        }
+    }
+}
+
+/// Which Polymarket exchange contract emitted the fill.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExchangeVenue {
+    /// CTF Exchange, used by plain binary markets
+    Binary,
+    /// NegRisk_CTFExchange, used by multi-outcome (negative risk) markets
+    NegRisk,
+}
+
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeOutput {
     pub tx_hash: String,
     pub log_index: u64,
+    pub order_hash: String,
+    /// Log index of the `OrdersMatched` this fill belongs to (None for direct fills)
+    pub match_log_index: Option<u64>,
+    pub block_number: u64,
+    pub block_hash: String,
+    /// Unix time of the block (None if it could not be resolved)
+    pub timestamp: Option<u64>,
     pub exchange: String,
+    pub venue: ExchangeVenue,
     pub maker: String,
     pub taker: String,
     pub maker_asset_id: String,
@@ -23,19 +80,775 @@ pub struct TradeOutput {
     pub taker_amount_filled: String,
     pub maker_decimals: u32,
     pub taker_decimals: u32,
-    pub price: String,
+    /// USDC per outcome token
+    pub price: Amount,
+    /// Outcome tokens traded
+    pub size: Amount,
+    /// Raw fee in base units of the asset the maker received
+    pub fee: String,
+    /// Fee valued in USDC (token-denominated fees are converted at the fill price)
+    pub fee_usdc: Amount,
+    /// Fee relative to the USDC notional, in basis points
+    pub fee_bps: Amount,
     pub token_id: String,
+    /// The maker's side of `token_id`; UNKNOWN when neither leg is collateral
     pub side: TradeSide,
+    /// What the maker did (same as `side` and `token_id`)
+    pub maker_action: TradeAction,
+    /// What the taker did: the other side of `token_id`, or on complementary fills the other
+    /// outcome in the same direction as the maker
+    pub taker_action: TradeAction,
+    /// Maker and taker both bought (the exchange minted) or both sold (it merged) complementary
+    /// outcomes, rather than trading one token with each other
+    pub complementary: bool,
+    /// Condition of `token_id` per the exchange's token registry (None when unregistered)
+    pub condition_id: Option<String>,
+    /// The other outcome token of the same binary condition
+    pub complement_token_id: Option<String>,
+    /// Outcome slot of `token_id` (0 = YES)
+    pub outcome_index: Option<u32>,
+    /// Where the receipt's ERC-1155 transfers disagree with the fill (empty when consistent)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub asset_issues: Vec<String>,
+}
+
+impl TradeOutput {
+    /// The taker order's own fill in a match (taker == exchange). It repeats the volume of the
+    /// match's maker fills, so aggregates skip it.
+    pub fn is_taker_summary(&self) -> bool {
+        self.taker.eq_ignore_ascii_case(&self.exchange)
+    }
+
+    /// Names the outcome of each participant's action from `outcome_index`; the complement
+    /// holds the other binary slot.
+    pub fn label_actions(&mut self) {
+        let Some(index) = self.outcome_index else { return };
+        let token = canonical_token_id(&self.token_id);
+        let complement = self.complement_token_id.as_deref().and_then(canonical_token_id);
+        for action in [&mut self.maker_action, &mut self.taker_action] {
+            let id = canonical_token_id(&action.token_id);
+            action.outcome = if id.is_some() && id == token {
+                Some(outcome_label(index as usize, true))
+            } else if id.is_some() && id == complement && index < 2 {
+                Some(outcome_label(1 - index as usize, true))
+            } else {
+                None
+            };
+        }
+    }
+}
+
+/// Test fills. Fields without a helper are overridden with struct update syntax.
+#[cfg(test)]
+impl TradeOutput {
+    /// Maker 0x11 buys 1.25 `token_id` for 0.50 USDC from taker 0x22 on the binary exchange,
+    /// in block 100 with no fee.
+    pub fn fixture(token_id: &str) -> Self {
+        Self {
+            tx_hash: "0x64".to_string(),
+            log_index: 1,
+            order_hash: "0xaa".to_string(),
+            match_log_index: None,
+            block_number: 100,
+            block_hash: "0xb100".to_string(),
+            timestamp: None,
+            exchange: crate::consts::EXCHANGE_PROXY_ADDRESS.to_lowercase(),
+            venue: ExchangeVenue::Binary,
+            maker: "0x11".to_string(),
+            taker: "0x22".to_string(),
+            maker_asset_id: "0".to_string(),
+            taker_asset_id: token_id.to_string(),
+            maker_amount_filled: "500000".to_string(),
+            taker_amount_filled: "1250000".to_string(),
+            maker_decimals: 6,
+            taker_decimals: 6,
+            price: Amount::new(400_000.into(), 6),
+            size: Amount::new(1_250_000.into(), 6),
+            fee: "0".to_string(),
+            fee_usdc: Amount::zero(6),
+            fee_bps: Amount::zero(2),
+            token_id: token_id.to_string(),
+            side: TradeSide::BUY,
+            maker_action: TradeAction::new(TradeSide::BUY, token_id),
+            taker_action: TradeAction::new(TradeSide::SELL, token_id),
+            complementary: false,
+            condition_id: None,
+            complement_token_id: None,
+            outcome_index: None,
+            asset_issues: Vec::new(),
+        }
+    }
+
+    /// Moves the fill to `block_number`, with hash `0xb<block>` and transaction `0x<block in hex>`.
+    pub fn at_block(self, block_number: u64) -> Self {
+        Self {
+            tx_hash: format!("0x{:x}", block_number),
+            block_number,
+            block_hash: format!("0xb{}", block_number),
+            ..self
+        }
+    }
+
+    /// The maker's side of `token_id`; the taker gets the opposite.
+    pub fn with_side(self, side: TradeSide) -> Self {
+        Self {
+            side,
+            maker_action: TradeAction::new(side, &self.token_id),
+            taker_action: TradeAction::new(side.opposite(), &self.token_id),
+            ..self
+        }
+    }
+
+    /// The maker pays `usdc` for `shares`, both in base units; sets the amounts, size and price.
+    pub fn filled(self, usdc: u64, shares: u64) -> Self {
+        let (usdc, shares) = (Amount::new(usdc.into(), 6), Amount::new(shares.into(), 6));
+        Self {
+            maker_amount_filled: usdc.raw().to_string(),
+            taker_amount_filled: shares.raw().to_string(),
+            price: Amount::ratio(usdc, shares, crate::amount::DEFAULT_PRICE_SCALE).unwrap_or(Amount::zero(6)),
+            size: shares,
+            ..self
+        }
+    }
+}
+
+/// Fee totals over a set of fills.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTotals {
+    /// Fills summed; taker-order summaries are left out
+    pub fills: usize,
+    pub notional_usdc: Amount,
+    pub fee_usdc: Amount,
+    /// Effective rate: total fees over total notional
+    pub fee_bps: Amount,
+}
+
+/// JSON shape of the trades output (unless `--fills-only`): every fill plus the fee totals.
+#[derive(Serialize, Debug)]
+pub struct TradesReport<'a> {
+    pub trades: &'a [TradeOutput],
+    pub totals: FeeTotals,
+}
+
+/// `OrdersMatched`: emitted once per match, after all of its `OrderFilled` logs.
+/// Asset and amount fields describe the taker order.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrdersMatchedEvent {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub exchange: String,
+    pub venue: ExchangeVenue,
+    pub taker_order_hash: String,
+    pub taker_order_maker: String,
+    pub maker_asset_id: String,
+    pub taker_asset_id: String,
+    pub maker_amount_filled: String,
+    pub taker_amount_filled: String,
 }
 
+/// One match: the taker order, every maker fill against it, and the aggregate.
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
+pub struct MatchOutput {
+    #[serde(flatten)]
+    pub matched: OrdersMatchedEvent,
+    pub token_id: String,
+    /// Side of the taker order
+    pub side: TradeSide,
+    /// Outcome tokens traded by the taker order
+    pub size: Amount,
+    /// Volume-weighted price paid/received by the taker across all maker fills
+    pub price: Amount,
+    /// The taker-summary fill (its `taker` is the exchange itself)
+    pub taker_fill: Option<TradeOutput>,
+    pub maker_fills: Vec<TradeOutput>,
+}
+
+/// One CTF position of a condition: an index set (bitmask over outcome slots) under the
+/// market's collateral, with its collection ID and ERC-1155 position ID.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutcomePosition {
+    pub index_set: u64,
+    pub collection_id: String,
+    pub position_id: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct MarketInfo {
     pub condition_id: String,
     pub question_id: String,
+    /// From the UMA adapter's `QuestionInitialized` ancillary data (None for other oracles)
+    pub title: Option<String>,
+    pub description: Option<String>,
     pub oracle: String,
     pub outcome_slot_count: u64,
+    /// Collateral the positions were derived from: USDC.e, or WrappedCollateral for neg-risk markets
     pub collateral_token: String,
-    pub yes_token_id: String,
-    pub no_token_id: String,
+    /// Condition belongs to a neg-risk market (prepared by the NegRiskAdapter)
+    pub neg_risk: bool,
+    /// Adapter that prepared the condition and wraps its collateral (None for plain CTF markets)
+    pub adapter: Option<String>,
+    /// One position per outcome slot; `outcomes[i]` has index set `1 << i`
+    pub outcomes: Vec<OutcomePosition>,
+    /// Positions for unions of outcome slots (e.g. index set 3 = slot 0 or slot 1), only when requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub combined_positions: Vec<OutcomePosition>,
+    /// Block of the `ConditionPreparation` log (None for manually derived markets)
+    pub block_number: Option<u64>,
+    pub block_hash: Option<String>,
+    /// Unix time of that block
+    pub timestamp: Option<u64>,
+}
+
+impl MarketInfo {
+    pub fn is_binary(&self) -> bool {
+        self.outcomes.len() == 2
+    }
+
+    /// Binary convenience: position ID of slot 0 (YES). None unless the condition has two outcomes.
+    pub fn yes_token_id(&self) -> Option<&str> {
+        self.binary_slot(0)
+    }
+
+    /// Binary convenience: position ID of slot 1 (NO). None unless the condition has two outcomes.
+    pub fn no_token_id(&self) -> Option<&str> {
+        self.binary_slot(1)
+    }
+
+    fn binary_slot(&self, slot: usize) -> Option<&str> {
+        if !self.is_binary() {
+            return None;
+        }
+        self.outcomes.get(slot).map(|o| o.position_id.as_str())
+    }
+
+    /// Every position ID of the condition: single outcomes first, then combined sets.
+    pub fn position_ids(&self) -> impl Iterator<Item = &str> {
+        self.outcomes.iter().chain(&self.combined_positions).map(|o| o.position_id.as_str())
+    }
+
+    /// "YES"/"NO" on binary markets, `OUTCOME_<slot>` otherwise. None for combined or unknown positions.
+    pub fn outcome_label(&self, token_id: &str) -> Option<String> {
+        let token = canonical_token_id(token_id)?;
+        let slot = self.outcomes.iter()
+            .position(|o| canonical_token_id(&o.position_id).as_deref() == Some(token.as_str()))?;
+        Some(outcome_label(slot, self.is_binary()))
+    }
+}
+
+#[cfg(test)]
+impl MarketInfo {
+    /// Untitled binary USDC market `0xc0` by oracle 0x…01, prepared in block 90, with YES at
+    /// position `yes` and NO at `no`.
+    pub fn fixture(yes: &str, no: &str) -> Self {
+        let position = |index_set: u64, position_id: &str| OutcomePosition {
+            index_set,
+            collection_id: "0x00".to_string(),
+            position_id: position_id.to_string(),
+        };
+        Self {
+            condition_id: "0xc0".to_string(),
+            question_id: "0x0b".to_string(),
+            title: None,
+            description: None,
+            oracle: "0x0000000000000000000000000000000000000001".to_string(),
+            outcome_slot_count: 2,
+            collateral_token: crate::consts::USDC_ADDRESS.to_lowercase(),
+            neg_risk: false,
+            adapter: None,
+            outcomes: vec![position(1, yes), position(2, no)],
+            combined_positions: Vec::new(),
+            block_number: Some(90),
+            block_hash: Some("0xb90".to_string()),
+            timestamp: None,
+        }
+    }
+}
+
+/// Label for outcome slot `slot`.
+pub fn outcome_label(slot: usize, binary: bool) -> String {
+    match (binary, slot) {
+        (true, 0) => "YES".to_string(),
+        (true, 1) => "NO".to_string(),
+        _ => format!("OUTCOME_{}", slot),
+    }
+}
+
+/// A decoded CTF lifecycle log. `kind` carries the event-specific fields.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CtfEvent {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub block_hash: String,
+    pub condition_id: String,
+    #[serde(flatten)]
+    pub kind: CtfEventKind,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all_fields = "camelCase")]
+pub enum CtfEventKind {
+    /// Collateral (or a parent position) locked into one position per partition element
+    PositionSplit {
+        stakeholder: String,
+        collateral_token: String,
+        parent_collection_id: String,
+        partition: Vec<u64>,
+        amount: Amount,
+    },
+    /// The reverse of a split: a full set of positions burned for collateral
+    PositionsMerge {
+        stakeholder: String,
+        collateral_token: String,
+        parent_collection_id: String,
+        partition: Vec<u64>,
+        amount: Amount,
+    },
+    /// The oracle reported payouts; `payout_numerators[i]` is the share of outcome slot `i`
+    ConditionResolution {
+        oracle: String,
+        question_id: String,
+        outcome_slot_count: u64,
+        payout_numerators: Vec<String>,
+    },
+    /// Positions in `index_sets` burned for `payout` collateral after resolution
+    PayoutRedemption {
+        redeemer: String,
+        collateral_token: String,
+        parent_collection_id: String,
+        index_sets: Vec<u64>,
+        payout: Amount,
+    },
+}
+
+/// A question initialized on a UMA CTF Adapter, with its ancillary data decoded.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UmaQuestion {
+    pub question_id: String,
+    pub adapter: String,
+    pub request_timestamp: u64,
+    pub creator: String,
+    pub reward_token: String,
+    pub reward: String,
+    pub proposal_bond: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// How UMA price values map to payouts, e.g. "p1: 0, p2: 1, p3: 0.5. Where p1 corresponds to No, ..."
+    pub resolution_data: Option<String>,
+    pub initializer: Option<String>,
+    /// Raw ancillary data as UTF-8
+    pub ancillary_data: String,
+    pub block_number: Option<u64>,
+}
+
+/// A UMA CTF Adapter lifecycle log, or an Optimistic Oracle log for a request made by an adapter.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UmaEvent {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub question_id: String,
+    #[serde(flatten)]
+    pub kind: UmaEventKind,
+}
+
+/// Prices are raw int256 values (1e18 = YES, 0 = NO, 0.5e18 = 50/50).
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all_fields = "camelCase")]
+pub enum UmaEventKind {
+    QuestionResolved {
+        settled_price: String,
+        payouts: Vec<String>,
+    },
+    QuestionEmergencyResolved {
+        payouts: Vec<String>,
+    },
+    QuestionReset,
+    QuestionFlagged,
+    QuestionPaused,
+    QuestionUnpaused,
+    ProposePrice {
+        proposer: String,
+        request_timestamp: u64,
+        proposed_price: String,
+        /// End of the liveness window; undisputed proposals can be settled after it
+        expiration_timestamp: u64,
+    },
+    DisputePrice {
+        proposer: String,
+        disputer: String,
+        request_timestamp: u64,
+        proposed_price: String,
+    },
+    Settle {
+        proposer: String,
+        disputer: String,
+        request_timestamp: u64,
+        price: String,
+        /// Bond and reward paid to the winner (raw units of the request currency)
+        payout: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UmaStatus {
+    Unproposed,
+    Proposed,
+    Disputed,
+    Settled,
+    Reset,
+}
+
+/// Resolution state of one UMA question, folded from its events in chain order.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UmaResolution {
+    pub question_id: String,
+    /// From `QuestionInitialized`; None when that log is outside the scanned range
+    pub title: Option<String>,
+    pub adapter: Option<String>,
+    pub status: UmaStatus,
+    /// Disputes over the question's lifetime; the first one resets the request
+    pub disputes: u32,
+    pub flagged: bool,
+    pub paused: bool,
+    /// Oracle request currently answering the question (its timestamp); None right after a
+    /// reset until the new request's first proposal
+    pub request_timestamp: Option<u64>,
+    pub proposer: Option<String>,
+    pub proposed_price: Option<String>,
+    pub proposed_payouts: Option<Vec<String>>,
+    pub expiration_timestamp: Option<u64>,
+    pub settled_price: Option<String>,
+    pub payouts: Option<Vec<String>>,
+    pub last_block: Option<u64>,
+}
+
+/// A decoded NegRiskAdapter log. `kind` carries the event-specific fields.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NegRiskEvent {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub block_hash: String,
+    #[serde(flatten)]
+    pub kind: NegRiskEventKind,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", rename_all_fields = "camelCase")]
+pub enum NegRiskEventKind {
+    MarketPrepared {
+        market_id: String,
+        oracle: String,
+        fee_bips: u64,
+        /// Opaque metadata passed to the adapter (hex)
+        data: String,
+    },
+    QuestionPrepared {
+        market_id: String,
+        question_id: String,
+        index: u64,
+        data: String,
+    },
+    OutcomeReported {
+        market_id: String,
+        question_id: String,
+        outcome: bool,
+    },
+    /// NO positions of the questions in `index_set` converted into YES of every other question plus collateral
+    PositionsConverted {
+        stakeholder: String,
+        market_id: String,
+        index_set: String,
+        amount: Amount,
+    },
+    PositionSplit {
+        stakeholder: String,
+        condition_id: String,
+        amount: Amount,
+    },
+    PositionsMerge {
+        stakeholder: String,
+        condition_id: String,
+        amount: Amount,
+    },
+    /// `amounts` are the redeemed YES/NO position amounts
+    PayoutRedemption {
+        redeemer: String,
+        condition_id: String,
+        amounts: Vec<Amount>,
+        payout: Amount,
+    },
+}
+
+/// One neg-risk market ("who will win ...") with all of its questions.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NegRiskMarket {
+    pub market_id: String,
+    /// From `MarketPrepared`; None when that log is outside the scanned range
+    pub oracle: Option<String>,
+    pub fee_bips: Option<u64>,
+    pub data: Option<String>,
+    pub block_number: Option<u64>,
+    /// Ordered by question index
+    pub questions: Vec<NegRiskQuestion>,
+    /// Number of `PositionsConverted` seen for this market
+    pub conversions: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NegRiskQuestion {
+    pub index: u64,
+    pub question_id: String,
+    pub data: String,
+    /// Reported by `OutcomeReported` (true = YES won)
+    pub outcome: Option<bool>,
+    /// CTF condition prepared by the adapter, with its positions under the wrapped collateral
+    pub condition: MarketInfo,
+}
+
+/// Everything that happened on-chain to one condition, in chain order.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketTimeline {
+    pub market: MarketInfo,
+    pub entries: Vec<TimelineEntry>,
+    pub summary: TimelineSummary,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub block_number: u64,
+    /// Unix time of the block (None if the block could not be fetched)
+    pub timestamp: Option<u64>,
+    /// None for the preparation entry, which is decoded without its log position
+    pub log_index: Option<u64>,
+    pub tx_hash: Option<String>,
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", content = "data")]
+pub enum TimelineEvent {
+    Preparation(MarketInfo),
+    Ctf(CtfEvent),
+    Trade(Box<TradeOutput>),
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineSummary {
+    pub first_trade_block: Option<u64>,
+    pub first_trade_timestamp: Option<u64>,
+    pub last_trade_block: Option<u64>,
+    pub last_trade_timestamp: Option<u64>,
+    /// Fills counted once each (taker-summary fills excluded)
+    pub trade_count: usize,
+    pub volume_usdc: Amount,
+    pub volume_shares: Amount,
+    pub splits: usize,
+    pub merges: usize,
+    pub redemptions: usize,
+    pub redeemed_usdc: Amount,
+    /// From `ConditionResolution`; None while unresolved
+    pub payout_numerators: Option<Vec<String>>,
+}
+
+/// `TokenRegistered` on an exchange: the token and its complement are the two outcomes of the condition.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredToken {
+    pub token_id: String,
+    pub complement_id: String,
+    pub condition_id: String,
+    /// Slot the token re-derives to from the condition and the venue's collateral (0 = YES)
+    pub outcome_index: Option<u32>,
+    pub exchange: String,
+    pub venue: ExchangeVenue,
+    pub block_number: u64,
+}
+
+/// Position balance of one address, as replayed from CTF transfers.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionBalance {
+    pub holder: String,
+    pub token_id: String,
+    /// Outcome label when the position's market is known
+    pub outcome: Option<String>,
+    pub balance: Amount,
+}
+
+/// One OHLCV bar of a token's fills. Prices are USDC per outcome token.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Candle {
+    pub token_id: String,
+    pub outcome: Option<String>,
+    /// Bar start, unix seconds
+    pub start: u64,
+    pub open: Amount,
+    pub high: Amount,
+    pub low: Amount,
+    pub close: Amount,
+    /// Outcome tokens traded
+    pub volume: Amount,
+    pub volume_usdc: Amount,
+    pub trades: usize,
+}
+
+/// Average-cost PnL of one position held by a wallet.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionPnl {
+    pub token_id: String,
+    pub outcome: Option<String>,
+    /// Held at the end of the range
+    pub shares: Amount,
+    /// Remaining cost of `shares`
+    pub cost_basis: Amount,
+    pub avg_price: Option<Amount>,
+    /// Payout per share once resolved, else the wallet's last fill price
+    pub mark_price: Option<Amount>,
+    pub realized: SignedAmount,
+    /// None when the holding has no mark price
+    pub unrealized: Option<SignedAmount>,
+}
+
+/// A wallet's PnL on one market (condition).
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketPnl {
+    /// None groups positions whose market could not be found
+    pub condition_id: Option<String>,
+    pub title: Option<String>,
+    pub resolved: bool,
+    pub realized: SignedAmount,
+    pub unrealized: SignedAmount,
+    pub total: SignedAmount,
+    pub positions: Vec<PositionPnl>,
+}
+
+/// Realized and unrealized PnL of a wallet over a block range, per market.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletPnl {
+    pub wallet: String,
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Fills and split/merge/redemption events that moved the wallet's positions
+    pub events: usize,
+    pub realized: SignedAmount,
+    pub unrealized: SignedAmount,
+    pub total: SignedAmount,
+    pub markets: Vec<MarketPnl>,
+}
+
+/// A traded token no source could map to a market, queued for investigation.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedToken {
+    pub token_id: String,
+    pub first_seen_block: u64,
+    pub last_seen_block: u64,
+    /// Fills stored for this token without a market
+    pub trade_count: u64,
+    /// Resolution attempts (one per batch the token traded in)
+    pub attempts: u64,
+    /// Why the last attempt failed
+    pub reason: String,
+}
+
+/// A decoded event pushed by live `watch` mode.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", content = "data")]
+pub enum StreamEvent {
+    Trade(Box<TradeOutput>),
+    Market(Box<MarketInfo>),
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GammaStatus {
+    Active,
+    Closed,
+    Archived,
+    Inactive,
+}
+
+impl GammaStatus {
+    /// Value stored in `markets.status`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GammaStatus::Active => "active",
+            GammaStatus::Closed => "closed",
+            GammaStatus::Archived => "archived",
+            GammaStatus::Inactive => "inactive",
+        }
+    }
+}
+
+/// A market listed by the Gamma API, with its JSON-encoded lists decoded.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaMarket {
+    pub id: String,
+    pub slug: String,
+    pub question: Option<String>,
+    pub description: Option<String>,
+    pub condition_id: String,
+    pub question_id: Option<String>,
+    /// Decimal token IDs, one per entry of `outcomes`
+    pub clob_token_ids: Vec<String>,
+    pub outcomes: Vec<String>,
+    pub neg_risk: bool,
+    pub status: GammaStatus,
+    /// Resolution oracle per Gamma (a UMA CTF Adapter for most markets)
+    pub resolved_by: Option<String>,
+    /// Parent event, when discovered through `/events`
+    pub event_slug: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaEvent {
+    pub id: String,
+    pub slug: String,
+    pub title: Option<String>,
+    pub neg_risk: bool,
+    pub markets: Vec<GammaMarket>,
+}
+
+/// A Gamma market checked against IDs re-derived from its condition.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaCheck {
+    #[serde(flatten)]
+    pub market: GammaMarket,
+    /// Position IDs derived from `conditionId` (decimal, like `clobTokenIds`)
+    pub expected_token_ids: Vec<String>,
+    /// Empty when every ID matches
+    pub issues: Vec<String>,
+}
+
+impl GammaCheck {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
 }
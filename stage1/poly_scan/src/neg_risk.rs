@@ -0,0 +1,187 @@
+use crate::amount::Amount;
+use crate::consts::*;
+use crate::models::{NegRiskEvent, NegRiskEventKind, NegRiskMarket, NegRiskQuestion};
+use crate::utils::{decode_log_data, derive_market_info, format_address, get_condition_id, log_topics, token_uint, token_uint_array, uint_array_type};
+use anyhow::Result;
+use ethers::abi::{ParamType, Token};
+use ethers::types::{Address, Log, H256, U256};
+use ethers::utils::keccak256;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// topic0 of every NegRiskAdapter event decoded here.
+pub fn neg_risk_event_topics() -> Vec<H256> {
+    [
+        NEG_RISK_MARKET_PREPARED_EVENT_SIGNATURE,
+        NEG_RISK_QUESTION_PREPARED_EVENT_SIGNATURE,
+        NEG_RISK_OUTCOME_REPORTED_EVENT_SIGNATURE,
+        NEG_RISK_POSITIONS_CONVERTED_EVENT_SIGNATURE,
+        NEG_RISK_POSITION_SPLIT_EVENT_SIGNATURE,
+        NEG_RISK_POSITIONS_MERGE_EVENT_SIGNATURE,
+        NEG_RISK_PAYOUT_REDEMPTION_EVENT_SIGNATURE,
+    ]
+    .iter()
+    .map(|sig| H256::from(keccak256(sig)))
+    .collect()
+}
+
+/// Question `index` of neg-risk market `market_id`: the market ID with the index in its low byte.
+pub fn question_id(market_id: H256, index: u64) -> H256 {
+    let id = U256::from_big_endian(market_id.as_bytes()) + U256::from(index);
+    let mut bytes = [0u8; 32];
+    id.to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+/// Decodes a NegRiskAdapter log. Ok(None) for logs of any other event.
+pub fn decode_neg_risk_log(log: &Log) -> Result<Option<NegRiskEvent>> {
+    let Some(topic0) = log.topics.first() else { return Ok(None) };
+    let is = |sig: &str| *topic0 == H256::from(keccak256(sig));
+    let collateral = |token: &Token| -> Result<Amount> { Ok(Amount::new(token_uint(token)?, COLLATERAL_DECIMALS)) };
+
+    let kind = if is(NEG_RISK_MARKET_PREPARED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[ParamType::Uint(256), ParamType::Bytes])?;
+        NegRiskEventKind::MarketPrepared {
+            market_id: format!("{:?}", topics[0]),
+            oracle: format_address(Address::from(topics[1])),
+            fee_bips: token_uint(&data[0])?.low_u64(),
+            data: bytes_hex(&data[1]),
+        }
+    } else if is(NEG_RISK_QUESTION_PREPARED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[ParamType::Uint(256), ParamType::Bytes])?;
+        NegRiskEventKind::QuestionPrepared {
+            market_id: format!("{:?}", topics[0]),
+            question_id: format!("{:?}", topics[1]),
+            index: token_uint(&data[0])?.low_u64(),
+            data: bytes_hex(&data[1]),
+        }
+    } else if is(NEG_RISK_OUTCOME_REPORTED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[ParamType::Bool])?;
+        NegRiskEventKind::OutcomeReported {
+            market_id: format!("{:?}", topics[0]),
+            question_id: format!("{:?}", topics[1]),
+            outcome: data[0].clone().into_bool().unwrap_or_default(),
+        }
+    } else if is(NEG_RISK_POSITIONS_CONVERTED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[ParamType::Uint(256)])?;
+        NegRiskEventKind::PositionsConverted {
+            stakeholder: format_address(Address::from(topics[0])),
+            market_id: format!("{:?}", topics[1]),
+            index_set: format!("0x{:x}", U256::from_big_endian(topics[2].as_bytes())),
+            amount: collateral(&data[0])?,
+        }
+    } else if is(NEG_RISK_POSITION_SPLIT_EVENT_SIGNATURE) || is(NEG_RISK_POSITIONS_MERGE_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[ParamType::Uint(256)])?;
+        let stakeholder = format_address(Address::from(topics[0]));
+        let condition_id = format!("{:?}", topics[1]);
+        let amount = collateral(&data[0])?;
+        if is(NEG_RISK_POSITION_SPLIT_EVENT_SIGNATURE) {
+            NegRiskEventKind::PositionSplit { stakeholder, condition_id, amount }
+        } else {
+            NegRiskEventKind::PositionsMerge { stakeholder, condition_id, amount }
+        }
+    } else if is(NEG_RISK_PAYOUT_REDEMPTION_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[uint_array_type(), ParamType::Uint(256)])?;
+        NegRiskEventKind::PayoutRedemption {
+            redeemer: format_address(Address::from(topics[0])),
+            condition_id: format!("{:?}", topics[1]),
+            amounts: token_uint_array(&data[0])?.into_iter().map(|a| Amount::new(a, COLLATERAL_DECIMALS)).collect(),
+            payout: collateral(&data[1])?,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(NegRiskEvent {
+        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+        log_index: log.log_index.unwrap_or_default().as_u64(),
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+        block_hash: format!("{:?}", log.block_hash.unwrap_or_default()),
+        kind,
+    }))
+}
+
+/// Groups `MarketPrepared`/`QuestionPrepared` (and outcome reports / conversions) by market ID.
+/// Each question gets the condition the adapter prepares for it: oracle = adapter, two outcomes,
+/// positions under the wrapped collateral.
+pub fn group_markets(events: &[NegRiskEvent]) -> Result<Vec<NegRiskMarket>> {
+    let adapter = Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?;
+    let mut markets: BTreeMap<String, NegRiskMarket> = BTreeMap::new();
+    let mut outcomes: BTreeMap<String, bool> = BTreeMap::new();
+
+    for event in events {
+        match &event.kind {
+            NegRiskEventKind::MarketPrepared { market_id, oracle, fee_bips, data } => {
+                let market = market_entry(&mut markets, market_id);
+                market.oracle = Some(oracle.clone());
+                market.fee_bips = Some(*fee_bips);
+                market.data = Some(data.clone());
+                market.block_number = Some(event.block_number);
+            }
+            NegRiskEventKind::QuestionPrepared { market_id, question_id, index, data } => {
+                let question_hash = H256::from_str(question_id).map_err(|_| anyhow::anyhow!("Invalid question ID"))?;
+                let market_hash = H256::from_str(market_id).map_err(|_| anyhow::anyhow!("Invalid market ID"))?;
+                if self::question_id(market_hash, *index) != question_hash {
+                    eprintln!("Warning: question {} is not market {} + index {}", question_id, market_id, index);
+                }
+                let condition_id = get_condition_id(adapter, question_hash, U256::from(2));
+                let mut condition = derive_market_info(condition_id, question_hash, adapter, 2, true)?;
+                condition.block_number = Some(event.block_number);
+                condition.block_hash = Some(event.block_hash.clone());
+
+                market_entry(&mut markets, market_id).questions.push(NegRiskQuestion {
+                    index: *index,
+                    question_id: question_id.clone(),
+                    data: data.clone(),
+                    outcome: None,
+                    condition,
+                });
+            }
+            NegRiskEventKind::OutcomeReported { question_id, outcome, .. } => {
+                outcomes.insert(question_id.clone(), *outcome);
+            }
+            NegRiskEventKind::PositionsConverted { market_id, .. } => {
+                market_entry(&mut markets, market_id).conversions += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let mut grouped: Vec<NegRiskMarket> = markets.into_values().collect();
+    for market in &mut grouped {
+        market.questions.sort_by_key(|q| q.index);
+        for question in &mut market.questions {
+            question.outcome = outcomes.get(&question.question_id).copied();
+        }
+    }
+    Ok(grouped)
+}
+
+fn market_entry<'a>(markets: &'a mut BTreeMap<String, NegRiskMarket>, market_id: &str) -> &'a mut NegRiskMarket {
+    markets.entry(market_id.to_string()).or_insert_with(|| NegRiskMarket {
+        market_id: market_id.to_string(),
+        oracle: None,
+        fee_bips: None,
+        data: None,
+        block_number: None,
+        questions: Vec::new(),
+        conversions: 0,
+    })
+}
+
+fn bytes_hex(token: &Token) -> String {
+    match token {
+        Token::Bytes(bytes) => format!("0x{}", ethers::utils::hex::encode(bytes)),
+        _ => "0x".to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "neg_risk_test.rs"]
+mod neg_risk_test;
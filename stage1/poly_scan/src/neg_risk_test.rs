@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::*;
+    use crate::neg_risk::{decode_neg_risk_log, group_markets, question_id};
+    use crate::models::NegRiskEventKind;
+    use crate::utils::{get_collection_id, get_condition_id, get_position_id};
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, Bytes, Log, H256, U256, U64};
+    use ethers::utils::keccak256;
+    use std::str::FromStr;
+
+    fn adapter_log(signature: &str, topics: Vec<H256>, data: Vec<Token>, block: u64) -> Log {
+        let mut all_topics = vec![H256::from(keccak256(signature))];
+        all_topics.extend(topics);
+        Log {
+            address: Address::from_str(NEG_RISK_ADAPTER_ADDRESS).unwrap(),
+            topics: all_topics,
+            data: Bytes::from(encode(&data)),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            log_index: Some(U256::from(block)),
+            block_number: Some(U64::from(block)),
+            ..Default::default()
+        }
+    }
+
+    fn market_id() -> H256 {
+        let mut bytes = [0x4d; 32];
+        bytes[31] = 0; // Market IDs have a zero low byte
+        H256::from(bytes)
+    }
+
+    fn question_prepared(index: u64, block: u64) -> Log {
+        adapter_log(
+            NEG_RISK_QUESTION_PREPARED_EVENT_SIGNATURE,
+            vec![market_id(), question_id(market_id(), index)],
+            vec![Token::Uint(U256::from(index)), Token::Bytes(b"candidate".to_vec())],
+            block,
+        )
+    }
+
+    #[test]
+    fn test_questions_are_grouped_under_their_market() {
+        let oracle = Address::repeat_byte(0x0a);
+        let prepared = adapter_log(
+            NEG_RISK_MARKET_PREPARED_EVENT_SIGNATURE,
+            vec![market_id(), H256::from(oracle)],
+            vec![Token::Uint(U256::from(100)), Token::Bytes(vec![0xab, 0xcd])],
+            10,
+        );
+        let reported = adapter_log(
+            NEG_RISK_OUTCOME_REPORTED_EVENT_SIGNATURE,
+            vec![market_id(), question_id(market_id(), 1)],
+            vec![Token::Bool(true)],
+            40,
+        );
+        let events: Vec<_> = [prepared, question_prepared(1, 30), question_prepared(0, 20), reported]
+            .iter()
+            .map(|log| decode_neg_risk_log(log).unwrap().unwrap())
+            .collect();
+
+        let markets = group_markets(&events).unwrap();
+        assert_eq!(markets.len(), 1);
+        let market = &markets[0];
+        assert_eq!(market.market_id, format!("{:?}", market_id()));
+        assert_eq!(market.oracle, Some(format!("{:?}", oracle)));
+        assert_eq!(market.fee_bips, Some(100));
+        assert_eq!(market.data.as_deref(), Some("0xabcd"));
+
+        let indexes: Vec<u64> = market.questions.iter().map(|q| q.index).collect();
+        assert_eq!(indexes, vec![0, 1]);
+        assert_eq!(market.questions[0].outcome, None);
+        assert_eq!(market.questions[1].outcome, Some(true));
+
+        // Condition prepared by the adapter (as oracle), positions under the wrapped collateral
+        let adapter = Address::from_str(NEG_RISK_ADAPTER_ADDRESS).unwrap();
+        let wrapped = Address::from_str(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS).unwrap();
+        let question = question_id(market_id(), 1);
+        let condition_id = get_condition_id(adapter, question, U256::from(2));
+        let condition = &market.questions[1].condition;
+        assert_eq!(condition.condition_id, format!("{:?}", condition_id));
+        assert!(condition.neg_risk);
+        let no = get_position_id(wrapped, get_collection_id(H256::zero(), condition_id, U256::from(2)));
+        assert_eq!(condition.no_token_id(), Some(format!("0x{:x}", no).as_str()));
+    }
+
+    #[test]
+    fn test_decode_conversion_and_redemption() {
+        let stakeholder = H256::from(Address::repeat_byte(0x11));
+        let converted = adapter_log(
+            NEG_RISK_POSITIONS_CONVERTED_EVENT_SIGNATURE,
+            vec![stakeholder, market_id(), H256::from_low_u64_be(0b101)],
+            vec![Token::Uint(U256::from(3_000_000))],
+            50,
+        );
+        match decode_neg_risk_log(&converted).unwrap().unwrap().kind {
+            NegRiskEventKind::PositionsConverted { index_set, amount, .. } => {
+                assert_eq!(index_set, "0x5");
+                assert_eq!(amount.to_string(), "3.000000");
+            }
+            other => panic!("Expected conversion, got {:?}", other),
+        }
+
+        let redemption = adapter_log(
+            NEG_RISK_PAYOUT_REDEMPTION_EVENT_SIGNATURE,
+            vec![stakeholder, H256::repeat_byte(0xc0)],
+            vec![Token::Array(vec![Token::Uint(U256::from(2_000_000)), Token::Uint(U256::zero())]), Token::Uint(U256::from(2_000_000))],
+            60,
+        );
+        match decode_neg_risk_log(&redemption).unwrap().unwrap().kind {
+            NegRiskEventKind::PayoutRedemption { condition_id, amounts, payout, .. } => {
+                assert_eq!(condition_id, format!("{:?}", H256::repeat_byte(0xc0)));
+                assert_eq!(amounts.len(), 2);
+                assert_eq!(payout.to_string(), "2.000000");
+            }
+            other => panic!("Expected redemption, got {:?}", other),
+        }
+
+        // The CTF's own PositionSplit has a different signature and is not decoded here
+        let ctf_split = adapter_log(POSITION_SPLIT_EVENT_SIGNATURE, vec![stakeholder], vec![], 70);
+        assert!(decode_neg_risk_log(&ctf_split).unwrap().is_none());
+    }
+}
@@ -0,0 +1,386 @@
+use crate::amount::{Amount, SignedAmount, DEFAULT_PRICE_SCALE};
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::consts::*;
+use crate::models::{CtfEvent, CtfEventKind, MarketInfo, MarketPnl, NegRiskEvent, NegRiskEventKind, PositionPnl, TradeOutput, TradeSide, WalletPnl};
+use crate::scanner::Scanner;
+use crate::utils::{format_address, outcome_position, parse_token_id};
+use anyhow::Result;
+use ethers::types::{Address, H256, I256, U256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+// Everything is accounted in collateral base units: positions are minted 1:1 against the
+// collateral, so shares and USDC share its decimals. Neg-risk `PositionsConverted` is not
+// modelled; conversions show up as unexplained sales or redemptions of the positions involved.
+
+/// A change to a wallet's positions, in collateral base units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnlEvent {
+    /// `shares` are net of any fee charged in the position
+    Buy { token: U256, shares: U256, cost: U256 },
+    /// `proceeds` are net of any fee charged in collateral
+    Sell { token: U256, shares: U256, proceeds: U256 },
+    /// `amount` of collateral locked into `amount` of each position
+    Split { tokens: Vec<U256>, amount: U256 },
+    /// `amount` of each position burned back into collateral
+    Merge { tokens: Vec<U256>, amount: U256 },
+    /// Positions burned for `payout`: `amounts` when the event names them, else the whole holding
+    Redeem { tokens: Vec<U256>, amounts: Option<Vec<U256>>, payout: U256 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PnlEntry {
+    pub block_number: u64,
+    pub log_index: u64,
+    pub event: PnlEvent,
+}
+
+/// The fill from `wallet`'s side. Fills where it is the taker of a match are skipped (its own
+/// taker-order fill carries them), as are position-for-position fills.
+pub fn trade_entry(trade: &TradeOutput, wallet: &str) -> Option<PnlEntry> {
+    let token = parse_token_id(&trade.token_id)?;
+    if trade.maker_asset_id != "0" && trade.taker_asset_id != "0" {
+        return None;
+    }
+    let maker_amount = U256::from_dec_str(&trade.maker_amount_filled).ok()?;
+    let taker_amount = U256::from_dec_str(&trade.taker_amount_filled).ok()?;
+    let received = taker_amount.saturating_sub(U256::from_dec_str(&trade.fee).ok()?);
+
+    let event = if trade.maker.eq_ignore_ascii_case(wallet) {
+        match trade.side {
+            TradeSide::BUY => PnlEvent::Buy { token, shares: received, cost: maker_amount },
+            TradeSide::SELL => PnlEvent::Sell { token, shares: maker_amount, proceeds: received },
+            TradeSide::UNKNOWN => return None,
+        }
+    } else if trade.taker.eq_ignore_ascii_case(wallet) && trade.match_log_index.is_none() {
+        // Direct fill: the taker gives what the maker got and gets what the maker gave
+        match trade.side {
+            TradeSide::BUY => PnlEvent::Sell { token, shares: taker_amount, proceeds: maker_amount },
+            TradeSide::SELL => PnlEvent::Buy { token, shares: maker_amount, cost: taker_amount },
+            TradeSide::UNKNOWN => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(PnlEntry { block_number: trade.block_number, log_index: trade.log_index, event })
+}
+
+/// A top-level CTF split, merge or redemption by `wallet`. Splits of a parent position and
+/// resolutions are skipped.
+pub fn ctf_entry(event: &CtfEvent, wallet: &str) -> Option<PnlEntry> {
+    let condition_id = H256::from_str(&event.condition_id).ok()?;
+    let top_level = |parent: &str| H256::from_str(parent).is_ok_and(|p| p.is_zero());
+    let positions = |collateral: &str, index_sets: &[u64]| -> Option<Vec<U256>> {
+        let collateral = Address::from_str(collateral).ok()?;
+        index_sets.iter()
+            .map(|&set| parse_token_id(&outcome_position(collateral, condition_id, set).position_id))
+            .collect()
+    };
+
+    let pnl_event = match &event.kind {
+        CtfEventKind::PositionSplit { stakeholder, collateral_token, parent_collection_id, partition, amount }
+            if stakeholder.eq_ignore_ascii_case(wallet) && top_level(parent_collection_id) =>
+        {
+            PnlEvent::Split { tokens: positions(collateral_token, partition)?, amount: amount.raw() }
+        }
+        CtfEventKind::PositionsMerge { stakeholder, collateral_token, parent_collection_id, partition, amount }
+            if stakeholder.eq_ignore_ascii_case(wallet) && top_level(parent_collection_id) =>
+        {
+            PnlEvent::Merge { tokens: positions(collateral_token, partition)?, amount: amount.raw() }
+        }
+        CtfEventKind::PayoutRedemption { redeemer, collateral_token, parent_collection_id, index_sets, payout }
+            if redeemer.eq_ignore_ascii_case(wallet) && top_level(parent_collection_id) =>
+        {
+            PnlEvent::Redeem { tokens: positions(collateral_token, index_sets)?, amounts: None, payout: payout.raw() }
+        }
+        _ => return None,
+    };
+    Some(PnlEntry { block_number: event.block_number, log_index: event.log_index, event: pnl_event })
+}
+
+/// A NegRiskAdapter split, merge or redemption by `wallet`, over the YES/NO positions of wrapped
+/// collateral the adapter holds on the CTF.
+pub fn neg_risk_entry(event: &NegRiskEvent, wallet: &str) -> Option<PnlEntry> {
+    let tokens = |condition_id: &str| -> Option<Vec<U256>> {
+        let condition_id = H256::from_str(condition_id).ok()?;
+        let collateral = Address::from_str(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS).ok()?;
+        [1, 2].iter()
+            .map(|&set| parse_token_id(&outcome_position(collateral, condition_id, set).position_id))
+            .collect()
+    };
+
+    let pnl_event = match &event.kind {
+        NegRiskEventKind::PositionSplit { stakeholder, condition_id, amount } if stakeholder.eq_ignore_ascii_case(wallet) => {
+            PnlEvent::Split { tokens: tokens(condition_id)?, amount: amount.raw() }
+        }
+        NegRiskEventKind::PositionsMerge { stakeholder, condition_id, amount } if stakeholder.eq_ignore_ascii_case(wallet) => {
+            PnlEvent::Merge { tokens: tokens(condition_id)?, amount: amount.raw() }
+        }
+        NegRiskEventKind::PayoutRedemption { redeemer, condition_id, amounts, payout } if redeemer.eq_ignore_ascii_case(wallet) => {
+            let amounts = amounts.iter().map(|a| a.raw()).collect();
+            PnlEvent::Redeem { tokens: tokens(condition_id)?, amounts: Some(amounts), payout: payout.raw() }
+        }
+        _ => return None,
+    };
+    Some(PnlEntry { block_number: event.block_number, log_index: event.log_index, event: pnl_event })
+}
+
+/// Average-cost state of one position.
+#[derive(Debug, Default)]
+struct Holding {
+    shares: U256,
+    cost: U256,
+    realized: I256,
+    last_price: Option<Amount>,
+}
+
+impl Holding {
+    fn buy(&mut self, shares: U256, cost: U256) {
+        self.shares += shares;
+        self.cost += cost;
+    }
+
+    /// Sells at the average cost. Shares beyond the holding were acquired before the range and
+    /// count as free.
+    fn sell(&mut self, shares: U256, proceeds: U256) {
+        let sold = shares.min(self.shares);
+        let basis = if self.shares.is_zero() { U256::zero() } else { self.cost * sold / self.shares };
+        self.realized += I256::from_raw(proceeds) - I256::from_raw(basis);
+        self.cost -= basis;
+        self.shares -= sold;
+    }
+
+    fn mark(&mut self, shares: U256, collateral: U256) {
+        let amount = |raw| Amount::new(raw, COLLATERAL_DECIMALS);
+        if let Some(price) = Amount::ratio(amount(collateral), amount(shares), DEFAULT_PRICE_SCALE) {
+            self.last_price = Some(price);
+        }
+    }
+}
+
+/// Where a position sits: its market and outcome slot.
+struct Slot<'m> {
+    market: &'m MarketInfo,
+    index: usize,
+}
+
+/// Replays `entries` in chain order and values what is left: at the payout when the market has
+/// resolved (realized), else at the wallet's last fill price (unrealized).
+pub fn wallet_pnl(
+    wallet: &str,
+    from_block: u64,
+    to_block: u64,
+    mut entries: Vec<PnlEntry>,
+    markets: &[MarketInfo],
+    payouts: &HashMap<String, Vec<U256>>,
+) -> WalletPnl {
+    entries.sort_by_key(|e| (e.block_number, e.log_index));
+
+    let mut slots: HashMap<U256, Slot> = HashMap::new();
+    for market in markets {
+        for (index, outcome) in market.outcomes.iter().enumerate() {
+            if let Some(token) = parse_token_id(&outcome.position_id) {
+                slots.insert(token, Slot { market, index });
+            }
+        }
+    }
+    // Share of the collateral one unit of `token` pays out, as (numerator, denominator)
+    let payout = |token: &U256| -> Option<(U256, U256)> {
+        let slot = slots.get(token)?;
+        let numerators = payouts.get(&slot.market.condition_id)?;
+        let denominator = numerators.iter().fold(U256::zero(), |sum, n| sum + n);
+        (!denominator.is_zero()).then(|| (numerators.get(slot.index).copied().unwrap_or_default(), denominator))
+    };
+
+    let mut holdings: BTreeMap<U256, Holding> = BTreeMap::new();
+    for entry in &entries {
+        match &entry.event {
+            PnlEvent::Buy { token, shares, cost } => {
+                let holding = holdings.entry(*token).or_default();
+                holding.buy(*shares, *cost);
+                holding.mark(*shares, *cost);
+            }
+            PnlEvent::Sell { token, shares, proceeds } => {
+                let holding = holdings.entry(*token).or_default();
+                holding.sell(*shares, *proceeds);
+                holding.mark(*shares, *proceeds);
+            }
+            PnlEvent::Split { tokens, amount } => {
+                for (token, cost) in tokens.iter().zip(split_evenly(*amount, tokens.len())) {
+                    holdings.entry(*token).or_default().buy(*amount, cost);
+                }
+            }
+            PnlEvent::Merge { tokens, amount } => {
+                for (token, proceeds) in tokens.iter().zip(split_evenly(*amount, tokens.len())) {
+                    holdings.entry(*token).or_default().sell(*amount, proceeds);
+                }
+            }
+            PnlEvent::Redeem { tokens, amounts, payout: collateral } => {
+                let redeemed: Vec<U256> = match amounts {
+                    Some(amounts) => amounts.clone(),
+                    None => tokens.iter().map(|t| holdings.get(t).map(|h| h.shares).unwrap_or_default()).collect(),
+                };
+                // Attribute the payout by what each position is worth once resolved
+                let weights: Vec<U256> = tokens.iter().zip(&redeemed)
+                    .map(|(token, amount)| payout(token).map_or(*amount, |(numerator, _)| amount * numerator))
+                    .collect();
+                for ((token, amount), proceeds) in tokens.iter().zip(&redeemed).zip(split_weighted(*collateral, &weights)) {
+                    holdings.entry(*token).or_default().sell(*amount, proceeds);
+                }
+            }
+        }
+    }
+
+    let zero = || SignedAmount::new(I256::zero(), COLLATERAL_DECIMALS);
+    let mut by_market: BTreeMap<Option<String>, MarketPnl> = BTreeMap::new();
+    for (token, mut holding) in holdings {
+        let slot = slots.get(&token);
+        let settled = payout(&token);
+        let mut mark_price = holding.last_price;
+        let mut unrealized = None;
+
+        if let Some((numerator, denominator)) = settled {
+            let value = holding.shares * numerator / denominator;
+            holding.sell(holding.shares, value);
+            mark_price = Amount::ratio(Amount::new(numerator, 0), Amount::new(denominator, 0), DEFAULT_PRICE_SCALE);
+        } else if let Some(price) = holding.last_price {
+            let value = Amount::new(holding.shares, COLLATERAL_DECIMALS)
+                .checked_mul(price, COLLATERAL_DECIMALS)
+                .map_or(U256::zero(), |v| v.raw());
+            unrealized = Some(I256::from_raw(value) - I256::from_raw(holding.cost));
+        }
+
+        let shares = Amount::new(holding.shares, COLLATERAL_DECIMALS);
+        let cost_basis = Amount::new(holding.cost, COLLATERAL_DECIMALS);
+        let position = PositionPnl {
+            token_id: format!("0x{:x}", token),
+            outcome: slot.and_then(|s| s.market.outcome_label(&format!("0x{:x}", token))),
+            shares,
+            cost_basis,
+            avg_price: Amount::ratio(cost_basis, shares, DEFAULT_PRICE_SCALE),
+            mark_price,
+            realized: SignedAmount::new(holding.realized, COLLATERAL_DECIMALS),
+            unrealized: unrealized.map(|u| SignedAmount::new(u, COLLATERAL_DECIMALS)),
+        };
+
+        let condition_id = slot.map(|s| s.market.condition_id.clone());
+        let market = by_market.entry(condition_id.clone()).or_insert_with(|| MarketPnl {
+            condition_id,
+            title: slot.and_then(|s| s.market.title.clone()),
+            resolved: settled.is_some(),
+            realized: zero(),
+            unrealized: zero(),
+            total: zero(),
+            positions: Vec::new(),
+        });
+        market.realized = add(market.realized, position.realized);
+        market.unrealized = add(market.unrealized, position.unrealized.unwrap_or_else(zero));
+        market.total = add(market.realized, market.unrealized);
+        market.positions.push(position);
+    }
+
+    // Known markets in condition order, the unknown bucket last
+    let mut markets: Vec<MarketPnl> = by_market.into_values().collect();
+    markets.sort_by_key(|m| m.condition_id.is_none());
+
+    let realized = markets.iter().fold(zero(), |sum, m| add(sum, m.realized));
+    let unrealized = markets.iter().fold(zero(), |sum, m| add(sum, m.unrealized));
+    WalletPnl {
+        wallet: wallet.to_string(),
+        from_block,
+        to_block,
+        events: entries.len(),
+        realized,
+        unrealized,
+        total: add(realized, unrealized),
+        markets,
+    }
+}
+
+/// Fetches the wallet's fills, splits, merges and redemptions in `[from_block, to_block]`, the
+/// markets they touch and their resolutions, and computes its PnL.
+pub async fn fetch_wallet_pnl(
+    scanner: &Scanner,
+    wallet: Address,
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+) -> Result<WalletPnl> {
+    let policy = ChunkPolicy::starting_at(chunk_size);
+    let trades = backfill(from_block, to_block, &policy, true, |from, to| scanner.fetch_wallet_trades(from, to, wallet)).await?;
+    let ctf_events = backfill(from_block, to_block, &policy, true, |from, to| scanner.fetch_wallet_ctf_events(from, to, wallet)).await?;
+    let neg_risk_events = backfill(from_block, to_block, &policy, true, |from, to| scanner.fetch_wallet_neg_risk_events(from, to, wallet)).await?;
+
+    let owner = format_address(wallet);
+    let mut conditions: BTreeSet<H256> = BTreeSet::new();
+    let mut entries = Vec::new();
+    for trade in &trades {
+        let Some(entry) = trade_entry(trade, &owner) else { continue };
+        let condition_id = match &trade.condition_id {
+            Some(condition_id) => H256::from_str(condition_id).ok(),
+            None => match parse_token_id(&trade.token_id) {
+                Some(token) => scanner.fetch_token_condition(token, 0).await?,
+                None => None,
+            },
+        };
+        conditions.extend(condition_id);
+        entries.push(entry);
+    }
+    for event in &ctf_events {
+        if let Some(entry) = ctf_entry(event, &owner) {
+            conditions.extend(H256::from_str(&event.condition_id).ok());
+            entries.push(entry);
+        }
+    }
+    for event in &neg_risk_events {
+        if let Some(entry) = neg_risk_entry(event, &owner) {
+            let condition_id = match &event.kind {
+                NegRiskEventKind::PositionSplit { condition_id, .. }
+                | NegRiskEventKind::PositionsMerge { condition_id, .. }
+                | NegRiskEventKind::PayoutRedemption { condition_id, .. } => H256::from_str(condition_id).ok(),
+                _ => None,
+            };
+            conditions.extend(condition_id);
+            entries.push(entry);
+        }
+    }
+
+    let mut markets = Vec::new();
+    for condition_id in &conditions {
+        if let Some(market) = scanner.fetch_market_info_by_condition_id(*condition_id, None).await? {
+            markets.push(market);
+        }
+    }
+    let condition_ids: Vec<H256> = conditions.into_iter().collect();
+    let payouts = scanner.fetch_condition_payouts(&condition_ids, to_block).await?;
+
+    Ok(wallet_pnl(&owner, from_block, to_block, entries, &markets, &payouts))
+}
+
+fn add(a: SignedAmount, b: SignedAmount) -> SignedAmount {
+    SignedAmount::new(a.raw() + b.raw(), COLLATERAL_DECIMALS)
+}
+
+/// `amount` in `parts` near-equal shares; the remainder goes to the first.
+fn split_evenly(amount: U256, parts: usize) -> Vec<U256> {
+    split_weighted(amount, &vec![U256::one(); parts])
+}
+
+/// `amount` in proportion to `weights`; the rounding remainder goes to the largest weight.
+/// Evenly when every weight is zero, e.g. redeeming positions acquired before the range.
+fn split_weighted(amount: U256, weights: &[U256]) -> Vec<U256> {
+    let total = weights.iter().fold(U256::zero(), |sum, w| sum + w);
+    if total.is_zero() {
+        return if weights.is_empty() { Vec::new() } else { split_evenly(amount, weights.len()) };
+    }
+    let mut shares: Vec<U256> = weights.iter().map(|w| amount * w / total).collect();
+    let assigned = shares.iter().fold(U256::zero(), |sum, s| sum + s);
+    if let Some(largest) = (0..weights.len()).max_by_key(|&i| (weights[i], std::cmp::Reverse(i))) {
+        shares[largest] += amount - assigned;
+    }
+    shares
+}
+
+#[cfg(test)]
+#[path = "pnl_test.rs"]
+mod pnl_test;
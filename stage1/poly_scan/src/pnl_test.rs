@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::{MarketInfo, TradeOutput, TradeSide};
+    use crate::pnl::{trade_entry, wallet_pnl, PnlEntry, PnlEvent};
+    use ethers::types::U256;
+    use std::collections::HashMap;
+
+    const WALLET: &str = "0x1111111111111111111111111111111111111111";
+    const OTHER: &str = "0x2222222222222222222222222222222222222222";
+
+    fn market() -> (MarketInfo, U256, U256) {
+        (MarketInfo::fixture("0xa1", "0xa2"), U256::from(0xa1), U256::from(0xa2))
+    }
+
+    /// `maker` buys (or sells) `shares` of `token` for `usdc`, all in base units.
+    fn fill(maker: &str, taker: &str, side: TradeSide, token: U256, shares: u64, usdc: u64) -> TradeOutput {
+        let token_id = format!("0x{:x}", token);
+        let (maker_asset_id, taker_asset_id, maker_amount, taker_amount) = match side {
+            TradeSide::BUY => ("0".to_string(), token_id.clone(), usdc, shares),
+            _ => (token_id.clone(), "0".to_string(), shares, usdc),
+        };
+        TradeOutput {
+            maker: maker.to_string(),
+            taker: taker.to_string(),
+            maker_asset_id,
+            taker_asset_id,
+            maker_amount_filled: maker_amount.to_string(),
+            taker_amount_filled: taker_amount.to_string(),
+            ..TradeOutput::fixture(&token_id).with_side(side)
+        }
+    }
+
+    fn entry(block_number: u64, event: PnlEvent) -> PnlEntry {
+        PnlEntry { block_number, log_index: 0, event }
+    }
+
+    #[test]
+    fn test_trade_entry_takes_the_wallets_side() {
+        let (_, yes, no) = market();
+
+        // Maker buy: the fee comes out of the shares received
+        let mut buy = fill(WALLET, OTHER, TradeSide::BUY, yes, 1_000_000, 400_000);
+        buy.fee = "20000".to_string();
+        assert_eq!(trade_entry(&buy, WALLET).unwrap().event, PnlEvent::Buy { token: yes, shares: U256::from(980_000), cost: U256::from(400_000) });
+
+        // Direct fill taker: the other side of the maker's order
+        assert_eq!(
+            trade_entry(&fill(OTHER, WALLET, TradeSide::BUY, yes, 1_000_000, 400_000), WALLET).unwrap().event,
+            PnlEvent::Sell { token: yes, shares: U256::from(1_000_000), proceeds: U256::from(400_000) },
+        );
+
+        // As the taker of a match, its own taker-order fill carries the trade
+        let mut matched = fill(OTHER, WALLET, TradeSide::BUY, yes, 1_000_000, 400_000);
+        matched.match_log_index = Some(5);
+        assert_eq!(trade_entry(&matched, WALLET), None);
+
+        let mut swap = fill(WALLET, OTHER, TradeSide::BUY, yes, 1, 1);
+        swap.maker_asset_id = format!("0x{:x}", no);
+        assert_eq!(trade_entry(&swap, WALLET), None);
+        assert_eq!(trade_entry(&buy, "0x3333333333333333333333333333333333333333"), None);
+    }
+
+    #[test]
+    fn test_average_cost_realizes_sales_and_marks_the_rest() {
+        let (market, yes, _) = market();
+        let entries = vec![
+            // Out of order on purpose: the sale comes after both buys
+            entry(120, PnlEvent::Sell { token: yes, shares: U256::from(4_000_000), proceeds: U256::from(2_400_000) }),
+            entry(100, PnlEvent::Buy { token: yes, shares: U256::from(5_000_000), cost: U256::from(1_500_000) }),
+            entry(110, PnlEvent::Buy { token: yes, shares: U256::from(5_000_000), cost: U256::from(2_500_000) }),
+        ];
+        let report = wallet_pnl(WALLET, 100, 200, entries, std::slice::from_ref(&market), &HashMap::new());
+
+        assert_eq!(report.events, 3);
+        assert_eq!(report.markets.len(), 1);
+        let pnl = &report.markets[0];
+        assert_eq!(pnl.condition_id.as_deref(), Some(market.condition_id.as_str()));
+        assert!(!pnl.resolved);
+
+        // 10 @ 0.40 average; selling 4 @ 0.60 realizes 0.80, the other 6 are marked at 0.60
+        let position = &pnl.positions[0];
+        assert_eq!(position.outcome.as_deref(), Some("YES"));
+        assert_eq!(position.shares.to_trimmed_string(), "6.0");
+        assert_eq!(position.avg_price.unwrap().to_trimmed_string(), "0.4");
+        assert_eq!(position.mark_price.unwrap().to_trimmed_string(), "0.6");
+        assert_eq!(position.realized.to_trimmed_string(), "0.8");
+        assert_eq!(position.unrealized.unwrap().to_trimmed_string(), "1.2");
+        assert_eq!(report.total.to_trimmed_string(), "2.0");
+    }
+
+    #[test]
+    fn test_splits_merges_and_redemptions_settle_at_the_payout() {
+        let (market, yes, no) = market();
+        let entries = vec![
+            entry(100, PnlEvent::Split { tokens: vec![yes, no], amount: U256::from(10_000_000) }),
+            entry(110, PnlEvent::Merge { tokens: vec![yes, no], amount: U256::from(2_000_000) }),
+            entry(120, PnlEvent::Sell { token: no, shares: U256::from(8_000_000), proceeds: U256::from(2_400_000) }),
+            // Redeems half the YES holding; the rest settles at the payout without being redeemed
+            entry(300, PnlEvent::Redeem { tokens: vec![yes], amounts: Some(vec![U256::from(4_000_000)]), payout: U256::from(4_000_000) }),
+        ];
+        let payouts = HashMap::from([(market.condition_id.clone(), vec![U256::one(), U256::zero()])]);
+        let report = wallet_pnl(WALLET, 100, 400, entries, &[market], &payouts);
+
+        let pnl = &report.markets[0];
+        assert!(pnl.resolved);
+        let by_outcome = |label: &str| pnl.positions.iter().find(|p| p.outcome.as_deref() == Some(label)).unwrap();
+
+        // Split at 0.50 each; the merge is break-even; NO sold 8 @ 0.30 loses 1.60
+        assert_eq!(by_outcome("NO").realized.to_trimmed_string(), "-1.6");
+        // YES: 8 at 0.50 cost pay out 1.00 each
+        let yes_pnl = by_outcome("YES");
+        assert_eq!(yes_pnl.realized.to_trimmed_string(), "4.0");
+        assert_eq!(yes_pnl.mark_price.unwrap().to_trimmed_string(), "1.0");
+        assert_eq!(yes_pnl.unrealized, None);
+        assert_eq!(report.realized.to_trimmed_string(), "2.4");
+        assert_eq!(report.unrealized.to_trimmed_string(), "0.0");
+    }
+
+    #[test]
+    fn test_redeeming_positions_from_before_the_range_is_pure_profit() {
+        let (market, yes, no) = market();
+        let unknown = U256::from(0xabcd);
+        let entries = vec![
+            entry(100, PnlEvent::Redeem { tokens: vec![yes, no], amounts: None, payout: U256::from(3_000_000) }),
+            entry(110, PnlEvent::Buy { token: unknown, shares: U256::from(1_000_000), cost: U256::from(500_000) }),
+        ];
+        let report = wallet_pnl(WALLET, 100, 200, entries, &[market], &HashMap::new());
+
+        assert_eq!(report.markets[0].realized.to_trimmed_string(), "3.0");
+        // Positions with no known market are grouped last
+        let unknown_market = report.markets.last().unwrap();
+        assert_eq!(unknown_market.condition_id, None);
+        assert_eq!(unknown_market.positions[0].unrealized.unwrap().to_trimmed_string(), "0.0");
+        assert_eq!(report.total.to_trimmed_string(), "3.0");
+    }
+}
@@ -0,0 +1,108 @@
+use crate::amount::Amount;
+use crate::assets::PositionTransfer;
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::consts::COLLATERAL_DECIMALS;
+use crate::models::{MarketInfo, PositionBalance};
+use crate::scanner::Scanner;
+use crate::utils::format_address;
+use anyhow::Result;
+use ethers::types::{Address, I256, U256};
+use std::collections::HashMap;
+
+/// Replays `[from_block, to_block]` in adaptive chunks. With a `holder`, only its transfers are
+/// fetched, which is enough for its own balances.
+pub async fn fetch_ledger(
+    scanner: &Scanner,
+    from_block: u64,
+    to_block: u64,
+    holder: Option<Address>,
+    chunk_size: u64,
+) -> Result<PositionLedger> {
+    let transfers = backfill(from_block, to_block, &ChunkPolicy::starting_at(chunk_size), true, |from, to| {
+        scanner.fetch_position_transfers(from, to, holder)
+    }).await?;
+    Ok(PositionLedger::new(transfers))
+}
+
+/// Position balances replayed from CTF ERC-1155 transfers, queryable at any block of the scan.
+///
+/// Balances are net flows since the first scanned block: an address that acquired a position
+/// before the range can go negative when it moves it. `incomplete` counts those, and queries
+/// only report positive balances.
+#[derive(Debug, Default)]
+pub struct PositionLedger {
+    /// Sorted by (block, log index); mints come from and burns go to the zero address
+    transfers: Vec<PositionTransfer>,
+}
+
+impl PositionLedger {
+    pub fn new(mut transfers: Vec<PositionTransfer>) -> Self {
+        transfers.sort_by_key(|t| (t.block_number, t.log_index));
+        Self { transfers }
+    }
+
+    pub fn transfer_count(&self) -> usize {
+        self.transfers.len()
+    }
+
+    /// Every non-zero (holder, position) balance after `block`.
+    pub fn balances_at(&self, block: u64) -> HashMap<(Address, U256), I256> {
+        let mut balances: HashMap<(Address, U256), I256> = HashMap::new();
+        for transfer in self.transfers.iter().take_while(|t| t.block_number <= block) {
+            let value = I256::from_raw(transfer.value);
+            if !transfer.from.is_zero() {
+                *balances.entry((transfer.from, transfer.id)).or_default() -= value;
+            }
+            if !transfer.to.is_zero() {
+                *balances.entry((transfer.to, transfer.id)).or_default() += value;
+            }
+        }
+        balances.retain(|_, balance| !balance.is_zero());
+        balances
+    }
+
+    /// Addresses holding `token_id` after `block`, largest first.
+    pub fn holders(&self, token_id: U256, block: u64) -> Vec<PositionBalance> {
+        self.positive(block, |_, id| id == token_id)
+    }
+
+    /// Positions `holder` has after `block`, largest first.
+    pub fn holdings(&self, holder: Address, block: u64) -> Vec<PositionBalance> {
+        self.positive(block, |address, _| address == holder)
+    }
+
+    /// Balances that went negative by `block`: the scan started after those positions were acquired.
+    pub fn incomplete(&self, block: u64) -> usize {
+        self.balances_at(block).values().filter(|b| b.is_negative()).count()
+    }
+
+    fn positive(&self, block: u64, keep: impl Fn(Address, U256) -> bool) -> Vec<PositionBalance> {
+        let mut balances: Vec<(Address, U256, U256)> = self.balances_at(block).into_iter()
+            .filter(|((address, id), balance)| balance.is_positive() && keep(*address, *id))
+            .map(|((address, id), balance)| (address, id, balance.into_raw()))
+            .collect();
+        balances.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+
+        balances.into_iter()
+            .map(|(address, id, balance)| PositionBalance {
+                holder: format_address(address),
+                token_id: format!("0x{:x}", id),
+                outcome: None,
+                balance: Amount::new(balance, COLLATERAL_DECIMALS),
+            })
+            .collect()
+    }
+}
+
+/// Names the outcome of each balance whose position belongs to `market`.
+pub fn label_outcomes(balances: &mut [PositionBalance], market: &MarketInfo) {
+    for balance in balances {
+        if let Some(outcome) = market.outcome_label(&balance.token_id) {
+            balance.outcome = Some(outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "positions_test.rs"]
+mod positions_test;
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use crate::assets::PositionTransfer;
+    use crate::positions::{label_outcomes, PositionLedger};
+    use crate::utils::{derive_market_info, parse_token_id};
+    use ethers::types::{Address, H256, U256};
+
+    const YES: u64 = 0xabcd;
+    const NO: u64 = 0xabce;
+
+    fn transfer(block_number: u64, from: u8, to: u8, id: u64, value: u64) -> PositionTransfer {
+        PositionTransfer {
+            from: Address::repeat_byte(from),
+            to: Address::repeat_byte(to),
+            id: U256::from(id),
+            value: U256::from(value),
+            block_number,
+            log_index: 0,
+        }
+    }
+
+    /// Alice (0x0a) splits 10 into YES+NO and sells 4 YES to Bob (0x0b), who redeems 1.
+    fn ledger() -> PositionLedger {
+        PositionLedger::new(vec![
+            // Out of order on purpose: the ledger sorts by block
+            transfer(120, 0x0a, 0x0b, YES, 4_000_000),
+            transfer(100, 0x00, 0x0a, YES, 10_000_000),
+            transfer(100, 0x00, 0x0a, NO, 10_000_000),
+            transfer(150, 0x0b, 0x00, YES, 1_000_000),
+        ])
+    }
+
+    fn balances(rows: &[crate::models::PositionBalance]) -> Vec<(String, String)> {
+        rows.iter().map(|b| (b.holder.clone(), b.balance.to_trimmed_string())).collect()
+    }
+
+    #[test]
+    fn test_holders_and_holdings_at_any_block() {
+        let ledger = ledger();
+        let alice = format!("{:?}", Address::repeat_byte(0x0a));
+        let bob = format!("{:?}", Address::repeat_byte(0x0b));
+
+        assert!(ledger.holders(U256::from(YES), 99).is_empty());
+        assert_eq!(balances(&ledger.holders(U256::from(YES), 100)), vec![(alice.clone(), "10.0".to_string())]);
+        assert_eq!(
+            balances(&ledger.holders(U256::from(YES), 120)),
+            vec![(alice.clone(), "6.0".to_string()), (bob.clone(), "4.0".to_string())],
+        );
+        // Burns leave circulation: nobody is credited with the zero address' balance
+        assert_eq!(balances(&ledger.holders(U256::from(YES), 150))[1], (bob, "3.0".to_string()));
+
+        let held = ledger.holdings(Address::repeat_byte(0x0a), 150);
+        let tokens: Vec<&str> = held.iter().map(|b| b.token_id.as_str()).collect();
+        assert_eq!(tokens, vec!["0xabce", "0xabcd"]);
+        assert_eq!(ledger.incomplete(150), 0);
+    }
+
+    #[test]
+    fn test_scan_starting_late_is_flagged_incomplete() {
+        // Carol (0x0c) received YES before the scanned range and sends it on inside it
+        let ledger = PositionLedger::new(vec![transfer(200, 0x0c, 0x0d, YES, 5)]);
+        assert_eq!(ledger.holdings(Address::repeat_byte(0x0c), 200), Vec::new());
+        assert_eq!(ledger.holders(U256::from(YES), 200).len(), 1);
+        assert_eq!(ledger.incomplete(200), 1);
+        assert_eq!(ledger.incomplete(199), 0);
+    }
+
+    #[test]
+    fn test_label_outcomes_from_market_positions() {
+        let market = derive_market_info(H256::repeat_byte(0xc0), H256::repeat_byte(0x0b), Address::repeat_byte(0x01), 2, false).unwrap();
+        let no = parse_token_id(market.no_token_id().unwrap()).unwrap();
+        let ledger = PositionLedger::new(vec![
+            PositionTransfer { id: no, ..transfer(100, 0x00, 0x0a, 0, 1) },
+            transfer(100, 0x00, 0x0a, YES, 1),
+        ]);
+
+        let mut held = ledger.holdings(Address::repeat_byte(0x0a), 100);
+        label_outcomes(&mut held, &market);
+        let outcomes: Vec<Option<&str>> = held.iter().map(|b| b.outcome.as_deref()).collect();
+        assert!(outcomes.contains(&Some("NO")));
+        assert!(outcomes.contains(&None));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::consts::*;
+use crate::models::{ExchangeVenue, RegisteredToken, TradeOutput};
+use crate::utils::{canonical_token_id, format_address, log_topics, outcome_position, parse_token_id};
+use anyhow::Result;
+use ethers::types::{Address, Log, H256, U256};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Decodes an exchange `TokenRegistered(token0, token1, conditionId)` log (all three indexed).
+/// `venue` is the emitting exchange's, which fixes the collateral the outcome index is derived with.
+pub fn decode_token_registered(log: &Log, venue: ExchangeVenue) -> Result<RegisteredToken> {
+    let topics = log_topics(log, 3)?;
+    let token = U256::from_big_endian(topics[0].as_bytes());
+    let complement = U256::from_big_endian(topics[1].as_bytes());
+    let condition_id = topics[2];
+
+    Ok(RegisteredToken {
+        token_id: format!("0x{:x}", token),
+        complement_id: format!("0x{:x}", complement),
+        condition_id: format!("{:?}", condition_id),
+        outcome_index: outcome_index(venue, condition_id, token),
+        exchange: format_address(log.address),
+        venue,
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+    })
+}
+
+/// Slot of `token` in the binary condition, if it is one of the two positions the venue's
+/// collateral derives (USDC on the binary exchange, wrapped USDC on the neg-risk one).
+fn outcome_index(venue: ExchangeVenue, condition_id: H256, token: U256) -> Option<u32> {
+    let collateral = match venue {
+        ExchangeVenue::Binary => USDC_ADDRESS,
+        ExchangeVenue::NegRisk => NEG_RISK_WRAPPED_COLLATERAL_ADDRESS,
+    };
+    let collateral = Address::from_str(collateral).ok()?;
+    (0..2u32).find(|slot| {
+        let position = outcome_position(collateral, condition_id, 1 << slot);
+        parse_token_id(&position.position_id) == Some(token)
+    })
+}
+
+/// Token ID -> condition and complement, built from `TokenRegistered` logs.
+#[derive(Debug, Default, Clone)]
+pub struct TokenRegistry {
+    /// Keyed by canonical `0x{:x}` token ID
+    tokens: HashMap<String, RegisteredToken>,
+}
+
+impl TokenRegistry {
+    /// Adds `token` and, unless already registered, its complement: the exchanges emit one log
+    /// per direction, but a scan starting between the two should still know both.
+    pub fn insert(&mut self, token: RegisteredToken) {
+        let mirror = RegisteredToken {
+            token_id: token.complement_id.clone(),
+            complement_id: token.token_id.clone(),
+            outcome_index: token.outcome_index.map(|slot| 1 - slot),
+            ..token.clone()
+        };
+        self.tokens.entry(mirror.token_id.clone()).or_insert(mirror);
+        self.tokens.insert(token.token_id.clone(), token);
+    }
+
+    /// Looks up a token ID in hex or decimal.
+    pub fn get(&self, token_id: &str) -> Option<&RegisteredToken> {
+        self.tokens.get(&canonical_token_id(token_id)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Forgets tokens registered after `block`, e.g. in blocks a reorg orphaned.
+    pub fn remove_after(&mut self, block: u64) {
+        self.tokens.retain(|_, token| token.block_number <= block);
+    }
+
+    /// Fills the trade's condition, complement and outcome index from its traded token.
+    pub fn label(&self, trade: &mut TradeOutput) {
+        if let Some(registered) = self.get(&trade.token_id) {
+            trade.condition_id = Some(registered.condition_id.clone());
+            trade.complement_token_id = Some(registered.complement_id.clone());
+            trade.outcome_index = registered.outcome_index;
+            trade.label_actions();
+        }
+    }
+}
+
+impl Extend<RegisteredToken> for TokenRegistry {
+    fn extend<I: IntoIterator<Item = RegisteredToken>>(&mut self, tokens: I) {
+        for token in tokens {
+            self.insert(token);
+        }
+    }
+}
+
+impl FromIterator<RegisteredToken> for TokenRegistry {
+    fn from_iter<I: IntoIterator<Item = RegisteredToken>>(tokens: I) -> Self {
+        let mut registry = Self::default();
+        registry.extend(tokens);
+        registry
+    }
+}
+
+#[cfg(test)]
+#[path = "registry_test.rs"]
+mod registry_test;
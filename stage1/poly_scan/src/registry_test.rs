@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::*;
+    use crate::models::{ExchangeVenue, TradeOutput};
+    use crate::registry::{decode_token_registered, TokenRegistry};
+    use crate::utils::{outcome_position, parse_token_id};
+    use ethers::types::{Address, Log, H256, U256, U64};
+    use ethers::utils::keccak256;
+    use std::str::FromStr;
+
+    /// Position IDs of both outcomes of `condition_id` with `collateral`.
+    fn positions(collateral: &str, condition_id: H256) -> (U256, U256) {
+        let collateral = Address::from_str(collateral).unwrap();
+        let id = |index_set| parse_token_id(&outcome_position(collateral, condition_id, index_set).position_id).unwrap();
+        (id(1), id(2))
+    }
+
+    fn registered_log(exchange: &str, token0: U256, token1: U256, condition_id: H256) -> Log {
+        let topic = |value: U256| {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            H256::from(bytes)
+        };
+        Log {
+            address: Address::from_str(exchange).unwrap(),
+            topics: vec![H256::from(keccak256(TOKEN_REGISTERED_EVENT_SIGNATURE)), topic(token0), topic(token1), condition_id],
+            block_number: Some(U64::from(300u64)),
+            ..Default::default()
+        }
+    }
+
+    fn fill(token_id: &str) -> TradeOutput {
+        TradeOutput::fixture(token_id).at_block(400).filled(500_000, 1_000_000)
+    }
+
+    #[test]
+    fn test_decode_derives_outcome_index_per_venue() {
+        let condition_id = H256::repeat_byte(0xc1);
+        let (yes, no) = positions(USDC_ADDRESS, condition_id);
+
+        // Registered NO-first: the index comes from derivation, not log order
+        let token = decode_token_registered(&registered_log(EXCHANGE_PROXY_ADDRESS, no, yes, condition_id), ExchangeVenue::Binary).unwrap();
+        assert_eq!(token.token_id, format!("0x{:x}", no));
+        assert_eq!(token.complement_id, format!("0x{:x}", yes));
+        assert_eq!(token.condition_id, format!("{:?}", condition_id));
+        assert_eq!(token.outcome_index, Some(1));
+        assert_eq!(token.block_number, 300);
+
+        // Neg-risk tokens are positions over wrapped collateral
+        let (neg_yes, neg_no) = positions(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, condition_id);
+        let neg = decode_token_registered(&registered_log(NEG_RISK_EXCHANGE_ADDRESS, neg_yes, neg_no, condition_id), ExchangeVenue::NegRisk).unwrap();
+        assert_eq!(neg.outcome_index, Some(0));
+        // ...so USDC positions on the neg-risk exchange don't derive
+        let wrong = decode_token_registered(&registered_log(NEG_RISK_EXCHANGE_ADDRESS, yes, no, condition_id), ExchangeVenue::NegRisk).unwrap();
+        assert_eq!(wrong.outcome_index, None);
+    }
+
+    #[test]
+    fn test_registry_knows_complements_and_labels_fills() {
+        let condition_id = H256::repeat_byte(0xc2);
+        let (yes, no) = positions(USDC_ADDRESS, condition_id);
+        let token = decode_token_registered(&registered_log(EXCHANGE_PROXY_ADDRESS, yes, no, condition_id), ExchangeVenue::Binary).unwrap();
+        let registry: TokenRegistry = [token].into_iter().collect();
+
+        // One log in the scanned range is enough for both directions
+        assert_eq!(registry.len(), 2);
+        let complement = registry.get(&no.to_string()).unwrap();
+        assert_eq!(complement.complement_id, format!("0x{:x}", yes));
+        assert_eq!(complement.outcome_index, Some(1));
+
+        let mut trade = fill(&format!("0x{:x}", no));
+        registry.label(&mut trade);
+        assert_eq!(trade.condition_id, Some(format!("{:?}", condition_id)));
+        assert_eq!(trade.complement_token_id, Some(format!("0x{:x}", yes)));
+        assert_eq!(trade.outcome_index, Some(1));
+
+        let mut unknown = fill("0xabcd");
+        registry.label(&mut unknown);
+        assert_eq!(unknown.condition_id, None);
+    }
+
+    #[test]
+    fn test_registry_forgets_rolled_back_registrations() {
+        let condition_id = H256::repeat_byte(0xc3);
+        let (yes, no) = positions(USDC_ADDRESS, condition_id);
+        let token = decode_token_registered(&registered_log(EXCHANGE_PROXY_ADDRESS, yes, no, condition_id), ExchangeVenue::Binary).unwrap();
+        let mut registry: TokenRegistry = [token].into_iter().collect();
+
+        // Registered at block 300: kept by a rollback to it, dropped with its complement below it
+        registry.remove_after(300);
+        assert_eq!(registry.len(), 2);
+        registry.remove_after(299);
+        assert_eq!(registry.len(), 0);
+        let mut trade = fill(&format!("0x{:x}", yes));
+        registry.label(&mut trade);
+        assert_eq!(trade.condition_id, None);
+    }
+}
@@ -1,48 +1,366 @@
+use crate::amount::{Amount, DEFAULT_PRICE_SCALE};
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::assets::{position_transfers, verify_fill, FillAssets, PositionTransfer};
 use crate::consts::*;
-use crate::models::{TradeOutput, TradeSide, MarketInfo};
-use crate::utils::{calculate_price, format_address, u256_to_string, get_condition_id, get_collection_id, get_position_id};
+use crate::ctf::{ctf_event_topics, decode_ctf_log};
+use crate::matches::{group_matches, link_fills};
+use crate::neg_risk::{decode_neg_risk_log, group_markets, neg_risk_event_topics};
+use crate::registry::{decode_token_registered, TokenRegistry};
+use crate::timestamps::{log_timestamps, TimestampCache, TimestampedLog, TIMESTAMP_BATCH};
+use crate::uma::{adapter_event_topics, apply_question, decode_question_initialized, decode_uma_log, is_uma_adapter, optimistic_oracle_addresses, oracle_event_topics, resolutions, uma_adapter_addresses};
+use crate::models::{TradeAction, TradeOutput, TradeSide, MarketInfo, ExchangeVenue, StreamEvent, OrdersMatchedEvent, MatchOutput, CtfEvent, CtfEventKind, NegRiskEvent, NegRiskMarket, RegisteredToken, UmaResolution};
+use crate::utils::{calculate_fee_bps, calculate_price, derive_market_info, format_address, u256_to_string, get_condition_id};
 use anyhow::Result;
 use ethers::prelude::*;
 use ethers::utils::keccak256;
-use std::collections::HashMap;
+use futures_util::future::try_join_all;
+use futures_util::stream;
+use std::collections::{BTreeSet, HashMap};
 use std::str::FromStr;
+use std::sync::RwLock;
+
+/// Transaction receipts requested concurrently when checking fills against their transfers.
+const RECEIPT_CONCURRENCY: usize = 16;
+
+/// Scanner over any JSON-RPC transport. Defaults to HTTP polling; `Scanner<Ws>` adds live subscriptions.
+pub struct Scanner<P = Http> {
+    provider: Provider<P>,
+    exchanges: HashMap<Address, ExchangeVenue>,
+    price_scale: u32,
+    /// Labels fills with their condition; grows as `TokenRegistered` logs are scanned
+    registry: RwLock<TokenRegistry>,
+    /// Block timestamps resolved so far (optionally persisted across runs)
+    timestamps: TimestampCache,
+}
 
-pub struct Scanner {
-    provider: Provider<Http>,
-    exchange_address: Address,
+/// Both Polymarket exchanges (binary + neg-risk).
+pub fn default_exchanges() -> Result<HashMap<Address, ExchangeVenue>> {
+    Ok(HashMap::from([
+        (Address::from_str(EXCHANGE_PROXY_ADDRESS)?, ExchangeVenue::Binary),
+        (Address::from_str(NEG_RISK_EXCHANGE_ADDRESS)?, ExchangeVenue::NegRisk),
+    ]))
 }
 
-impl Scanner {
+impl Scanner<Http> {
+    /// Scanner watching both Polymarket exchanges (binary + neg-risk).
     pub fn new(rpc_url: &str) -> Result<Self> {
+        Self::with_exchanges(rpc_url, default_exchanges()?)
+    }
+
+    /// Scanner watching a custom set of exchange contracts, each tagged with its venue.
+    pub fn with_exchanges(rpc_url: &str, exchanges: HashMap<Address, ExchangeVenue>) -> Result<Self> {
         let provider = Provider::<Http>::try_from(rpc_url)?;
-        let exchange_address = Address::from_str(EXCHANGE_PROXY_ADDRESS)?;
+        Self::from_provider(provider, exchanges)
+    }
+}
+
+impl Scanner<Ws> {
+    /// Opens a WebSocket connection (needed for `watch`) watching both exchanges.
+    pub async fn connect_ws(ws_url: &str) -> Result<Self> {
+        let provider = Provider::<Ws>::connect(ws_url).await?;
+        Self::from_provider(provider, default_exchanges()?)
+    }
+}
+
+impl<P: JsonRpcClient> Scanner<P> {
+    pub fn from_provider(provider: Provider<P>, exchanges: HashMap<Address, ExchangeVenue>) -> Result<Self> {
+        if exchanges.is_empty() {
+            return Err(anyhow::anyhow!("At least one exchange address is required"));
+        }
         Ok(Self {
             provider,
-            exchange_address,
+            exchanges,
+            price_scale: DEFAULT_PRICE_SCALE,
+            registry: RwLock::new(TokenRegistry::default()),
+            timestamps: TimestampCache::in_memory(),
         })
     }
 
+    /// Decimal places kept when dividing amounts into prices (truncated beyond).
+    pub fn with_price_scale(mut self, price_scale: u32) -> Self {
+        self.price_scale = price_scale;
+        self
+    }
+
+    /// Resolves block timestamps through `cache` (e.g. one persisted on disk) instead of a
+    /// per-scanner in-memory map.
+    pub fn with_timestamp_cache(mut self, cache: TimestampCache) -> Self {
+        self.timestamps = cache;
+        self
+    }
+
+    /// Handle on the scanner's timestamp cache, to share it with other scanners.
+    pub fn timestamp_cache(&self) -> TimestampCache {
+        self.timestamps.clone()
+    }
+
+    /// Adds registrations (e.g. loaded from the index database) used to label later fills.
+    pub fn register_tokens(&self, tokens: impl IntoIterator<Item = RegisteredToken>) {
+        self.registry.write().unwrap_or_else(|poisoned| poisoned.into_inner()).extend(tokens);
+    }
+
+    /// Drops registrations from blocks after `block`, once a reorg rolled them back.
+    pub fn unregister_tokens_after(&self, block: u64) {
+        self.registry.write().unwrap_or_else(|poisoned| poisoned.into_inner()).remove_after(block);
+    }
+
+    pub fn registered_token(&self, token_id: &str) -> Option<RegisteredToken> {
+        self.registry.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(token_id).cloned()
+    }
+
+    pub fn registered_token_count(&self) -> usize {
+        self.registry.read().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+    }
+
+    fn exchange_addresses(&self) -> Vec<Address> {
+        self.exchanges.keys().copied().collect()
+    }
+
+    pub async fn latest_block(&self) -> Result<u64> {
+        Ok(self.provider.get_block_number().await?.as_u64())
+    }
+
+    /// Canonical hash of `block_number`, None if the node doesn't have that block (yet).
+    pub async fn block_hash(&self, block_number: u64) -> Result<Option<H256>> {
+        let block = self.provider.get_block(block_number).await?;
+        Ok(block.and_then(|b| b.hash))
+    }
+
+    /// Unix timestamp of each distinct block in `blocks` (blocks the node doesn't have are left out).
+    /// Cached blocks are served from the timestamp cache; the rest are fetched `TIMESTAMP_BATCH`
+    /// headers at a time and cached.
+    pub async fn block_timestamps(&self, blocks: impl IntoIterator<Item = u64>) -> Result<HashMap<u64, u64>> {
+        let unique: BTreeSet<u64> = blocks.into_iter().collect();
+        let mut timestamps = self.timestamps.get_many(&unique)?;
+        let missing: Vec<u64> = unique.into_iter().filter(|b| !timestamps.contains_key(b)).collect();
+
+        for batch in missing.chunks(TIMESTAMP_BATCH) {
+            let headers = try_join_all(batch.iter().map(|&block_number| self.provider.get_block(block_number))).await?;
+            let fetched: HashMap<u64, u64> = batch.iter()
+                .zip(headers)
+                .filter_map(|(&block_number, block)| block.map(|b| (block_number, b.timestamp.as_u64())))
+                .collect();
+            self.timestamps.insert_many(&fetched)?;
+            timestamps.extend(fetched);
+        }
+        Ok(timestamps)
+    }
+
+    /// `eth_getLogs`, caching the `blockTimestamp` of logs whose node returns one.
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let logs: Vec<TimestampedLog> = self.provider.request("eth_getLogs", [filter]).await?;
+        self.timestamps.insert_many(&log_timestamps(&logs))?;
+        Ok(logs.into_iter().map(|l| l.log).collect())
+    }
+
+    /// `eth_getLogs` for `filter` over `[from_block, to_block]` (default: latest), split by
+    /// `backfill` with `policy`.
+    async fn get_logs_in(&self, filter: &Filter, from_block: u64, to_block: Option<u64>, policy: &ChunkPolicy) -> Result<Vec<Log>> {
+        let to_block = match to_block {
+            Some(b) => b,
+            None => self.latest_block().await?,
+        };
+        backfill(from_block, to_block, policy, false, |from, to| {
+            let chunk = filter.clone().from_block(from).to_block(to);
+            async move { self.get_logs(&chunk).await }
+        }).await
+    }
+
+    /// Sets each market's `timestamp` from its block.
+    async fn stamp_markets(&self, markets: &mut [MarketInfo]) -> Result<()> {
+        let timestamps = self.block_timestamps(markets.iter().filter_map(|m| m.block_number)).await?;
+        for market in markets.iter_mut() {
+            market.timestamp = market.block_number.and_then(|b| timestamps.get(&b).copied());
+        }
+        Ok(())
+    }
+
     pub async fn fetch_events(&self, from_block: u64, to_block: u64) -> Result<Vec<TradeOutput>> {
+        let (trades, _) = self.fetch_exchange_logs(from_block, to_block).await?;
+        Ok(trades)
+    }
+
+    /// Fills in the range grouped by their parent `OrdersMatched`.
+    pub async fn fetch_matches(&self, from_block: u64, to_block: u64) -> Result<Vec<MatchOutput>> {
+        let (trades, matched) = self.fetch_exchange_logs(from_block, to_block).await?;
+        Ok(group_matches(trades, matched, self.price_scale))
+    }
+
+    pub async fn fetch_tx_events(&self, tx_hash: H256) -> Result<Vec<TradeOutput>> {
+        let (trades, _) = self.fetch_tx_exchange_logs(tx_hash).await?;
+        Ok(trades)
+    }
+
+    pub async fn fetch_tx_matches(&self, tx_hash: H256) -> Result<Vec<MatchOutput>> {
+        let (trades, matched) = self.fetch_tx_exchange_logs(tx_hash).await?;
+        Ok(group_matches(trades, matched, self.price_scale))
+    }
+
+    /// Fills on any exchange where either leg is one of `token_ids`. Token IDs are not indexed,
+    /// so logs are filtered on their raw data before any decoding round-trips.
+    pub async fn fetch_token_trades(&self, from_block: u64, to_block: u64, token_ids: &[U256]) -> Result<Vec<TradeOutput>> {
         let filter = Filter::new()
-            .address(self.exchange_address)
+            .address(self.exchange_addresses())
             .event(ORDER_FILLED_EVENT_SIGNATURE)
             .from_block(from_block)
             .to_block(to_block);
 
-        let logs = self.provider.get_logs(&filter).await?;
+        let logs: Vec<Log> = self.get_logs(&filter).await?
+            .into_iter()
+            .filter(|log| {
+                log.data.len() >= 64 && {
+                    let maker_asset_id = U256::from_big_endian(&log.data[0..32]);
+                    let taker_asset_id = U256::from_big_endian(&log.data[32..64]);
+                    token_ids.contains(&maker_asset_id) || token_ids.contains(&taker_asset_id)
+                }
+            })
+            .collect();
+
         self.process_logs(logs).await
     }
 
-    pub async fn fetch_tx_events(&self, tx_hash: H256) -> Result<Vec<TradeOutput>> {
+    /// Fills where `wallet` is an order's maker or the taker of a direct fill, linked to their
+    /// matches. Maker fills whose taker is `wallet` are mirrored by its own taker-order fill.
+    pub async fn fetch_wallet_trades(&self, from_block: u64, to_block: u64, wallet: Address) -> Result<Vec<TradeOutput>> {
+        let order_filled_sig = H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE));
+        let base = Filter::new()
+            .address(self.exchange_addresses())
+            .from_block(from_block)
+            .to_block(to_block);
+
+        // topic2 is the order maker of OrderFilled and the taker order's maker of OrdersMatched
+        let mut logs = self.get_logs(&base.clone()
+            .topic0(vec![order_filled_sig, H256::from(keccak256(ORDERS_MATCHED_EVENT_SIGNATURE))])
+            .topic2(H256::from(wallet))).await?;
+        logs.extend(self.get_logs(&base.topic0(order_filled_sig).topic3(H256::from(wallet))).await?);
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+        logs.dedup_by_key(|log| (log.block_number, log.log_index));
+
+        let (trades, _) = self.decode_exchange_logs(logs).await?;
+        Ok(trades)
+    }
+
+    /// `TokenRegistered` logs of every watched exchange in the range, in chain order. They are
+    /// also added to the registry, so fills fetched afterwards carry their condition.
+    pub async fn fetch_token_registrations(&self, from_block: u64, to_block: u64) -> Result<Vec<RegisteredToken>> {
+        let filter = Filter::new()
+            .address(self.exchange_addresses())
+            .event(TOKEN_REGISTERED_EVENT_SIGNATURE)
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let mut logs = self.get_logs(&filter).await?;
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut tokens = Vec::new();
+        for log in &logs {
+            let Some(&venue) = self.exchanges.get(&log.address) else { continue };
+            tokens.push(decode_token_registered(log, venue)?);
+        }
+        self.register_tokens(tokens.clone());
+        Ok(tokens)
+    }
+
+    /// CTF position transfers (mints, burns and moves) in the range, in chain order. With a
+    /// `holder`, only transfers from or to it: one query per indexed side, since ERC-1155
+    /// position IDs are not indexed.
+    pub async fn fetch_position_transfers(&self, from_block: u64, to_block: u64, holder: Option<Address>) -> Result<Vec<PositionTransfer>> {
+        let base = Filter::new()
+            .address(Address::from_str(CTF_ADDRESS)?)
+            .topic0(vec![
+                H256::from(keccak256(TRANSFER_SINGLE_EVENT_SIGNATURE)),
+                H256::from(keccak256(TRANSFER_BATCH_EVENT_SIGNATURE)),
+            ])
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let mut logs = match holder {
+            None => self.get_logs(&base).await?,
+            Some(holder) => {
+                let mut logs = self.get_logs(&base.clone().topic2(H256::from(holder))).await?;
+                logs.extend(self.get_logs(&base.topic3(H256::from(holder))).await?);
+                logs
+            }
+        };
+        // A self-transfer matches both queries
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+        logs.dedup_by_key(|log| (log.block_number, log.log_index));
+
+        position_transfers(&logs)
+    }
+
+    /// Condition `token_id` was registered under on any watched exchange: from the registry when
+    /// known, else from a `TokenRegistered` lookup (topic1 = token).
+    pub async fn fetch_token_condition(&self, token_id: U256, from_block: u64) -> Result<Option<H256>> {
+        if let Some(registered) = self.registered_token(&token_id.to_string()) {
+            return Ok(H256::from_str(&registered.condition_id).ok());
+        }
+
+        let mut topic = [0u8; 32];
+        token_id.to_big_endian(&mut topic);
+        let filter = Filter::new()
+            .address(self.exchange_addresses())
+            .event(TOKEN_REGISTERED_EVENT_SIGNATURE)
+            .topic1(H256::from(topic));
+
+        let logs = self.get_logs_in(&filter, from_block, None, &ChunkPolicy::sparse()).await?;
+        let Some(log) = logs.first() else { return Ok(None) };
+        let Some(&venue) = self.exchanges.get(&log.address) else { return Ok(None) };
+        let registered = decode_token_registered(log, venue)?;
+        let condition_id = H256::from_str(&registered.condition_id).ok();
+        self.register_tokens([registered]);
+        Ok(condition_id)
+    }
+
+    async fn fetch_exchange_logs(&self, from_block: u64, to_block: u64) -> Result<(Vec<TradeOutput>, Vec<OrdersMatchedEvent>)> {
+        // One eth_getLogs call covering every exchange we know about, both event types
+        let filter = Filter::new()
+            .address(self.exchange_addresses())
+            .topic0(vec![
+                H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE)),
+                H256::from(keccak256(ORDERS_MATCHED_EVENT_SIGNATURE)),
+            ])
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let logs = self.get_logs(&filter).await?;
+        self.decode_exchange_logs(logs).await
+    }
+
+    async fn fetch_tx_exchange_logs(&self, tx_hash: H256) -> Result<(Vec<TradeOutput>, Vec<OrdersMatchedEvent>)> {
         let receipt = self.provider.get_transaction_receipt(tx_hash).await?
             .ok_or_else(|| anyhow::anyhow!("Transaction receipt not found"))?;
 
-        // Filter logs first
+        // Only logs emitted by one of our exchanges
         let logs: Vec<Log> = receipt.logs.into_iter()
-            .filter(|log| log.address == self.exchange_address && !log.topics.is_empty())
+            .filter(|log| self.exchanges.contains_key(&log.address))
             .collect();
-            
-        self.process_logs(logs).await
+
+        self.decode_exchange_logs(logs).await
+    }
+
+    /// Splits exchange logs into fills and matches, decodes both and links each fill to its match.
+    async fn decode_exchange_logs(&self, logs: Vec<Log>) -> Result<(Vec<TradeOutput>, Vec<OrdersMatchedEvent>)> {
+        let order_filled_sig = H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE));
+        let orders_matched_sig = H256::from(keccak256(ORDERS_MATCHED_EVENT_SIGNATURE));
+
+        let mut fills = Vec::new();
+        let mut matched = Vec::new();
+        for log in logs {
+            match log.topics.first() {
+                Some(sig) if *sig == order_filled_sig => fills.push(log),
+                Some(sig) if *sig == orders_matched_sig => {
+                    if let Ok(event) = self.parse_orders_matched(&log) {
+                        matched.push(event);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut trades = self.process_logs(fills).await?;
+        link_fills(&mut trades, &matched);
+        Ok((trades, matched))
     }
 
     pub async fn fetch_market_info(&self, tx_hash: H256) -> Result<Option<MarketInfo>> {
@@ -51,39 +369,17 @@ impl Scanner {
 
         let event_sig = H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE));
 
-        for log in receipt.logs {
-            if log.topics.get(0) == Some(&event_sig) {
-                if log.topics.len() < 4 { continue; }
-
-                let condition_id_log = log.topics[1];
-                let oracle_h256 = log.topics[2]; 
-                let question_id = log.topics[3];
-                let outcome_slot_count = U256::from_big_endian(&log.data);
-
-                let oracle_addr = Address::from_slice(&oracle_h256.as_bytes()[12..32]);
-                let calculated_condition_id = get_condition_id(oracle_addr, question_id, outcome_slot_count);
-
-                // Note: USDC is used as collateral
-                let collateral_token = Address::from_str(USDC_ADDRESS)?;
-
-                // Collection IDs
-                let parent_collection_id = H256::zero();
-                let collection_id_yes = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(1));
-                let collection_id_no = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(2));
-
-                // Position IDs (Token IDs)
-                let yes_token_id = get_position_id(collateral_token, collection_id_yes);
-                let no_token_id = get_position_id(collateral_token, collection_id_no);
-
-                return Ok(Some(MarketInfo {
-                    condition_id: format!("{:?}", condition_id_log),
-                    question_id: format!("{:?}", question_id),
-                    oracle: format_address(oracle_addr),
-                    outcome_slot_count: outcome_slot_count.as_u64(),
-                    collateral_token: format_address(collateral_token),
-                    yes_token_id: format!("0x{:x}", yes_token_id),
-                    no_token_id: format!("0x{:x}", no_token_id),
-                }));
+        // Prepared through the NegRiskAdapter if it emitted anything in the same transaction
+        let adapter = Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?;
+        let via_adapter = receipt.logs.iter().any(|log| log.address == adapter);
+
+        for log in &receipt.logs {
+            if log.topics.first() == Some(&event_sig)
+                && let Some(mut market) = Self::market_from_log_via(log, via_adapter)? {
+                // The UMA adapter emits QuestionInitialized in the same transaction
+                apply_uma_questions(std::slice::from_mut(&mut market), &receipt.logs)?;
+                self.stamp_markets(std::slice::from_mut(&mut market)).await?;
+                return Ok(Some(market));
             }
         }
         
@@ -94,56 +390,54 @@ impl Scanner {
         // We will scan for logs with Topic1 = conditionId
         // CTF Contract Address
         let ctf_address = Address::from_str(CTF_ADDRESS)?;
-        let start = from_block.unwrap_or(0);
-
         let filter = Filter::new()
             .address(ctf_address)
             .event(CONDITION_PREPARATION_EVENT_SIGNATURE)
-            .topic1(condition_id)
-            .from_block(start); // CLI should provide a reasonable start or 0 if risky
-            // .to_block(BlockNumber::Latest); // Implicit
+            .topic1(condition_id);
 
-        let logs = self.provider.get_logs(&filter).await?;
+        let logs = self.get_logs_in(&filter, from_block.unwrap_or(0), None, &ChunkPolicy::sparse()).await?;
 
         if let Some(log) = logs.first() {
-            // Found the log!
-            // Logic is very similar to fetch_market_info, but extracting logic
-            
-            let condition_id_log = log.topics[1]; // Should match input
-            let oracle_h256 = log.topics[2]; 
+            // Found the log! Decoding is shared with fetch_market_info / fetch_market_events
+            let oracle_addr = Address::from_slice(&log.topics[2].as_bytes()[12..32]);
             let question_id = log.topics[3];
             let outcome_slot_count = U256::from_big_endian(&log.data);
 
-            let oracle_addr = Address::from_slice(&oracle_h256.as_bytes()[12..32]);
             // Re-verify calculation just in case
-            let calculated_condition_id = get_condition_id(oracle_addr, question_id, outcome_slot_count);
-
-            if calculated_condition_id != condition_id {
+            if get_condition_id(oracle_addr, question_id, outcome_slot_count) != condition_id {
                  // Should technically not happen if topic match worked and logic holds, but sanity check
                  eprintln!("Warning: Calculated condition ID mismatch.");
             }
 
-            // Note: USDC is used as collateral
-            let collateral_token = Address::from_str(USDC_ADDRESS)?;
-
-            // Collection IDs
-            let parent_collection_id = H256::zero();
-            let collection_id_yes = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(1));
-            let collection_id_no = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(2));
-
-            // Position IDs (Token IDs)
-            let yes_token_id = get_position_id(collateral_token, collection_id_yes);
-            let no_token_id = get_position_id(collateral_token, collection_id_no);
-
-            return Ok(Some(MarketInfo {
-                condition_id: format!("{:?}", condition_id_log),
-                question_id: format!("{:?}", question_id),
-                oracle: format_address(oracle_addr),
-                outcome_slot_count: outcome_slot_count.as_u64(),
-                collateral_token: format_address(collateral_token),
-                yes_token_id: format!("0x{:x}", yes_token_id),
-                no_token_id: format!("0x{:x}", no_token_id),
-            }));
+            let Some(mut market) = Self::market_from_log(log)? else { return Ok(None) };
+            if let Some(block) = market.block_number {
+                self.fetch_uma_questions(std::slice::from_mut(&mut market), block, block).await?;
+            }
+            self.stamp_markets(std::slice::from_mut(&mut market)).await?;
+            return Ok(Some(market));
+        }
+
+        Ok(None)
+    }
+
+    /// Market prepared in `[from_block, latest]` one of whose derived position IDs is `token_id`.
+    /// Every `ConditionPreparation` in the range is decoded: the token is not a log topic.
+    pub async fn fetch_market_by_position_id(&self, token_id: &str, from_block: u64) -> Result<Option<MarketInfo>> {
+        let filter = Filter::new()
+            .address(Address::from_str(CTF_ADDRESS)?)
+            .event(CONDITION_PREPARATION_EVENT_SIGNATURE);
+
+        let logs = self.get_logs_in(&filter, from_block, None, &ChunkPolicy::sparse()).await?;
+        for log in &logs {
+            let Some(mut market) = Self::market_from_log(log)? else { continue };
+            if market.outcome_label(token_id).is_none() {
+                continue;
+            }
+            if let Some(block) = market.block_number {
+                self.fetch_uma_questions(std::slice::from_mut(&mut market), block, block).await?;
+            }
+            self.stamp_markets(std::slice::from_mut(&mut market)).await?;
+            return Ok(Some(market));
         }
 
         Ok(None)
@@ -158,265 +452,306 @@ impl Scanner {
             .from_block(from_block)
             .to_block(to_block);
 
-        let logs = self.provider.get_logs(&filter).await?;
+        let logs = self.get_logs(&filter).await?;
         let mut markets = Vec::new();
 
         for log in logs {
-            if log.topics.len() < 4 { continue; }
-            
-            let condition_id_log = log.topics[1]; 
-            let oracle_h256 = log.topics[2]; 
-            let question_id = log.topics[3];
-            let outcome_slot_count = U256::from_big_endian(&log.data);
+            if let Some(market) = Self::market_from_log(&log)? {
+                markets.push(market);
+            }
+        }
+
+        self.fetch_uma_questions(&mut markets, from_block, to_block).await?;
+        self.stamp_markets(&mut markets).await?;
+        Ok(markets)
+    }
 
-            let oracle_addr = Address::from_slice(&oracle_h256.as_bytes()[12..32]);
-            let calculated_condition_id = get_condition_id(oracle_addr, question_id, outcome_slot_count);
+    /// Titles `markets` whose oracle is a UMA CTF Adapter from the adapter's `QuestionInitialized`
+    /// logs in the range. Skips the request when no market needs it.
+    async fn fetch_uma_questions(&self, markets: &mut [MarketInfo], from_block: u64, to_block: u64) -> Result<()> {
+        if !markets.iter().any(|m| is_uma_adapter(&m.oracle)) {
+            return Ok(());
+        }
+        let filter = Filter::new()
+            .address(uma_adapter_addresses()?)
+            .event(UMA_QUESTION_INITIALIZED_EVENT_SIGNATURE)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = self.get_logs(&filter).await?;
+        apply_uma_questions(markets, &logs)
+    }
+
+    /// Split, merge, resolution and redemption logs on the CTF, in chain order.
+    pub async fn fetch_ctf_events(&self, from_block: u64, to_block: u64) -> Result<Vec<CtfEvent>> {
+        let filter = Filter::new()
+            .address(Address::from_str(CTF_ADDRESS)?)
+            .topic0(ctf_event_topics())
+            .from_block(from_block)
+            .to_block(to_block);
+        decode_ctf_logs(&self.get_logs(&filter).await?)
+    }
+
+    /// Splits, merges and redemptions `wallet` made directly on the CTF (topic1 is the stakeholder
+    /// or redeemer), in chain order.
+    pub async fn fetch_wallet_ctf_events(&self, from_block: u64, to_block: u64, wallet: Address) -> Result<Vec<CtfEvent>> {
+        let filter = Filter::new()
+            .address(Address::from_str(CTF_ADDRESS)?)
+            .topic0(ctf_event_topics())
+            .topic1(H256::from(wallet))
+            .from_block(from_block)
+            .to_block(to_block);
+        decode_ctf_logs(&self.get_logs(&filter).await?)
+    }
+
+    /// Payout numerators of every condition in `condition_ids` resolved by `to_block`, keyed by
+    /// condition ID. Unresolved conditions are absent.
+    pub async fn fetch_condition_payouts(&self, condition_ids: &[H256], to_block: u64) -> Result<HashMap<String, Vec<U256>>> {
+        if condition_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let filter = Filter::new()
+            .address(Address::from_str(CTF_ADDRESS)?)
+            .event(CONDITION_RESOLUTION_EVENT_SIGNATURE)
+            .topic1(condition_ids.to_vec());
+        let logs = self.get_logs_in(&filter, 0, Some(to_block), &ChunkPolicy::sparse()).await?;
+
+        let mut payouts = HashMap::new();
+        for event in decode_ctf_logs(&logs)? {
+            if let CtfEventKind::ConditionResolution { payout_numerators, .. } = event.kind {
+                let numerators = payout_numerators.iter()
+                    .map(|n| U256::from_dec_str(n).map_err(|e| anyhow::anyhow!("Invalid payout numerator {}: {:?}", n, e)))
+                    .collect::<Result<Vec<U256>>>()?;
+                payouts.insert(event.condition_id, numerators);
+            }
+        }
+        Ok(payouts)
+    }
+
+    /// NegRiskAdapter events (market/question preparation, reports, conversions, splits, merges,
+    /// redemptions), in chain order.
+    pub async fn fetch_neg_risk_events(&self, from_block: u64, to_block: u64) -> Result<Vec<NegRiskEvent>> {
+        let filter = Filter::new()
+            .address(Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?)
+            .topic0(neg_risk_event_topics())
+            .from_block(from_block)
+            .to_block(to_block);
+        decode_neg_risk_logs(&self.get_logs(&filter).await?)
+    }
+
+    /// NegRiskAdapter splits, merges, conversions and redemptions made by `wallet`, in chain order.
+    pub async fn fetch_wallet_neg_risk_events(&self, from_block: u64, to_block: u64, wallet: Address) -> Result<Vec<NegRiskEvent>> {
+        let filter = Filter::new()
+            .address(Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?)
+            .topic0(neg_risk_event_topics())
+            .topic1(H256::from(wallet))
+            .from_block(from_block)
+            .to_block(to_block);
+        decode_neg_risk_logs(&self.get_logs(&filter).await?)
+    }
 
-            let collateral_token = Address::from_str(USDC_ADDRESS)?;
+    /// One neg-risk market by ID: its `MarketPrepared`, every `QuestionPrepared` and any reported outcomes.
+    pub async fn fetch_neg_risk_market(&self, market_id: H256, from_block: Option<u64>) -> Result<Option<NegRiskMarket>> {
+        let topics: Vec<H256> = [
+            NEG_RISK_MARKET_PREPARED_EVENT_SIGNATURE,
+            NEG_RISK_QUESTION_PREPARED_EVENT_SIGNATURE,
+            NEG_RISK_OUTCOME_REPORTED_EVENT_SIGNATURE,
+        ].iter().map(|sig| H256::from(keccak256(sig))).collect();
 
-            let parent_collection_id = H256::zero();
-            let collection_id_yes = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(1));
-            let collection_id_no = get_collection_id(parent_collection_id, calculated_condition_id, U256::from(2));
+        // marketId is topic1 on all three events
+        let filter = Filter::new()
+            .address(Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?)
+            .topic0(topics)
+            .topic1(market_id);
+        let logs = self.get_logs_in(&filter, from_block.unwrap_or(0), None, &ChunkPolicy::sparse()).await?;
+        let events = decode_neg_risk_logs(&logs)?;
+        Ok(group_markets(&events)?.into_iter().next())
+    }
 
-            let yes_token_id = get_position_id(collateral_token, collection_id_yes);
-            let no_token_id = get_position_id(collateral_token, collection_id_no);
+    /// Resolution state of UMA questions: adapter lifecycle events plus the Optimistic Oracle
+    /// proposals, disputes and settlements the adapters requested. `question_id` narrows the scan
+    /// to one question; `to_block` defaults to latest.
+    pub async fn fetch_uma_resolutions(&self, from_block: u64, to_block: Option<u64>, question_id: Option<H256>) -> Result<Vec<UmaResolution>> {
+        let adapters = uma_adapter_addresses()?;
+        let to_block = match to_block {
+            Some(b) => b,
+            None => self.latest_block().await?,
+        };
+        // One question's adapter logs are a handful; everything else is chunked like a backfill
+        let adapter_policy = if question_id.is_some() { ChunkPolicy::sparse() } else { ChunkPolicy::default() };
+        let adapter_filter = |filter: Filter| match question_id {
+            Some(id) => filter.address(adapters.clone()).topic1(id),
+            None => filter.address(adapters.clone()),
+        };
 
-            markets.push(MarketInfo {
-                condition_id: format!("{:?}", condition_id_log),
-                question_id: format!("{:?}", question_id),
-                oracle: format_address(oracle_addr),
-                outcome_slot_count: outcome_slot_count.as_u64(),
-                collateral_token: format_address(collateral_token),
-                yes_token_id: format!("0x{:x}", yes_token_id),
-                no_token_id: format!("0x{:x}", no_token_id),
-            });
+        let initialized = adapter_filter(Filter::new().event(UMA_QUESTION_INITIALIZED_EVENT_SIGNATURE));
+        let questions = self.get_logs_in(&initialized, from_block, Some(to_block), &adapter_policy).await?
+            .iter()
+            .map(decode_question_initialized)
+            .collect::<Result<Vec<_>>>()?;
+
+        let lifecycle = adapter_filter(Filter::new().topic0(adapter_event_topics()));
+        // Oracle logs are indexed by requester, not question; the question filter applies after decoding
+        let oracle = Filter::new()
+            .address(optimistic_oracle_addresses()?)
+            .topic0(oracle_event_topics())
+            .topic1(adapters.iter().copied().map(H256::from).collect::<Vec<_>>());
+
+        let lifecycle_logs = self.get_logs_in(&lifecycle, from_block, Some(to_block), &adapter_policy).await?;
+        let oracle_logs = self.get_logs_in(&oracle, from_block, Some(to_block), &ChunkPolicy::default()).await?;
+        let mut events = Vec::new();
+        for log in lifecycle_logs.iter().chain(oracle_logs.iter()) {
+            if let Some(event) = decode_uma_log(log)? {
+                events.push(event);
+            }
         }
-        
-        Ok(markets)
+        if let Some(id) = question_id {
+            let id = format!("{:?}", id);
+            events.retain(|e| e.question_id == id);
+        }
+        Ok(resolutions(&questions, &events))
+    }
+
+    /// Decodes a `ConditionPreparation` log into a `MarketInfo` (None if the log is malformed
+    /// or its outcome slot count is unsupported). Neg-risk is detected from the oracle.
+    fn market_from_log(log: &Log) -> Result<Option<MarketInfo>> {
+        Self::market_from_log_via(log, false)
     }
 
+    /// Like `market_from_log`, forcing neg-risk derivation when the caller knows the condition
+    /// was prepared through the NegRiskAdapter.
+    fn market_from_log_via(log: &Log, via_adapter: bool) -> Result<Option<MarketInfo>> {
+        if log.topics.len() < 4 || log.data.len() < 32 { return Ok(None); }
+
+        let condition_id = log.topics[1];
+        let oracle_addr = Address::from_slice(&log.topics[2].as_bytes()[12..32]);
+        let question_id = log.topics[3];
+        let outcome_slot_count = U256::from_big_endian(&log.data[0..32]);
+        if outcome_slot_count > U256::from(u64::MAX) { return Ok(None); }
+
+        let Ok(mut market) = derive_market_info(condition_id, question_id, oracle_addr, outcome_slot_count.as_u64(), via_adapter) else {
+            return Ok(None);
+        };
+        market.block_number = log.block_number.map(|b| b.as_u64());
+        market.block_hash = log.block_hash.map(|h| format!("{:?}", h));
+        Ok(Some(market))
+    }
+
+    /// Decodes `OrderFilled` logs and checks each fill's position legs against its transaction's
+    /// ERC-1155 transfers (one receipt per transaction). Inconsistencies end up in `asset_issues`.
     async fn process_logs(&self, logs: Vec<Log>) -> Result<Vec<TradeOutput>> {
-         // 1. First pass: Parse logs to get raw info (tx_hash, amounts, asset_ids)
-         // We need the raw data to know what to look for
-         
-         struct RawTrade {
-             log: Log,
-             maker_asset_id: U256,
-             taker_asset_id: U256,
-             maker_amount: U256,
-             taker_amount: U256,
-         }
-         
-         let mut raw_trades = Vec::new();
-         let mut potential_tokens = std::collections::HashSet::new(); // IDs treated as addresses
-         let mut tx_hashes = std::collections::HashSet::new();
-
-         for log in logs {
-             if log.data.len() < 128 { continue; } // Basic size check
-             
-             let data = log.data.to_vec();
-             let maker_asset_id = U256::from_big_endian(&data[0..32]);
-             let taker_asset_id = U256::from_big_endian(&data[32..64]);
-             let maker_amount = U256::from_big_endian(&data[64..96]);
-             let taker_amount = U256::from_big_endian(&data[96..128]);
-             
-             if maker_asset_id != U256::zero() {
-                 let mut bytes = [0u8; 32];
-                 maker_asset_id.to_big_endian(&mut bytes);
-                 let addr = Address::from_slice(&bytes[12..32]);
-                 potential_tokens.insert(addr);
-             }
-             if taker_asset_id != U256::zero() {
-                 let mut bytes = [0u8; 32];
-                 taker_asset_id.to_big_endian(&mut bytes);
-                 let addr = Address::from_slice(&bytes[12..32]);
-                 potential_tokens.insert(addr);
-             }
-             
-             if let Some(tx_hash) = log.transaction_hash {
-                 tx_hashes.insert(tx_hash);
-             }
-
-             raw_trades.push(RawTrade { log, maker_asset_id, taker_asset_id, maker_amount, taker_amount });
-         }
-
-         // 2. Try to fetch decimals for simple Address-like IDs
-         let mut decimals_map: HashMap<Address, u32> = HashMap::new();
-         
-         for addr in potential_tokens {
-             if let Some(dec) = self.get_decimals(addr).await {
-                 decimals_map.insert(addr, dec);
-                 // If this addr works, then the ID *is* the address (or mapped correctly)
-                 // We can map the ID (u256) back to this addr for lookup later is tricky cause ID->Addr lossy.
-                 // Actually we can just use the Addr derived from ID.
-             }
-         }
-
-         // 3. For trades where ID didn't work (decimals_map miss), Fetch Receipts
-         // We iterate raw_trades. If we can't find decimals for an ID, we flag the TX for receipt fetch.
-         
-         let mut txs_to_fetch = std::collections::HashSet::new();
-         
-         for trade in &raw_trades {
-              // Check Maker
-              if trade.maker_asset_id != U256::zero() {
-                   let mut bytes = [0u8; 32];
-                   trade.maker_asset_id.to_big_endian(&mut bytes);
-                   let addr = Address::from_slice(&bytes[12..32]);
-                   if !decimals_map.contains_key(&addr) {
-                       txs_to_fetch.insert(trade.log.transaction_hash.unwrap());
-                   }
-              }
-              // Check Taker
-              if trade.taker_asset_id != U256::zero() {
-                   let mut bytes = [0u8; 32];
-                   trade.taker_asset_id.to_big_endian(&mut bytes);
-                   let addr = Address::from_slice(&bytes[12..32]);
-                   if !decimals_map.contains_key(&addr) {
-                       txs_to_fetch.insert(trade.log.transaction_hash.unwrap());
-                   }
-              }
-         }
-         
-         // 4. Fetch Receipts for problematic TXs
-         let mut receipt_token_map: HashMap<H256, HashMap<U256, Address>> = HashMap::new();
-         // Map TxHash -> (Amount -> TokenAddress)
-         // Wait, Amount is not unique?
-         // We map (Amount) -> TokenAddress. If collision, first win?
-         // Heuristic: Scan Transfer events.
-         
-         for tx_hash in txs_to_fetch {
-             if let Ok(Some(receipt)) = self.provider.get_transaction_receipt(tx_hash).await {
-                 // Scan logs for Transfers
-                 // Transfer(from, to, value) -> topic0 = 0xddf252...
-                 let transfer_topic = H256::from_str("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef").unwrap();
-                 
-                 let mut amount_map = HashMap::new();
-                 
-                 for log in receipt.logs {
-                     if log.topics.get(0) == Some(&transfer_topic) && log.topics.len() == 3 {
-                         // ERC20 Transfer: topic1=from, topic2=to, data=value
-                         let value = U256::from_big_endian(&log.data);
-                         amount_map.insert(value, log.address); // Token Address is log.address
-                     }
-                 }
-                 receipt_token_map.insert(tx_hash, amount_map);
-             }
-         }
-         
-         // 5. Final Pass: Parse
-         let mut trades = Vec::new();
-         for trade in raw_trades {
-             let mut maker_decimals = 6; // Polymarket uses 6 decimals for all tokens
-             let mut taker_decimals = 6;
-             
-             // Resolve Maker Decimals
-             if trade.maker_asset_id == U256::zero() {
-                 maker_decimals = 6;
-             } else {
-                 let mut bytes = [0u8; 32];
-                 trade.maker_asset_id.to_big_endian(&mut bytes);
-                 let addr_from_id = Address::from_slice(&bytes[12..32]);
-                 
-                 if let Some(&d) = decimals_map.get(&addr_from_id) {
-                     maker_decimals = d;
-                 } else {
-                     // Try receipt lookup
-                     if let Some(map) = receipt_token_map.get(&trade.log.transaction_hash.unwrap()) {
-                         if let Some(&real_token_addr) = map.get(&trade.maker_amount) {
-                             // Fetch Decimals for this Real Address if not known
-                             if let Some(&d) = decimals_map.get(&real_token_addr) {
-                                 maker_decimals = d;
-                             } else {
-                                 if let Some(d) = self.get_decimals(real_token_addr).await {
-                                     maker_decimals = d;
-                                     decimals_map.insert(real_token_addr, d); // Cache
-                                 }
-                             }
-                         }
-                     }
-                 }
-             }
-             
-             // Resolve Taker Decimals
-             if trade.taker_asset_id == U256::zero() {
-                 taker_decimals = 6;
-             } else {
-                 let mut bytes = [0u8; 32];
-                 trade.taker_asset_id.to_big_endian(&mut bytes);
-                 let addr_from_id = Address::from_slice(&bytes[12..32]);
-                 
-                 if let Some(&d) = decimals_map.get(&addr_from_id) {
-                     taker_decimals = d;
-                 } else {
-                     // Try receipt lookup
-                     if let Some(map) = receipt_token_map.get(&trade.log.transaction_hash.unwrap()) {
-                         if let Some(&real_token_addr) = map.get(&trade.taker_amount) {
-                             if let Some(&d) = decimals_map.get(&real_token_addr) {
-                                 taker_decimals = d;
-                             } else {
-                                 if let Some(d) = self.get_decimals(real_token_addr).await {
-                                     taker_decimals = d;
-                                     decimals_map.insert(real_token_addr, d); // Cache
-                                 }
-                             }
-                         }
-                     }
-                 }
-             }
-
-             if let Ok(output) = self.parse_final(trade.log, maker_decimals, taker_decimals) {
-                 trades.push(output);
-             }
-         }
-         Ok(trades)
-    }
-
-    async fn get_decimals(&self, token: Address) -> Option<u32> {
-        let tx = TransactionRequest::new()
-            .to(token)
-            .data(Bytes::from_str("0x313ce567").unwrap()); 
-
-        match self.provider.call(&tx.into(), None).await {
-            Ok(result) => {
-                if result.len() >= 32 {
-                    Some(U256::from_big_endian(&result).as_u32())
-                } else {
-                    None // Call successful but no data (e.g. EOA or non-compliant)
-                }
-            },
-            Err(_) => None // Call failed (not a contract?)
+        let tx_hashes: BTreeSet<H256> = logs.iter().filter_map(|log| log.transaction_hash).collect();
+        let transfers: HashMap<H256, _> = stream::iter(tx_hashes)
+            .map(|tx_hash| async move {
+                let moved = match self.provider.get_transaction_receipt(tx_hash).await {
+                    Ok(Some(receipt)) => position_transfers(&receipt.logs).map_err(|e| format!("undecodable receipt: {}", e)),
+                    Ok(None) => Err("receipt not found".to_string()),
+                    Err(e) => Err(format!("receipt unavailable: {}", e)),
+                };
+                (tx_hash, moved)
+            })
+            .buffer_unordered(RECEIPT_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut trades = Vec::new();
+        for log in logs {
+            let moved = log.transaction_hash.and_then(|tx_hash| transfers.get(&tx_hash));
+            let Ok(fill) = FillAssets::from_log(&log) else { continue };
+            let Ok(mut trade) = self.parse_final(log) else { continue };
+            trade.asset_issues = match moved {
+                Some(Ok(moved)) => verify_fill(&fill, moved),
+                Some(Err(reason)) => vec![reason.clone()],
+                None => vec!["log has no transaction hash".to_string()],
+            };
+            trades.push(trade);
         }
+
+        let timestamps = self.block_timestamps(trades.iter().map(|t| t.block_number)).await?;
+        for trade in &mut trades {
+            trade.timestamp = timestamps.get(&trade.block_number).copied();
+        }
+        Ok(trades)
     }
 
-    fn parse_final(&self, log: Log, maker_decimals: u32, taker_decimals: u32) -> Result<TradeOutput> {
-         // Event signature already checked implicitly
-        
-        let maker = Address::from(log.topics[2]);
-        let taker = Address::from(log.topics[3]);
+    fn parse_orders_matched(&self, log: &Log) -> Result<OrdersMatchedEvent> {
+        if log.topics.len() < 3 || log.data.len() < 128 {
+            return Err(anyhow::anyhow!("Malformed OrdersMatched log"));
+        }
+        let venue = *self.exchanges.get(&log.address)
+            .ok_or_else(|| anyhow::anyhow!("Log not emitted by a known exchange"))?;
         let data = log.data.to_vec();
 
-        let maker_asset_id = U256::from_big_endian(&data[0..32]);
-        let taker_asset_id = U256::from_big_endian(&data[32..64]);
-        let maker_amount_filled = U256::from_big_endian(&data[64..96]);
-        let taker_amount_filled = U256::from_big_endian(&data[96..128]);
+        let asset_str = |id: U256| if id.is_zero() { "0".to_string() } else { format!("0x{:x}", id) };
+
+        Ok(OrdersMatchedEvent {
+            tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+            log_index: log.log_index.unwrap_or_default().as_u64(),
+            block_number: log.block_number.unwrap_or_default().as_u64(),
+            exchange: format_address(log.address),
+            venue,
+            taker_order_hash: format!("{:?}", log.topics[1]),
+            taker_order_maker: format_address(Address::from(log.topics[2])),
+            maker_asset_id: asset_str(U256::from_big_endian(&data[0..32])),
+            taker_asset_id: asset_str(U256::from_big_endian(&data[32..64])),
+            maker_amount_filled: u256_to_string(U256::from_big_endian(&data[64..96])),
+            taker_amount_filled: u256_to_string(U256::from_big_endian(&data[96..128])),
+        })
+    }
+
+    fn parse_final(&self, log: Log) -> Result<TradeOutput> {
+        let venue = *self.exchanges.get(&log.address)
+            .ok_or_else(|| anyhow::anyhow!("Log not emitted by a known exchange"))?;
+        let fill = FillAssets::from_log(&log)?;
+        let taker = Address::from(log.topics[3]);
+
+        let maker = fill.maker;
+        let maker_asset_id = fill.maker_asset.id();
+        let taker_asset_id = fill.taker_asset.id();
+        let maker_amount_filled = fill.maker_amount;
+        let taker_amount_filled = fill.taker_amount;
+        let maker_decimals = fill.maker_asset.decimals();
+        let taker_decimals = fill.taker_asset.decimals();
 
         // Determine Side and Price based on which asset is USDC matching User Logic
+        let scale = self.price_scale;
         let (price, maker_asset_str, taker_asset_str) = if maker_asset_id == U256::zero() {
-            let p = calculate_price(maker_amount_filled, maker_decimals, taker_amount_filled, taker_decimals);
+            let p = calculate_price(maker_amount_filled, maker_decimals, taker_amount_filled, taker_decimals, scale);
             (p, "0".to_string(), format!("0x{:x}", taker_asset_id))
         } else if taker_asset_id == U256::zero() {
-             let p = calculate_price(taker_amount_filled, taker_decimals, maker_amount_filled, maker_decimals);
+             let p = calculate_price(taker_amount_filled, taker_decimals, maker_amount_filled, maker_decimals, scale);
             (p, format!("0x{:x}", maker_asset_id), "0".to_string())
         } else {
-            let p = calculate_price(maker_amount_filled, maker_decimals, taker_amount_filled, taker_decimals);
+            let p = calculate_price(maker_amount_filled, maker_decimals, taker_amount_filled, taker_decimals, scale);
             (p, format!("0x{:x}", maker_asset_id), format!("0x{:x}", taker_asset_id))
         };
 
+        // Size in outcome-token units (the non-USDC leg)
+        let size = if maker_asset_id == U256::zero() {
+            Amount::new(taker_amount_filled, taker_decimals)
+        } else {
+            Amount::new(maker_amount_filled, maker_decimals)
+        };
+
+        // Charged in the asset the maker receives (the taker asset)
+        let fee = fill.fee;
+        let (notional, fee_usdc) = if maker_asset_id == U256::zero() {
+            // Maker buys outcome tokens, so the fee is in tokens: value it at the fill price
+            let notional = Amount::new(maker_amount_filled, maker_decimals);
+            let fee_usdc = Amount::new(fee, taker_decimals).checked_mul(price, maker_decimals).unwrap_or_else(|| Amount::zero(maker_decimals));
+            (notional, fee_usdc)
+        } else if taker_asset_id == U256::zero() {
+            (Amount::new(taker_amount_filled, taker_decimals), Amount::new(fee, taker_decimals))
+        } else {
+            (Amount::zero(taker_decimals), Amount::zero(taker_decimals))
+        };
+        let fee_bps = calculate_fee_bps(fee_usdc, notional);
+
         let side = if maker_asset_id == U256::zero() {
             TradeSide::BUY
-        } else {
+        } else if taker_asset_id == U256::zero() {
             TradeSide::SELL
+        } else {
+            TradeSide::UNKNOWN
         };
 
         // Identifies the non-USDC token
@@ -426,10 +761,16 @@ impl Scanner {
             maker_asset_id
         };
 
-        Ok(TradeOutput {
+        let mut trade = TradeOutput {
             tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
             log_index: log.log_index.unwrap_or_default().as_u64(),
+            order_hash: format!("{:?}", log.topics[1]),
+            match_log_index: None,
+            block_number: log.block_number.unwrap_or_default().as_u64(),
+            block_hash: format!("{:?}", log.block_hash.unwrap_or_default()),
+            timestamp: None,
             exchange: format_address(log.address),
+            venue,
             maker: format_address(maker),
             taker: format_address(taker),
             maker_asset_id: maker_asset_str,
@@ -439,10 +780,159 @@ impl Scanner {
             maker_decimals,
             taker_decimals,
             price,
+            size,
+            fee: u256_to_string(fee),
+            fee_usdc,
+            fee_bps,
             token_id: format!("0x{:x}", token_id),
             side,
-        })
+            maker_action: TradeAction::new(side, &format!("0x{:x}", token_id)),
+            // Until linked to a match, the taker is the maker's counterparty on the same token
+            taker_action: TradeAction::new(side.opposite(), &format!("0x{:x}", token_id)),
+            complementary: false,
+            condition_id: None,
+            complement_token_id: None,
+            outcome_index: None,
+            asset_issues: Vec::new(),
+        };
+        self.registry.read().unwrap_or_else(|poisoned| poisoned.into_inner()).label(&mut trade);
+        Ok(trade)
+    }
+}
+
+impl<P: PubsubClient> Scanner<P> {
+    /// Subscribes to `OrderFilled` (all exchanges) and `ConditionPreparation` (CTF) logs and
+    /// pushes each decoded event into `events` as it arrives.
+    ///
+    /// `cursor` is the (block, log index) of the last log handled. When set (a reconnect), the logs
+    /// from its block up to the latest are backfilled first; logs at or before it are skipped, as
+    /// are logs the node flags as removed by a reorg. A log that fails to decode is reported and
+    /// skipped rather than ending the subscription.
+    ///
+    /// Returns `Ok(())` once the receiver is dropped, and an error when the subscription ends
+    /// (e.g. the socket dropped) so the caller can reconnect.
+    pub async fn watch(&self, events: &tokio::sync::mpsc::Sender<StreamEvent>, cursor: &mut Option<(u64, u64)>) -> Result<()> {
+        let order_filled_sig = H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE));
+        let condition_prep_sig = H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE));
+
+        let mut addresses = self.exchange_addresses();
+        addresses.push(Address::from_str(CTF_ADDRESS)?);
+
+        // Single subscription: any of our contracts, either event signature
+        let filter = Filter::new()
+            .address(addresses)
+            .topic0(vec![order_filled_sig, condition_prep_sig]);
+
+        // Subscribe before catching up so nothing lands between the backfill and the stream
+        let mut stream = self.provider.subscribe_logs(&filter).await?;
+
+        if let Some((block, _)) = *cursor {
+            let missed = self.get_logs_in(&filter, block, None, &ChunkPolicy::default()).await?;
+            for log in missed {
+                if !self.forward(log, events, cursor).await {
+                    return Ok(());
+                }
+            }
+        }
+
+        while let Some(log) = stream.next().await {
+            if !self.forward(log, events, cursor).await {
+                return Ok(()); // Consumer is gone, stop quietly
+            }
+        }
+
+        Err(anyhow::anyhow!("Log subscription ended"))
+    }
+
+    /// Decodes one watched log into `events` and advances `cursor` past it. False once the
+    /// receiver is dropped.
+    async fn forward(&self, log: Log, events: &tokio::sync::mpsc::Sender<StreamEvent>, cursor: &mut Option<(u64, u64)>) -> bool {
+        let position = (
+            log.block_number.map_or(0, |b| b.as_u64()),
+            log.log_index.map_or(0, |i| i.as_u64()),
+        );
+        if log.removed == Some(true) {
+            eprintln!("Skipping log {}:{} removed by a reorg", position.0, position.1);
+            return true;
+        }
+        if cursor.is_some_and(|last| position <= last) {
+            return true; // Already handled before a reconnect
+        }
+        *cursor = Some(position);
+
+        let decoded = match self.decode_watched(log).await {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                eprintln!("Skipping log {}:{}: {}", position.0, position.1, e);
+                return true;
+            }
+        };
+        for event in decoded {
+            if events.send(event).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn decode_watched(&self, log: Log) -> Result<Vec<StreamEvent>> {
+        let order_filled_sig = H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE));
+        let condition_prep_sig = H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE));
+
+        if log.topics.first() == Some(&order_filled_sig) && self.exchanges.contains_key(&log.address) {
+            let trades = self.process_logs(vec![log]).await?;
+            Ok(trades.into_iter().map(|trade| StreamEvent::Trade(Box::new(trade))).collect())
+        } else if log.topics.first() == Some(&condition_prep_sig) {
+            let mut markets: Vec<MarketInfo> = Self::market_from_log(&log)?.into_iter().collect();
+            self.stamp_markets(&mut markets).await?;
+            Ok(markets.into_iter().map(|market| StreamEvent::Market(Box::new(market))).collect())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// CTF events among `logs`, in chain order.
+fn decode_ctf_logs(logs: &[Log]) -> Result<Vec<CtfEvent>> {
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(event) = decode_ctf_log(log)? {
+            events.push(event);
+        }
+    }
+    events.sort_by_key(|e| (e.block_number, e.log_index));
+    Ok(events)
+}
+
+/// NegRiskAdapter events among `logs`, in chain order.
+fn decode_neg_risk_logs(logs: &[Log]) -> Result<Vec<NegRiskEvent>> {
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(event) = decode_neg_risk_log(log)? {
+            events.push(event);
+        }
+    }
+    events.sort_by_key(|e| (e.block_number, e.log_index));
+    Ok(events)
+}
+
+/// Joins `QuestionInitialized` logs (others are ignored) onto UMA-resolved markets by question ID.
+fn apply_uma_questions(markets: &mut [MarketInfo], logs: &[Log]) -> Result<()> {
+    let event_sig = H256::from(keccak256(UMA_QUESTION_INITIALIZED_EVENT_SIGNATURE));
+    let adapters = uma_adapter_addresses()?;
+
+    for log in logs {
+        if log.topics.first() != Some(&event_sig) || !adapters.contains(&log.address) {
+            continue;
+        }
+        let question = decode_question_initialized(log)?;
+        for market in markets.iter_mut() {
+            if market.question_id == question.question_id && is_uma_adapter(&market.oracle) {
+                apply_question(market, &question);
+            }
+        }
     }
+    Ok(())
 }
 
 #[cfg(test)]
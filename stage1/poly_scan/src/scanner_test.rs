@@ -2,11 +2,158 @@
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use ethers::types::{H256, Address, U256};
-    use ethers::abi::{encode_packed, Token};
+    use ethers::types::{H256, Address, U256, Log, Bytes, U64};
+    use ethers::abi::{encode, encode_packed, Token};
     use ethers::utils::keccak256;
+    use crate::consts::{EXCHANGE_PROXY_ADDRESS, NEG_RISK_EXCHANGE_ADDRESS, ORDER_FILLED_EVENT_SIGNATURE};
+    use crate::models::{ExchangeVenue, TradeSide};
     use crate::scanner::Scanner;
-    use crate::utils::u256_to_string;
+    use crate::utils::{fee_totals, u256_to_string};
+
+    fn order_filled_log(exchange: &str, maker_asset_id: U256, taker_asset_id: U256, maker_amount: u64, taker_amount: u64, fee: u64) -> Log {
+        let data = encode(&[
+            Token::Uint(maker_asset_id),
+            Token::Uint(taker_asset_id),
+            Token::Uint(U256::from(maker_amount)),
+            Token::Uint(U256::from(taker_amount)),
+            Token::Uint(U256::from(fee)),
+        ]);
+        Log {
+            address: Address::from_str(exchange).unwrap(),
+            topics: vec![
+                H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE)),
+                H256::repeat_byte(0xaa),
+                H256::from(Address::repeat_byte(0x11)),
+                H256::from(Address::repeat_byte(0x22)),
+            ],
+            data: Bytes::from(data),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            log_index: Some(U256::from(7)),
+            block_number: Some(U64::from(66_000_000u64)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trades_are_tagged_with_venue() {
+        let scanner = Scanner::new(crate::consts::POLYGON_RPC_URL).unwrap();
+        let token = U256::from(12345);
+
+        let binary = order_filled_log(EXCHANGE_PROXY_ADDRESS, U256::zero(), token, 500_000, 1_000_000, 0);
+        let trade = scanner.parse_final(binary).unwrap();
+        assert_eq!(trade.venue, ExchangeVenue::Binary);
+
+        let neg_risk = order_filled_log(NEG_RISK_EXCHANGE_ADDRESS, token, U256::zero(), 1_000_000, 400_000, 0);
+        let trade = scanner.parse_final(neg_risk).unwrap();
+        assert_eq!(trade.venue, ExchangeVenue::NegRisk);
+        assert_eq!(trade.log_index, 7);
+
+        let unknown = order_filled_log(crate::consts::CTF_ADDRESS, U256::zero(), token, 1, 1, 0);
+        assert!(scanner.parse_final(unknown).is_err());
+
+        // Neither leg is collateral: no side can be stated for either participant
+        let swap = scanner.parse_final(order_filled_log(EXCHANGE_PROXY_ADDRESS, token, U256::from(54321), 1, 1, 0)).unwrap();
+        assert_eq!(swap.side, TradeSide::UNKNOWN);
+        assert_eq!(swap.taker_action.side, TradeSide::UNKNOWN);
+    }
+
+    #[test]
+    fn test_fee_is_decoded_and_valued_in_usdc() {
+        let scanner = Scanner::new(crate::consts::POLYGON_RPC_URL).unwrap();
+        let token = U256::from(12345);
+
+        // Maker sells 200 tokens for 80 USDC and pays 0.16 USDC
+        let sell = order_filled_log(EXCHANGE_PROXY_ADDRESS, token, U256::zero(), 200_000_000, 80_000_000, 160_000);
+        let sell = scanner.parse_final(sell).unwrap();
+        assert_eq!(sell.fee, "160000");
+        assert_eq!(sell.fee_usdc.to_string(), "0.160000");
+        assert_eq!(sell.fee_bps.to_string(), "20.00");
+
+        // Maker buys 100 tokens @ 0.40 and pays 0.5 tokens, worth 0.20 USDC
+        let buy = order_filled_log(EXCHANGE_PROXY_ADDRESS, U256::zero(), token, 40_000_000, 100_000_000, 500_000);
+        let buy = scanner.parse_final(buy).unwrap();
+        assert_eq!(buy.fee_usdc.to_string(), "0.200000");
+        assert_eq!(buy.fee_bps.to_string(), "50.00");
+
+        // The taker order's own fill repeats the match's volume and is left out
+        let mut summary = buy.clone();
+        summary.taker = summary.exchange.clone();
+        let totals = fee_totals(&[sell, buy, summary]);
+        assert_eq!(totals.fills, 2);
+        assert_eq!(totals.notional_usdc.to_string(), "120.000000");
+        assert_eq!(totals.fee_usdc.to_string(), "0.360000");
+        assert_eq!(totals.fee_bps.to_string(), "30.00");
+    }
+
+    fn condition_preparation_log(outcome_slot_count: u64) -> Log {
+        condition_preparation_log_from(Address::repeat_byte(0x0a), outcome_slot_count)
+    }
+
+    fn condition_preparation_log_from(oracle: Address, outcome_slot_count: u64) -> Log {
+        Log {
+            address: Address::from_str(crate::consts::CTF_ADDRESS).unwrap(),
+            topics: vec![
+                H256::from(keccak256(crate::consts::CONDITION_PREPARATION_EVENT_SIGNATURE)),
+                H256::repeat_byte(0xc0),
+                H256::from(oracle),
+                H256::repeat_byte(0x0b),
+            ],
+            data: Bytes::from(encode(&[Token::Uint(U256::from(outcome_slot_count))])),
+            block_number: Some(U64::from(90u64)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_market_from_log_derives_one_position_per_slot() {
+        let usdc = Address::from_str(crate::consts::USDC_ADDRESS).unwrap();
+        let condition_id = H256::repeat_byte(0xc0);
+
+        let mut market = Scanner::<ethers::providers::Http>::market_from_log(&condition_preparation_log(3)).unwrap().unwrap();
+        let index_sets: Vec<u64> = market.outcomes.iter().map(|o| o.index_set).collect();
+        assert_eq!(index_sets, vec![1, 2, 4]);
+        let third = crate::utils::get_position_id(usdc, crate::utils::get_collection_id(H256::zero(), condition_id, U256::from(4)));
+        assert_eq!(market.outcomes[2].position_id, format!("0x{:x}", third));
+        assert_eq!(market.yes_token_id(), None);
+        assert_eq!(market.outcome_label(&format!("0x{:x}", third)), Some("OUTCOME_2".to_string()));
+
+        let mut binary = Scanner::<ethers::providers::Http>::market_from_log(&condition_preparation_log(2)).unwrap().unwrap();
+        assert_eq!(binary.yes_token_id(), Some(binary.outcomes[0].position_id.as_str()));
+        assert_eq!(binary.outcome_label(&binary.outcomes[1].position_id), Some("NO".to_string()));
+        assert_eq!(binary.block_number, Some(90));
+
+        // Combined sets must be proper subsets; the full set (3 for binary) is rejected
+        crate::utils::add_combined_positions(&mut market, &[3, 5, 1]).unwrap();
+        let combined: Vec<u64> = market.combined_positions.iter().map(|p| p.index_set).collect();
+        assert_eq!(combined, vec![3, 5]);
+        assert!(crate::utils::add_combined_positions(&mut market, &[8]).is_err());
+        assert!(crate::utils::add_combined_positions(&mut binary, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_neg_risk_conditions_use_wrapped_collateral() {
+        use crate::consts::{NEG_RISK_ADAPTER_ADDRESS, NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, USDC_ADDRESS};
+        let adapter = Address::from_str(NEG_RISK_ADAPTER_ADDRESS).unwrap();
+        let wrapped = Address::from_str(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS).unwrap();
+        let yes_collection = crate::utils::get_collection_id(H256::zero(), H256::repeat_byte(0xc0), U256::from(1));
+
+        let market = Scanner::<ethers::providers::Http>::market_from_log(&condition_preparation_log_from(adapter, 2)).unwrap().unwrap();
+        assert!(market.neg_risk);
+        assert_eq!(market.adapter, Some(format!("{:?}", adapter)));
+        assert_eq!(market.collateral_token, format!("{:?}", wrapped));
+        let expected = crate::utils::get_position_id(wrapped, yes_collection);
+        assert_eq!(market.yes_token_id(), Some(format!("0x{:x}", expected).as_str()));
+
+        // Same condition under a plain oracle derives from USDC.e
+        let plain = Scanner::<ethers::providers::Http>::market_from_log(&condition_preparation_log(2)).unwrap().unwrap();
+        assert!(!plain.neg_risk && plain.adapter.is_none());
+        let expected = crate::utils::get_position_id(Address::from_str(USDC_ADDRESS).unwrap(), yes_collection);
+        assert_eq!(plain.yes_token_id(), Some(format!("0x{:x}", expected).as_str()));
+
+        // Prepared through the adapter with another oracle
+        let via = Scanner::<ethers::providers::Http>::market_from_log_via(&condition_preparation_log(2), true).unwrap().unwrap();
+        assert_eq!(via.collateral_token, format!("{:?}", wrapped));
+    }
 
     fn get_collection_id_packed(
         parent_collection_id: H256,
@@ -82,8 +229,8 @@ mod tests {
                 println!("Question ID: {}", info.question_id);
                 println!("Oracle: {}", info.oracle);
                 println!("Outcome Slots: {}", info.outcome_slot_count);
-                println!("Yes Token: {}", info.yes_token_id);
-                println!("No Token: {}", info.no_token_id);
+                println!("Yes Token: {}", info.yes_token_id().unwrap_or("-"));
+                println!("No Token: {}", info.no_token_id().unwrap_or("-"));
             },
             Ok(None) => panic!("Market not found!"),
             Err(e) => panic!("Error: {}", e),
@@ -114,8 +261,8 @@ mod tests {
                         println!("Oracle: {}", m.oracle);
                         println!("Question ID: {}", m.question_id);
                         println!("Outcome Slots: {}", m.outcome_slot_count);
-                        println!("Yes Token: {}", m.yes_token_id);
-                        println!("No Token: {}", m.no_token_id);
+                        println!("Yes Token: {}", m.yes_token_id().unwrap_or("-"));
+                        println!("No Token: {}", m.no_token_id().unwrap_or("-"));
                         println!("--------------------------------------------------");
                         return;
                     }
@@ -0,0 +1,61 @@
+use crate::models::StreamEvent;
+use crate::scanner::Scanner;
+use crate::timestamps::TimestampCache;
+use anyhow::Result;
+use colored::*;
+use ethers::providers::Ws;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Reconnect backoff for `follow`.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Streams live trades and market creations from `ws_url` into `events`, reconnecting with
+/// exponential backoff whenever the socket drops and backfilling from the last handled log.
+/// Returns once the receiver is dropped. Every connection resolves block timestamps through
+/// `timestamps`.
+pub async fn follow(ws_url: &str, timestamps: TimestampCache, events: mpsc::Sender<StreamEvent>, policy: ReconnectPolicy) -> Result<()> {
+    let mut delay = policy.initial_delay;
+    let mut cursor = None;
+
+    loop {
+        match Scanner::<Ws>::connect_ws(ws_url).await {
+            Ok(scanner) => {
+                let scanner = scanner.with_timestamp_cache(timestamps.clone());
+                eprintln!("{} {}", "Connected to".green().bold(), ws_url.cyan());
+                delay = policy.initial_delay; // Healthy connection, reset backoff
+
+                match scanner.watch(&events, &mut cursor).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) => eprintln!("{} {}", "Stream interrupted:".yellow(), e),
+                }
+            }
+            Err(e) => eprintln!("{} {}", "WebSocket connection failed:".red(), e),
+        }
+
+        if events.is_closed() {
+            return Ok(());
+        }
+
+        eprintln!("{}", format!("Reconnecting in {:?}...", delay).yellow().italic());
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(policy.max_delay);
+    }
+}
+
+#[cfg(test)]
+#[path = "stream_test.rs"]
+mod stream_test;
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::{CONDITION_PREPARATION_EVENT_SIGNATURE, CTF_ADDRESS, NEG_RISK_EXCHANGE_ADDRESS, ORDER_FILLED_EVENT_SIGNATURE};
+    use crate::models::{ExchangeVenue, StreamEvent};
+    use crate::stream::{follow, ReconnectPolicy};
+    use crate::timestamps::TimestampCache;
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, Bytes, Log, H256, U256, U64};
+    use ethers::utils::keccak256;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn condition_preparation_log() -> Log {
+        Log {
+            address: Address::from_str(CTF_ADDRESS).unwrap(),
+            topics: vec![
+                H256::from(keccak256(CONDITION_PREPARATION_EVENT_SIGNATURE)),
+                H256::repeat_byte(0xc0),
+                H256::from(Address::repeat_byte(0x0a)),
+                H256::repeat_byte(0x0b),
+            ],
+            data: Bytes::from(encode(&[Token::Uint(U256::from(2))])),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            log_index: Some(U256::from(0)),
+            block_number: Some(U64::from(100u64)),
+            ..Default::default()
+        }
+    }
+
+    fn order_filled_log() -> Log {
+        let data = encode(&[
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::from(777)),
+            Token::Uint(U256::from(450_000)),
+            Token::Uint(U256::from(1_000_000)),
+            Token::Uint(U256::zero()),
+        ]);
+        Log {
+            address: Address::from_str(NEG_RISK_EXCHANGE_ADDRESS).unwrap(),
+            topics: vec![
+                H256::from(keccak256(ORDER_FILLED_EVENT_SIGNATURE)),
+                H256::repeat_byte(0xaa),
+                H256::from(Address::repeat_byte(0x11)),
+                H256::from(Address::repeat_byte(0x22)),
+            ],
+            data: Bytes::from(data),
+            transaction_hash: Some(H256::repeat_byte(0x02)),
+            log_index: Some(U256::from(3)),
+            block_number: Some(U64::from(101u64)),
+            ..Default::default()
+        }
+    }
+
+    /// `order_filled_log` moved to another block / log index.
+    fn fill_at(block: u64, log_index: u64) -> Log {
+        Log {
+            log_index: Some(U256::from(log_index)),
+            block_number: Some(U64::from(block)),
+            ..order_filled_log()
+        }
+    }
+
+    /// Minimal JSON-RPC over WebSocket: answers `eth_subscribe` and pushes `pushed` on the
+    /// subscription, returns `missed` for `eth_getLogs` and block 0x66 as the latest, and answers
+    /// every other call with `null`. Drops the socket right after pushing if `drop_after_push`.
+    async fn serve_connection(stream: TcpStream, pushed: Vec<Log>, missed: Vec<Log>, drop_after_push: bool) {
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        while let Some(Ok(msg)) = ws.next().await {
+            let Message::Text(text) = msg else { continue };
+            let request: Value = serde_json::from_str(&text).unwrap();
+            let id = request["id"].clone();
+
+            if request["method"] == "eth_subscribe" {
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": "0x1" });
+                ws.send(Message::Text(reply.to_string())).await.unwrap();
+
+                for log in &pushed {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "eth_subscription",
+                        "params": { "subscription": "0x1", "result": log },
+                    });
+                    ws.send(Message::Text(notification.to_string())).await.unwrap();
+                }
+
+                if drop_after_push {
+                    return; // Simulate the node dropping the socket
+                }
+            } else {
+                let result = match request["method"].as_str() {
+                    Some("eth_blockNumber") => json!("0x66"),
+                    Some("eth_getLogs") => json!(missed),
+                    _ => Value::Null,
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                ws.send(Message::Text(reply.to_string())).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follow_decodes_and_reconnects_after_socket_drop() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            // First connection delivers a market then drops
+            let (first, _) = listener.accept().await.unwrap();
+            serve_connection(first, vec![condition_preparation_log()], Vec::new(), true).await;
+            // The second backfills the trade missed while disconnected (and the market again),
+            // then streams a reorged-out fill, the missed trade again and a new one
+            let removed = Log { removed: Some(true), ..fill_at(102, 0) };
+            let (second, _) = listener.accept().await.unwrap();
+            serve_connection(
+                second,
+                vec![removed, order_filled_log(), fill_at(102, 1)],
+                vec![condition_preparation_log(), order_filled_log()],
+                false,
+            ).await;
+        });
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(200),
+        };
+        // Cached up front: the first socket is gone before a header could be fetched over it
+        let timestamps = TimestampCache::in_memory();
+        timestamps.insert_many(&HashMap::from([(100, 1_700_000_000), (101, 1_700_000_002), (102, 1_700_000_004)])).unwrap();
+        let follower = tokio::spawn(async move { follow(&ws_url, timestamps, tx, policy).await });
+
+        let timeout = Duration::from_secs(20);
+        let first = tokio::time::timeout(timeout, rx.recv()).await.unwrap().unwrap();
+        let second = tokio::time::timeout(timeout, rx.recv()).await.unwrap().unwrap();
+        let third = tokio::time::timeout(timeout, rx.recv()).await.unwrap().unwrap();
+
+        match first {
+            StreamEvent::Market(market) => {
+                assert_eq!(market.outcome_slot_count, 2);
+                assert_eq!(market.timestamp, Some(1_700_000_000));
+            }
+            other => panic!("Expected market, got {:?}", other),
+        }
+        match second {
+            StreamEvent::Trade(trade) => {
+                assert_eq!(trade.venue, ExchangeVenue::NegRisk);
+                assert_eq!(trade.log_index, 3);
+                assert_eq!(trade.price.to_string(), "0.450000");
+                assert_eq!(trade.size.to_string(), "1.000000");
+                assert_eq!(trade.timestamp, Some(1_700_000_002));
+            }
+            other => panic!("Expected trade, got {:?}", other),
+        }
+        // Neither the duplicate nor the removed fill came through
+        match third {
+            StreamEvent::Trade(trade) => assert_eq!((trade.block_number, trade.log_index), (102, 1)),
+            other => panic!("Expected trade, got {:?}", other),
+        }
+
+        // Dropping the receiver stops the follower
+        drop(rx);
+        follower.abort();
+    }
+}
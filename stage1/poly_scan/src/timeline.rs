@@ -0,0 +1,130 @@
+use crate::amount::Amount;
+use crate::backfill::{backfill, ChunkPolicy};
+use crate::consts::COLLATERAL_DECIMALS;
+use crate::models::{CtfEvent, CtfEventKind, MarketInfo, MarketTimeline, TimelineEntry, TimelineEvent, TimelineSummary, TradeOutput};
+use crate::scanner::Scanner;
+use crate::utils::{parse_token_id, usdc_notional};
+use anyhow::Result;
+use ethers::types::{H256, U256};
+use std::collections::HashMap;
+
+/// Collects preparation, CTF lifecycle events and trades of every position ID for `condition_id`.
+///
+/// The preparation is looked up from genesis; `from_block` (default: the preparation block) only
+/// starts the event window, which ends at `to_block` (default: latest). Returns None if no
+/// `ConditionPreparation` exists for the condition.
+pub async fn fetch_timeline(
+    scanner: &Scanner,
+    condition_id: H256,
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+    chunk_size: u64,
+) -> Result<Option<MarketTimeline>> {
+    let Some(market) = scanner.fetch_market_info_by_condition_id(condition_id, None).await? else {
+        return Ok(None);
+    };
+
+    let start = from_block.or(market.block_number).unwrap_or(0);
+    let end = match to_block {
+        Some(b) => b,
+        None => scanner.latest_block().await?,
+    };
+    let token_ids: Vec<U256> = market.position_ids().filter_map(parse_token_id).collect();
+
+    let (token_ids, condition_id) = (&token_ids, &market.condition_id);
+    let chunks = backfill(start, end, &ChunkPolicy::starting_at(chunk_size), true, |from, to| async move {
+        let ctf_events: Vec<CtfEvent> = scanner.fetch_ctf_events(from, to).await?
+            .into_iter()
+            .filter(|e| &e.condition_id == condition_id)
+            .collect();
+        let trades = scanner.fetch_token_trades(from, to, token_ids).await?;
+        Ok(vec![(ctf_events, trades)])
+    }).await?;
+    let (ctf_chunks, trade_chunks): (Vec<_>, Vec<_>) = chunks.into_iter().unzip();
+    let ctf_events: Vec<CtfEvent> = ctf_chunks.into_iter().flatten().collect();
+    let trades: Vec<TradeOutput> = trade_chunks.into_iter().flatten().collect();
+
+    let blocks = market.block_number.into_iter()
+        .chain(ctf_events.iter().map(|e| e.block_number))
+        .chain(trades.iter().map(|t| t.block_number));
+    let timestamps = scanner.block_timestamps(blocks).await?;
+
+    Ok(Some(assemble(market, ctf_events, trades, &timestamps)))
+}
+
+/// Orders everything chronologically and computes the summary.
+pub fn assemble(market: MarketInfo, ctf_events: Vec<CtfEvent>, trades: Vec<TradeOutput>, timestamps: &HashMap<u64, u64>) -> MarketTimeline {
+    let mut summary = TimelineSummary {
+        volume_usdc: Amount::zero(COLLATERAL_DECIMALS),
+        volume_shares: Amount::zero(COLLATERAL_DECIMALS),
+        redeemed_usdc: Amount::zero(COLLATERAL_DECIMALS),
+        ..TimelineSummary::default()
+    };
+    let mut entries = Vec::new();
+
+    if let Some(block_number) = market.block_number {
+        entries.push(TimelineEntry {
+            block_number,
+            timestamp: timestamps.get(&block_number).copied(),
+            log_index: None,
+            tx_hash: None,
+            event: TimelineEvent::Preparation(market.clone()),
+        });
+    }
+
+    for event in ctf_events.into_iter().filter(|e| e.condition_id == market.condition_id) {
+        match &event.kind {
+            CtfEventKind::PositionSplit { .. } => summary.splits += 1,
+            CtfEventKind::PositionsMerge { .. } => summary.merges += 1,
+            CtfEventKind::ConditionResolution { payout_numerators, .. } => {
+                summary.payout_numerators = Some(payout_numerators.clone());
+            }
+            CtfEventKind::PayoutRedemption { payout, .. } => {
+                summary.redemptions += 1;
+                summary.redeemed_usdc = summary.redeemed_usdc.checked_add(*payout).unwrap_or(summary.redeemed_usdc);
+            }
+        }
+        entries.push(TimelineEntry {
+            block_number: event.block_number,
+            timestamp: timestamps.get(&event.block_number).copied(),
+            log_index: Some(event.log_index),
+            tx_hash: Some(event.tx_hash.clone()),
+            event: TimelineEvent::Ctf(event),
+        });
+    }
+
+    for trade in trades {
+        // The taker-summary fill (taker == exchange) repeats its maker fills; count volume once
+        if trade.taker != trade.exchange {
+            summary.trade_count += 1;
+            summary.volume_shares = summary.volume_shares.checked_add(trade.size).unwrap_or(summary.volume_shares);
+            if let Some(notional) = usdc_notional(&trade) {
+                summary.volume_usdc = summary.volume_usdc.checked_add(notional).unwrap_or(summary.volume_usdc);
+            }
+        }
+        entries.push(TimelineEntry {
+            block_number: trade.block_number,
+            timestamp: timestamps.get(&trade.block_number).copied(),
+            log_index: Some(trade.log_index),
+            tx_hash: Some(trade.tx_hash.clone()),
+            event: TimelineEvent::Trade(Box::new(trade)),
+        });
+    }
+
+    entries.sort_by_key(|e| (e.block_number, e.log_index));
+
+    let mut trade_blocks = entries.iter()
+        .filter(|e| matches!(e.event, TimelineEvent::Trade(_)))
+        .map(|e| (e.block_number, e.timestamp));
+    if let Some((block, timestamp)) = trade_blocks.next() {
+        (summary.first_trade_block, summary.first_trade_timestamp) = (Some(block), timestamp);
+        let (last_block, last_timestamp) = trade_blocks.next_back().unwrap_or((block, timestamp));
+        (summary.last_trade_block, summary.last_trade_timestamp) = (Some(last_block), last_timestamp);
+    }
+
+    MarketTimeline { market, entries, summary }
+}
+
+#[cfg(test)]
+#[path = "timeline_test.rs"]
+mod timeline_test;
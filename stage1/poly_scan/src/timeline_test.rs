@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::amount::Amount;
+    use crate::models::{CtfEvent, CtfEventKind, MarketInfo, TimelineEvent, TradeOutput};
+    use crate::timeline::assemble;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    const EXCHANGE: &str = "0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e";
+
+    fn ctf(condition_id: &str, block_number: u64, log_index: u64, kind: CtfEventKind) -> CtfEvent {
+        CtfEvent {
+            tx_hash: format!("0xt{}", block_number),
+            log_index,
+            block_number,
+            block_hash: format!("0xb{}", block_number),
+            condition_id: condition_id.to_string(),
+            kind,
+        }
+    }
+
+    /// Maker buys `shares` YES for `usdc` (both in base units) from `taker`.
+    fn trade(block_number: u64, log_index: u64, taker: &str, usdc: u64, shares: u64) -> TradeOutput {
+        TradeOutput { log_index, taker: taker.to_string(), ..TradeOutput::fixture("0xabcd").at_block(block_number).filled(usdc, shares) }
+    }
+
+    #[test]
+    fn test_timeline_is_chronological_with_summary() {
+        let split = CtfEventKind::PositionSplit {
+            stakeholder: "0x11".to_string(),
+            collateral_token: "0x2791".to_string(),
+            parent_collection_id: "0x00".to_string(),
+            partition: vec![1, 2],
+            amount: Amount::from_str("10.000000").unwrap(),
+        };
+        let resolution = CtfEventKind::ConditionResolution {
+            oracle: "0x0a".to_string(),
+            question_id: "0xq0".to_string(),
+            outcome_slot_count: 2,
+            payout_numerators: vec!["1".to_string(), "0".to_string()],
+        };
+        let redemption = CtfEventKind::PayoutRedemption {
+            redeemer: "0x11".to_string(),
+            collateral_token: "0x2791".to_string(),
+            parent_collection_id: "0x00".to_string(),
+            index_sets: vec![1],
+            payout: Amount::from_str("5.000000").unwrap(),
+        };
+        let ctf_events = vec![
+            ctf("0xc0", 300, 2, redemption),
+            ctf("0xc0", 100, 1, split.clone()),
+            ctf("0xc0", 250, 0, resolution),
+            ctf("0xother", 120, 0, split),
+        ];
+        let trades = vec![
+            trade(200, 5, "0x22", 2_000_000, 4_000_000),
+            // Taker-summary fill repeating the two maker fills: shown, not counted
+            trade(200, 6, EXCHANGE, 3_000_000, 6_000_000),
+            trade(150, 3, "0x22", 1_000_000, 2_000_000),
+        ];
+        let timestamps = HashMap::from([(90, 1_700_000_000), (150, 1_700_000_100), (200, 1_700_000_200)]);
+
+        let timeline = assemble(MarketInfo::fixture("0xabcd", "0xabce"), ctf_events, trades, &timestamps);
+
+        let order: Vec<(u64, Option<u64>)> = timeline.entries.iter().map(|e| (e.block_number, e.log_index)).collect();
+        assert_eq!(order, vec![(90, None), (100, Some(1)), (150, Some(3)), (200, Some(5)), (200, Some(6)), (250, Some(0)), (300, Some(2))]);
+        assert!(matches!(timeline.entries[0].event, TimelineEvent::Preparation(_)));
+        assert_eq!(timeline.entries[2].timestamp, Some(1_700_000_100));
+
+        let summary = &timeline.summary;
+        assert_eq!((summary.first_trade_block, summary.first_trade_timestamp), (Some(150), Some(1_700_000_100)));
+        assert_eq!((summary.last_trade_block, summary.last_trade_timestamp), (Some(200), Some(1_700_000_200)));
+        assert_eq!(summary.trade_count, 2);
+        assert_eq!(summary.volume_usdc.to_string(), "3.000000");
+        assert_eq!(summary.volume_shares.to_string(), "6.000000");
+        assert_eq!((summary.splits, summary.merges, summary.redemptions), (1, 0, 1));
+        assert_eq!(summary.redeemed_usdc.to_string(), "5.000000");
+        assert_eq!(summary.payout_numerators, Some(vec!["1".to_string(), "0".to_string()]));
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use ethers::types::{Log, U64};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// On-disk block timestamp cache used by the CLI.
+pub const DEFAULT_TIMESTAMP_CACHE: &str = "block_timestamps.db";
+
+/// Block headers requested concurrently when resolving timestamps.
+pub const TIMESTAMP_BATCH: usize = 32;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS block_timestamps (
+    block_number INTEGER PRIMARY KEY,
+    timestamp    INTEGER NOT NULL
+);
+
+-- Chain the cached blocks belong to, stamped by the first open
+CREATE TABLE IF NOT EXISTS block_timestamps_chain (
+    id       INTEGER PRIMARY KEY CHECK (id = 0),
+    chain_id INTEGER NOT NULL
+);
+";
+
+/// An `eth_getLogs` result entry. Some nodes attach the block's timestamp (`blockTimestamp`),
+/// which saves a header lookup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampedLog {
+    #[serde(flatten)]
+    pub log: Log,
+    #[serde(rename = "blockTimestamp", default, skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<U64>,
+}
+
+/// Block number -> timestamp of the logs that carried one.
+pub fn log_timestamps(logs: &[TimestampedLog]) -> HashMap<u64, u64> {
+    logs.iter()
+        .filter_map(|l| Some((l.log.block_number?.as_u64(), l.block_timestamp?.as_u64())))
+        .collect()
+}
+
+/// Block number -> unix timestamp, in memory and optionally persisted to SQLite. Clones share
+/// the same map and database.
+#[derive(Clone, Default)]
+pub struct TimestampCache {
+    memory: Arc<Mutex<HashMap<u64, u64>>>,
+    disk: Option<Arc<DiskCache>>,
+}
+
+/// SQLite file behind a cache. It is only opened once needed and only created by a write, so
+/// commands that never resolve a timestamp leave no file behind.
+struct DiskCache {
+    path: PathBuf,
+    /// Opening a file stamped with another chain fails: block numbers alone are ambiguous
+    chain_id: u64,
+    conn: Mutex<Option<Connection>>,
+}
+
+impl DiskCache {
+    /// Runs `f` on the connection, opening it first if needed. Without `create`, a missing file
+    /// is left alone and `f` is skipped.
+    fn with<T: Default>(&self, create: bool, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let conn = match &mut *conn {
+            Some(conn) => conn,
+            None if !create && !self.path.exists() => return Ok(T::default()),
+            None => {
+                let opened = Connection::open(&self.path)?;
+                opened.execute_batch(SCHEMA)?;
+                self.check_chain(&opened)?;
+                conn.insert(opened)
+            }
+        };
+        f(conn)
+    }
+
+    /// Stamps a new file with `chain_id`, and rejects a file stamped with another chain.
+    fn check_chain(&self, conn: &Connection) -> Result<()> {
+        conn.execute("INSERT OR IGNORE INTO block_timestamps_chain (id, chain_id) VALUES (0, ?1)", params![self.chain_id as i64])?;
+        let stored: i64 = conn.query_row("SELECT chain_id FROM block_timestamps_chain WHERE id = 0", [], |row| row.get(0))?;
+        if stored as u64 != self.chain_id {
+            return Err(anyhow::anyhow!(
+                "Timestamp cache {} holds blocks of chain {}, not {}",
+                self.path.display(), stored, self.chain_id,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TimestampCache {
+    /// Cache that lives as long as the process.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Cache of `chain_id` blocks backed by the SQLite file at `path`, opened on first use.
+    pub fn on_disk<P: AsRef<Path>>(path: P, chain_id: u64) -> Self {
+        let disk = DiskCache { path: path.as_ref().to_path_buf(), chain_id, conn: Mutex::new(None) };
+        Self { memory: Arc::default(), disk: Some(Arc::new(disk)) }
+    }
+
+    /// Known timestamps of `blocks`; blocks missing from the result still need a header lookup.
+    /// Disk hits are kept in memory for later calls.
+    pub fn get_many(&self, blocks: &BTreeSet<u64>) -> Result<HashMap<u64, u64>> {
+        let mut memory = self.memory.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut found: HashMap<u64, u64> = blocks.iter()
+            .filter_map(|block| memory.get(block).map(|ts| (*block, *ts)))
+            .collect();
+
+        if let Some(disk) = &self.disk && found.len() < blocks.len() {
+            let missing: Vec<u64> = blocks.iter().copied().filter(|b| !found.contains_key(b)).collect();
+            let stored: Vec<(u64, u64)> = disk.with(false, |conn| {
+                let mut stmt = conn.prepare_cached("SELECT timestamp FROM block_timestamps WHERE block_number = ?1")?;
+                let mut stored = Vec::new();
+                for block in missing {
+                    let timestamp: Option<i64> = stmt.query_row(params![block as i64], |row| row.get(0)).optional()?;
+                    stored.extend(timestamp.map(|ts| (block, ts as u64)));
+                }
+                Ok(stored)
+            })?;
+            memory.extend(stored.iter().copied());
+            found.extend(stored);
+        }
+        Ok(found)
+    }
+
+    /// Records `timestamps` in memory and, in one transaction, on disk.
+    pub fn insert_many(&self, timestamps: &HashMap<u64, u64>) -> Result<()> {
+        if timestamps.is_empty() {
+            return Ok(());
+        }
+        self.memory.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend(timestamps);
+
+        if let Some(disk) = &self.disk {
+            disk.with(true, |conn| {
+                let tx = conn.transaction()?;
+                {
+                    let mut stmt = tx.prepare_cached("INSERT OR REPLACE INTO block_timestamps (block_number, timestamp) VALUES (?1, ?2)")?;
+                    for (block, timestamp) in timestamps {
+                        stmt.execute(params![*block as i64, *timestamp as i64])?;
+                    }
+                }
+                tx.commit()?;
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "timestamps_test.rs"]
+mod timestamps_test;
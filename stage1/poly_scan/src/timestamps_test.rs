@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use crate::timestamps::{log_timestamps, TimestampCache, TimestampedLog};
+    use serde_json::json;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn test_disk_cache_survives_reopening() {
+        let path = std::env::temp_dir().join(format!("poly_scan_timestamps_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Lookups alone never create the file
+        let cache = TimestampCache::on_disk(&path, 137);
+        assert!(cache.get_many(&BTreeSet::from([100])).unwrap().is_empty());
+        assert!(!path.exists());
+        cache.insert_many(&HashMap::from([(100, 1_700_000_000), (101, 1_700_000_002)])).unwrap();
+        drop(cache);
+
+        let reopened = TimestampCache::on_disk(&path, 137);
+        let found = reopened.get_many(&BTreeSet::from([100, 101, 102])).unwrap();
+        assert_eq!(found, HashMap::from([(100, 1_700_000_000), (101, 1_700_000_002)]));
+
+        // Another chain's blocks never come from this file
+        let other_chain = TimestampCache::on_disk(&path, 80002);
+        let err = other_chain.get_many(&BTreeSet::from([100])).unwrap_err();
+        assert!(err.to_string().contains("chain 137, not 80002"), "{}", err);
+        assert!(other_chain.insert_many(&HashMap::from([(100, 1)])).is_err());
+        assert_eq!(reopened.get_many(&BTreeSet::from([100])).unwrap()[&100], 1_700_000_000);
+
+        // The in-memory cache starts empty and keeps what it is given
+        let memory = TimestampCache::in_memory();
+        assert!(memory.get_many(&BTreeSet::from([100])).unwrap().is_empty());
+        memory.insert_many(&HashMap::from([(100, 1)])).unwrap();
+        assert_eq!(memory.get_many(&BTreeSet::from([100])).unwrap()[&100], 1);
+
+        drop(reopened);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_block_timestamp_is_read_from_get_logs_when_present() {
+        let log = |block: &str, timestamp: Option<&str>| {
+            let mut log = json!({
+                "address": "0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e",
+                "topics": [],
+                "data": "0x",
+                "blockNumber": block,
+                "logIndex": "0x0",
+            });
+            if let Some(ts) = timestamp {
+                log["blockTimestamp"] = json!(ts);
+            }
+            log
+        };
+        let logs: Vec<TimestampedLog> = serde_json::from_value(json!([
+            log("0x64", Some("0x6553f100")),
+            log("0x65", None),
+        ])).unwrap();
+
+        assert_eq!(logs[0].log.block_number.unwrap().as_u64(), 100);
+        assert_eq!(log_timestamps(&logs), HashMap::from([(100, 1_700_000_000)]));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::consts::*;
+use crate::models::{MarketInfo, UmaEvent, UmaEventKind, UmaQuestion, UmaResolution, UmaStatus};
+use crate::utils::{decode_log_data, format_address, log_topics, token_address, token_uint, token_uint_array, uint_array_type};
+use anyhow::Result;
+use ethers::abi::{ParamType, Token};
+use ethers::types::{Address, Log, H256, I256, U256};
+use ethers::utils::keccak256;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// Addresses of every known UMA CTF Adapter.
+pub fn uma_adapter_addresses() -> Result<Vec<Address>> {
+    UMA_CTF_ADAPTER_ADDRESSES.iter()
+        .map(|a| Address::from_str(a).map_err(|e| anyhow::anyhow!("Invalid adapter address {}: {}", a, e)))
+        .collect()
+}
+
+/// Addresses of the Optimistic Oracles the adapters request prices from.
+pub fn optimistic_oracle_addresses() -> Result<Vec<Address>> {
+    UMA_OPTIMISTIC_ORACLE_ADDRESSES.iter()
+        .map(|a| Address::from_str(a).map_err(|e| anyhow::anyhow!("Invalid oracle address {}: {}", a, e)))
+        .collect()
+}
+
+pub fn is_uma_adapter(oracle: &str) -> bool {
+    let Ok(oracle) = Address::from_str(oracle) else { return false };
+    uma_adapter_addresses().is_ok_and(|adapters| adapters.contains(&oracle))
+}
+
+/// Decodes a `QuestionInitialized` log (the caller filters on topic0).
+pub fn decode_question_initialized(log: &Log) -> Result<UmaQuestion> {
+    let topics = log_topics(log, 3)?;
+    let data = decode_log_data(log, &[ParamType::Bytes, ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)])?;
+    let ancillary_data = match &data[0] {
+        Token::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        _ => return Err(anyhow::anyhow!("Malformed QuestionInitialized log")),
+    };
+    let parsed = AncillaryData::parse(&ancillary_data);
+
+    Ok(UmaQuestion {
+        question_id: format!("{:?}", topics[0]),
+        adapter: format_address(log.address),
+        request_timestamp: U256::from_big_endian(topics[1].as_bytes()).low_u64(),
+        creator: format_address(Address::from(topics[2])),
+        reward_token: format_address(token_address(&data[1])?),
+        reward: token_uint(&data[2])?.to_string(),
+        proposal_bond: token_uint(&data[3])?.to_string(),
+        title: parsed.title,
+        description: parsed.description,
+        resolution_data: parsed.resolution_data,
+        initializer: parsed.initializer,
+        ancillary_data,
+        block_number: log.block_number.map(|b| b.as_u64()),
+    })
+}
+
+/// Copies the human-readable fields of `question` onto `market`.
+pub fn apply_question(market: &mut MarketInfo, question: &UmaQuestion) {
+    market.title = question.title.clone();
+    market.description = question.description.clone();
+}
+
+/// topic0 of the adapter events that move a question through resolution.
+pub fn adapter_event_topics() -> Vec<H256> {
+    [
+        UMA_QUESTION_RESOLVED_EVENT_SIGNATURE,
+        UMA_QUESTION_EMERGENCY_RESOLVED_EVENT_SIGNATURE,
+        UMA_QUESTION_RESET_EVENT_SIGNATURE,
+        UMA_QUESTION_FLAGGED_EVENT_SIGNATURE,
+        UMA_QUESTION_PAUSED_EVENT_SIGNATURE,
+        UMA_QUESTION_UNPAUSED_EVENT_SIGNATURE,
+    ]
+    .iter()
+    .map(|sig| H256::from(keccak256(sig)))
+    .collect()
+}
+
+/// topic0 of the Optimistic Oracle proposal, dispute and settlement events.
+pub fn oracle_event_topics() -> Vec<H256> {
+    [UMA_PROPOSE_PRICE_EVENT_SIGNATURE, UMA_DISPUTE_PRICE_EVENT_SIGNATURE, UMA_SETTLE_EVENT_SIGNATURE]
+        .iter()
+        .map(|sig| H256::from(keccak256(sig)))
+        .collect()
+}
+
+/// Decodes an adapter lifecycle log or an Optimistic Oracle log. Ok(None) for logs of any other event.
+/// Oracle logs are keyed by the keccak256 of their ancillary data, which is the adapter's question ID.
+pub fn decode_uma_log(log: &Log) -> Result<Option<UmaEvent>> {
+    let Some(topic0) = log.topics.first() else { return Ok(None) };
+    let is = |sig: &str| *topic0 == H256::from(keccak256(sig));
+    let address = |topic: &H256| format_address(Address::from(*topic));
+
+    let (question_id, kind) = if is(UMA_QUESTION_RESOLVED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[uint_array_type()])?;
+        let kind = UmaEventKind::QuestionResolved {
+            settled_price: I256::from_raw(U256::from_big_endian(topics[1].as_bytes())).to_string(),
+            payouts: token_uint_array(&data[0])?.iter().map(U256::to_string).collect(),
+        };
+        (topics[0], kind)
+    } else if is(UMA_QUESTION_EMERGENCY_RESOLVED_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 1)?;
+        let data = decode_log_data(log, &[uint_array_type()])?;
+        let payouts = token_uint_array(&data[0])?.iter().map(U256::to_string).collect();
+        (topics[0], UmaEventKind::QuestionEmergencyResolved { payouts })
+    } else if is(UMA_QUESTION_RESET_EVENT_SIGNATURE) {
+        (log_topics(log, 1)?[0], UmaEventKind::QuestionReset)
+    } else if is(UMA_QUESTION_FLAGGED_EVENT_SIGNATURE) {
+        (log_topics(log, 1)?[0], UmaEventKind::QuestionFlagged)
+    } else if is(UMA_QUESTION_PAUSED_EVENT_SIGNATURE) {
+        (log_topics(log, 1)?[0], UmaEventKind::QuestionPaused)
+    } else if is(UMA_QUESTION_UNPAUSED_EVENT_SIGNATURE) {
+        (log_topics(log, 1)?[0], UmaEventKind::QuestionUnpaused)
+    } else if is(UMA_PROPOSE_PRICE_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 2)?;
+        let data = decode_log_data(log, &[
+            ParamType::FixedBytes(32), ParamType::Uint(256), ParamType::Bytes,
+            ParamType::Int(256), ParamType::Uint(256), ParamType::Address,
+        ])?;
+        let kind = UmaEventKind::ProposePrice {
+            proposer: address(&topics[1]),
+            request_timestamp: token_uint(&data[1])?.low_u64(),
+            proposed_price: token_int(&data[3])?.to_string(),
+            expiration_timestamp: token_uint(&data[4])?.low_u64(),
+        };
+        (ancillary_question_id(&data[2])?, kind)
+    } else if is(UMA_DISPUTE_PRICE_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[ParamType::FixedBytes(32), ParamType::Uint(256), ParamType::Bytes, ParamType::Int(256)])?;
+        let kind = UmaEventKind::DisputePrice {
+            proposer: address(&topics[1]),
+            disputer: address(&topics[2]),
+            request_timestamp: token_uint(&data[1])?.low_u64(),
+            proposed_price: token_int(&data[3])?.to_string(),
+        };
+        (ancillary_question_id(&data[2])?, kind)
+    } else if is(UMA_SETTLE_EVENT_SIGNATURE) {
+        let topics = log_topics(log, 3)?;
+        let data = decode_log_data(log, &[
+            ParamType::FixedBytes(32), ParamType::Uint(256), ParamType::Bytes,
+            ParamType::Int(256), ParamType::Uint(256),
+        ])?;
+        let kind = UmaEventKind::Settle {
+            proposer: address(&topics[1]),
+            disputer: address(&topics[2]),
+            request_timestamp: token_uint(&data[1])?.low_u64(),
+            price: token_int(&data[3])?.to_string(),
+            payout: token_uint(&data[4])?.to_string(),
+        };
+        (ancillary_question_id(&data[2])?, kind)
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(UmaEvent {
+        tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+        log_index: log.log_index.unwrap_or_default().as_u64(),
+        block_number: log.block_number.unwrap_or_default().as_u64(),
+        question_id: format!("{:?}", question_id),
+        kind,
+    }))
+}
+
+/// CTF payouts `[YES, NO]` the adapter reports for a YES_OR_NO_QUERY price. None for any other
+/// price (e.g. the "too early" sentinel), which makes the adapter reset the question instead.
+pub fn price_payouts(price: &str) -> Option<Vec<String>> {
+    let price = I256::from_dec_str(price).ok()?;
+    let yes = I256::from(UMA_PRICE_YES);
+    let payouts: [u8; 2] = if price.is_zero() {
+        [0, 1]
+    } else if price == yes {
+        [1, 0]
+    } else if price == yes / I256::from(2) {
+        [1, 1]
+    } else {
+        return None;
+    };
+    Some(payouts.iter().map(u8::to_string).collect())
+}
+
+/// Folds questions and their events (any order) into one resolution state per question ID.
+/// A reset supersedes the active oracle request: the old request keeps its ancillary data (and so
+/// its question ID) and may still be disputed or settled through the DVM, but those events no
+/// longer decide the question.
+pub fn resolutions(questions: &[UmaQuestion], events: &[UmaEvent]) -> Vec<UmaResolution> {
+    let mut states: BTreeMap<String, UmaResolution> = BTreeMap::new();
+    // Question ID -> request timestamps replaced by a reset
+    let mut superseded: HashMap<String, HashSet<u64>> = HashMap::new();
+    for question in questions {
+        let state = resolution_entry(&mut states, &question.question_id);
+        state.title = question.title.clone();
+        state.request_timestamp = Some(question.request_timestamp);
+        state.adapter = Some(question.adapter.clone());
+        state.last_block = state.last_block.max(question.block_number);
+    }
+
+    let mut ordered: Vec<&UmaEvent> = events.iter().collect();
+    ordered.sort_by_key(|e| (e.block_number, e.log_index));
+    for event in ordered {
+        let stale = superseded.entry(event.question_id.clone()).or_default();
+        let request = match &event.kind {
+            UmaEventKind::ProposePrice { request_timestamp, .. }
+            | UmaEventKind::DisputePrice { request_timestamp, .. }
+            | UmaEventKind::Settle { request_timestamp, .. } => Some(*request_timestamp),
+            _ => None,
+        };
+        if request.is_some_and(|ts| stale.contains(&ts)) {
+            continue;
+        }
+
+        let state = resolution_entry(&mut states, &event.question_id);
+        state.last_block = Some(event.block_number);
+        match &event.kind {
+            UmaEventKind::ProposePrice { proposer, proposed_price, expiration_timestamp, request_timestamp } => {
+                state.request_timestamp = Some(*request_timestamp);
+                state.status = UmaStatus::Proposed;
+                state.proposer = Some(proposer.clone());
+                state.proposed_price = Some(proposed_price.clone());
+                state.proposed_payouts = price_payouts(proposed_price);
+                state.expiration_timestamp = Some(*expiration_timestamp);
+            }
+            UmaEventKind::DisputePrice { .. } => {
+                state.status = UmaStatus::Disputed;
+                state.disputes += 1;
+            }
+            UmaEventKind::QuestionReset => {
+                // A fresh request replaces the disputed one
+                stale.extend(state.request_timestamp.take());
+                state.status = UmaStatus::Reset;
+                state.proposer = None;
+                state.proposed_price = None;
+                state.proposed_payouts = None;
+                state.expiration_timestamp = None;
+            }
+            UmaEventKind::Settle { price, .. } => {
+                state.status = UmaStatus::Settled;
+                state.settled_price = Some(price.clone());
+                state.payouts = price_payouts(price);
+            }
+            UmaEventKind::QuestionResolved { settled_price, payouts } => {
+                state.status = UmaStatus::Settled;
+                state.settled_price = Some(settled_price.clone());
+                state.payouts = Some(payouts.clone());
+            }
+            UmaEventKind::QuestionEmergencyResolved { payouts } => {
+                state.status = UmaStatus::Settled;
+                state.payouts = Some(payouts.clone());
+            }
+            UmaEventKind::QuestionFlagged => state.flagged = true,
+            UmaEventKind::QuestionPaused => state.paused = true,
+            UmaEventKind::QuestionUnpaused => state.paused = false,
+        }
+    }
+    states.into_values().collect()
+}
+
+fn resolution_entry<'a>(states: &'a mut BTreeMap<String, UmaResolution>, question_id: &str) -> &'a mut UmaResolution {
+    states.entry(question_id.to_string()).or_insert_with(|| UmaResolution {
+        question_id: question_id.to_string(),
+        title: None,
+        adapter: None,
+        status: UmaStatus::Unproposed,
+        disputes: 0,
+        flagged: false,
+        paused: false,
+        request_timestamp: None,
+        proposer: None,
+        proposed_price: None,
+        proposed_payouts: None,
+        expiration_timestamp: None,
+        settled_price: None,
+        payouts: None,
+        last_block: None,
+    })
+}
+
+fn token_int(token: &Token) -> Result<I256> {
+    token.clone().into_int().map(I256::from_raw).ok_or_else(|| anyhow::anyhow!("Expected int in log data"))
+}
+
+fn ancillary_question_id(token: &Token) -> Result<H256> {
+    match token {
+        Token::Bytes(bytes) => Ok(H256::from(keccak256(bytes))),
+        _ => Err(anyhow::anyhow!("Expected ancillary data bytes in log data")),
+    }
+}
+
+/// Fields of Polymarket's UMA ancillary data:
+/// `q: title: <title>, description: <description> res_data: <p1..p3 mapping>,initializer:<hex address>`.
+/// Missing markers leave the corresponding field empty; the raw text is always kept by the caller.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AncillaryData {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub resolution_data: Option<String>,
+    pub initializer: Option<String>,
+}
+
+impl AncillaryData {
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+
+        // Trailing `,initializer:<address>` is appended by the adapter
+        let (body, initializer) = match text.rfind(",initializer:") {
+            Some(pos) => (&text[..pos], Some(format!("0x{}", text[pos + ",initializer:".len()..].trim().trim_start_matches("0x")))),
+            None => (text, None),
+        };
+
+        let (body, resolution_data) = match body.rfind("res_data:") {
+            Some(pos) => (&body[..pos], non_empty(body[pos + "res_data:".len()..].trim())),
+            None => (body, None),
+        };
+
+        let body = body.trim().strip_prefix("q:").unwrap_or(body).trim();
+        let body = body.strip_prefix("title:").map(str::trim);
+
+        let (title, description) = match body {
+            Some(rest) => match rest.find("description:") {
+                Some(pos) => (
+                    non_empty(rest[..pos].trim().trim_end_matches(',').trim()),
+                    non_empty(rest[pos + "description:".len()..].trim()),
+                ),
+                None => (non_empty(rest.trim_end_matches(',').trim()), None),
+            },
+            None => (None, None),
+        };
+
+        Self { title, description, resolution_data, initializer }
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+#[cfg(test)]
+#[path = "uma_test.rs"]
+mod uma_test;
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use crate::consts::*;
+    use crate::models::{UmaEventKind, UmaStatus};
+    use crate::uma::{decode_question_initialized, decode_uma_log, is_uma_adapter, price_payouts, resolutions, AncillaryData};
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, Bytes, Log, H256, I256, U256, U64};
+    use ethers::utils::keccak256;
+    use std::str::FromStr;
+
+    const ANCILLARY: &str = "q: title: Will Bitcoin reach $100k in 2024?, description: This market will resolve to \"Yes\" if Bitcoin trades above $100,000 on Binance before Dec 31, 2024. Otherwise \"No\". res_data: p1: 0, p2: 1, p3: 0.5. Where p1 corresponds to No, p2 to a Yes, p3 to unknown/50-50,initializer:91430cad2d3975766499717fa0d66a78d814e5c5";
+
+    #[test]
+    fn test_parse_ancillary_data() {
+        let parsed = AncillaryData::parse(ANCILLARY);
+        assert_eq!(parsed.title.as_deref(), Some("Will Bitcoin reach $100k in 2024?"));
+        assert_eq!(
+            parsed.description.as_deref(),
+            Some("This market will resolve to \"Yes\" if Bitcoin trades above $100,000 on Binance before Dec 31, 2024. Otherwise \"No\"."),
+        );
+        assert_eq!(parsed.resolution_data.as_deref(), Some("p1: 0, p2: 1, p3: 0.5. Where p1 corresponds to No, p2 to a Yes, p3 to unknown/50-50"));
+        assert_eq!(parsed.initializer.as_deref(), Some("0x91430cad2d3975766499717fa0d66a78d814e5c5"));
+
+        // Free-form text without the markers is left untitled
+        assert_eq!(AncillaryData::parse("just some text"), AncillaryData::default());
+    }
+
+    #[test]
+    fn test_decode_question_initialized() {
+        let adapter = Address::from_str(UMA_CTF_ADAPTER_ADDRESSES[0]).unwrap();
+        let usdc = Address::from_str(USDC_ADDRESS).unwrap();
+        let log = Log {
+            address: adapter,
+            topics: vec![
+                H256::from(keccak256(UMA_QUESTION_INITIALIZED_EVENT_SIGNATURE)),
+                H256::repeat_byte(0x0b),
+                H256::from_low_u64_be(1_700_000_000),
+                H256::from(Address::repeat_byte(0x33)),
+            ],
+            data: Bytes::from(encode(&[
+                Token::Bytes(ANCILLARY.as_bytes().to_vec()),
+                Token::Address(usdc),
+                Token::Uint(U256::from(5_000_000)),
+                Token::Uint(U256::from(500_000_000)),
+            ])),
+            block_number: Some(U64::from(300u64)),
+            ..Default::default()
+        };
+
+        let question = decode_question_initialized(&log).unwrap();
+        assert_eq!(question.question_id, format!("{:?}", H256::repeat_byte(0x0b)));
+        assert_eq!(question.request_timestamp, 1_700_000_000);
+        assert_eq!(question.creator, format!("{:?}", Address::repeat_byte(0x33)));
+        assert_eq!(question.reward, "5000000");
+        assert_eq!(question.proposal_bond, "500000000");
+        assert_eq!(question.title.as_deref(), Some("Will Bitcoin reach $100k in 2024?"));
+        assert_eq!(question.ancillary_data, ANCILLARY);
+        assert_eq!(question.block_number, Some(300));
+
+        assert!(is_uma_adapter(&question.adapter));
+        assert!(!is_uma_adapter(&format!("{:?}", usdc)));
+    }
+
+    fn uma_log(address: &str, signature: &str, topics: Vec<H256>, data: Vec<Token>, block: u64, log_index: u64) -> Log {
+        let mut all_topics = vec![H256::from(keccak256(signature))];
+        all_topics.extend(topics);
+        Log {
+            address: Address::from_str(address).unwrap(),
+            topics: all_topics,
+            data: Bytes::from(encode(&data)),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            log_index: Some(U256::from(log_index)),
+            block_number: Some(U64::from(block)),
+            ..Default::default()
+        }
+    }
+
+    /// Request timestamp of the adapter's first price request, and of the one a reset makes
+    const FIRST_REQUEST: u64 = 1_700_000_000;
+    const RESET_REQUEST: u64 = 1_700_003_600;
+
+    /// ProposePrice / DisputePrice / Settle share the (identifier, timestamp, ancillaryData, price) prefix
+    fn oracle_log(signature: &str, indexed: usize, request: u64, price: i64, extra: Vec<Token>, block: u64) -> Log {
+        let requester = H256::from(Address::from_str(UMA_CTF_ADAPTER_ADDRESSES[0]).unwrap());
+        let accounts = [requester, H256::from(Address::repeat_byte(0x44)), H256::from(Address::repeat_byte(0x55))];
+        let mut data = vec![
+            Token::FixedBytes(b"YES_OR_NO_QUERY".to_vec().into_iter().chain(std::iter::repeat(0)).take(32).collect()),
+            Token::Uint(U256::from(request)),
+            Token::Bytes(ANCILLARY.as_bytes().to_vec()),
+            Token::Int(I256::from(price).into_raw()),
+        ];
+        data.extend(extra);
+        uma_log(UMA_OPTIMISTIC_ORACLE_ADDRESSES[0], signature, accounts[..indexed].to_vec(), data, block, 0)
+    }
+
+    #[test]
+    fn test_price_payouts() {
+        assert_eq!(price_payouts("1000000000000000000"), Some(vec!["1".to_string(), "0".to_string()]));
+        assert_eq!(price_payouts("0"), Some(vec!["0".to_string(), "1".to_string()]));
+        assert_eq!(price_payouts("500000000000000000"), Some(vec!["1".to_string(), "1".to_string()]));
+        // "Too early" sentinel (type(int256).min) has no payout
+        assert_eq!(price_payouts(&I256::MIN.to_string()), None);
+    }
+
+    #[test]
+    fn test_dispute_reset_and_settlement_fold_into_status() {
+        let adapter = UMA_CTF_ADAPTER_ADDRESSES[0];
+        let question_id = H256::from(keccak256(ANCILLARY.as_bytes()));
+        let yes = 1_000_000_000_000_000_000i64;
+
+        let logs = [
+            // First proposal (NO) is disputed; the adapter resets the question in the same transaction
+            oracle_log(UMA_PROPOSE_PRICE_EVENT_SIGNATURE, 2, FIRST_REQUEST, 0, vec![Token::Uint(U256::from(1_700_007_200)), Token::Address(Address::zero())], 100),
+            oracle_log(UMA_DISPUTE_PRICE_EVENT_SIGNATURE, 3, FIRST_REQUEST, 0, vec![], 110),
+            uma_log(adapter, UMA_QUESTION_RESET_EVENT_SIGNATURE, vec![question_id], vec![], 110, 1),
+            oracle_log(UMA_PROPOSE_PRICE_EVENT_SIGNATURE, 2, RESET_REQUEST, yes, vec![Token::Uint(U256::from(1_700_014_400)), Token::Address(Address::zero())], 120),
+        ];
+        let mut events: Vec<_> = logs.iter().map(|log| decode_uma_log(log).unwrap().unwrap()).collect();
+        assert!(events.iter().all(|e| e.question_id == format!("{:?}", question_id)));
+        match &events[1].kind {
+            UmaEventKind::DisputePrice { disputer, proposed_price, .. } => {
+                assert_eq!(disputer, &format!("{:?}", Address::repeat_byte(0x55)));
+                assert_eq!(proposed_price, "0");
+            }
+            other => panic!("Expected dispute, got {:?}", other),
+        }
+
+        let state = &resolutions(&[], &events)[0];
+        assert_eq!(state.status, UmaStatus::Proposed);
+        assert_eq!(state.disputes, 1);
+        assert_eq!(state.proposed_payouts, Some(vec!["1".to_string(), "0".to_string()]));
+        assert_eq!(state.expiration_timestamp, Some(1_700_014_400));
+        assert_eq!(state.request_timestamp, Some(RESET_REQUEST));
+
+        // Settlement and the adapter's resolution land in one transaction
+        let settle = oracle_log(UMA_SETTLE_EVENT_SIGNATURE, 3, RESET_REQUEST, yes, vec![Token::Uint(U256::from(5_000_000))], 130);
+        let mut settled_price = [0u8; 32];
+        I256::from(yes).into_raw().to_big_endian(&mut settled_price);
+        let resolved = uma_log(
+            adapter, UMA_QUESTION_RESOLVED_EVENT_SIGNATURE, vec![question_id, H256::from(settled_price)],
+            vec![Token::Array(vec![Token::Uint(U256::one()), Token::Uint(U256::zero())])], 130, 1,
+        );
+        events.push(decode_uma_log(&settle).unwrap().unwrap());
+        events.push(decode_uma_log(&resolved).unwrap().unwrap());
+
+        let state = &resolutions(&[], &events)[0];
+        assert_eq!(state.status, UmaStatus::Settled);
+        assert_eq!(state.settled_price.as_deref(), Some("1000000000000000000"));
+        assert_eq!(state.payouts, Some(vec!["1".to_string(), "0".to_string()]));
+        assert_eq!(state.last_block, Some(130));
+    }
+
+    #[test]
+    fn test_settling_a_superseded_request_does_not_resolve_the_question() {
+        let adapter = UMA_CTF_ADAPTER_ADDRESSES[0];
+        let question_id = H256::from(keccak256(ANCILLARY.as_bytes()));
+        let yes = 1_000_000_000_000_000_000i64;
+        let proposal = |request, price, block| oracle_log(
+            UMA_PROPOSE_PRICE_EVENT_SIGNATURE, 2, request, price,
+            vec![Token::Uint(U256::from(request + 7_200)), Token::Address(Address::zero())], block,
+        );
+
+        let logs = [
+            proposal(FIRST_REQUEST, 0, 100),
+            oracle_log(UMA_DISPUTE_PRICE_EVENT_SIGNATURE, 3, FIRST_REQUEST, 0, vec![], 110),
+            uma_log(adapter, UMA_QUESTION_RESET_EVENT_SIGNATURE, vec![question_id], vec![], 110, 1),
+            proposal(RESET_REQUEST, yes, 120),
+            // The DVM settles the disputed request for NO long after the reset
+            oracle_log(UMA_SETTLE_EVENT_SIGNATURE, 3, FIRST_REQUEST, 0, vec![Token::Uint(U256::from(5_000_000))], 200),
+        ];
+        let mut events: Vec<_> = logs.iter().map(|log| decode_uma_log(log).unwrap().unwrap()).collect();
+
+        let state = &resolutions(&[], &events)[0];
+        assert_eq!(state.status, UmaStatus::Proposed);
+        assert_eq!(state.request_timestamp, Some(RESET_REQUEST));
+        assert_eq!(state.proposed_payouts, Some(vec!["1".to_string(), "0".to_string()]));
+        assert_eq!((state.settled_price.as_deref(), state.payouts.as_ref()), (None, None));
+        assert_eq!(state.last_block, Some(120));
+
+        // The active request settling does resolve it
+        let settle = oracle_log(UMA_SETTLE_EVENT_SIGNATURE, 3, RESET_REQUEST, yes, vec![Token::Uint(U256::from(5_000_000))], 210);
+        events.push(decode_uma_log(&settle).unwrap().unwrap());
+        let state = &resolutions(&[], &events)[0];
+        assert_eq!(state.status, UmaStatus::Settled);
+        assert_eq!(state.payouts, Some(vec!["1".to_string(), "0".to_string()]));
+    }
+}
@@ -1,6 +1,11 @@
-use ethers::types::{U256, Address, H256};
-use ethers::utils::{format_units, keccak256};
-use ethers::abi::{encode, Token};
+use crate::amount::Amount;
+use crate::consts::{NEG_RISK_ADAPTER_ADDRESS, NEG_RISK_WRAPPED_COLLATERAL_ADDRESS, USDC_ADDRESS};
+use crate::models::{FeeTotals, MarketInfo, OutcomePosition, TradeOutput};
+use anyhow::Result;
+use ethers::types::{U256, Address, H256, Log};
+use ethers::utils::keccak256;
+use ethers::abi::{decode, encode, ParamType, Token};
+use std::str::FromStr;
 
 pub fn format_address(addr: Address) -> String {
     format!("{:?}", addr)
@@ -10,21 +15,90 @@ pub fn u256_to_string(val: U256) -> String {
     val.to_string()
 }
 
+/// USDC per outcome token, exact up to `scale` decimals (truncated beyond).
 pub fn calculate_price(
     maker_amount: U256,
     maker_decimals: u32,
     taker_amount: U256,
     taker_decimals: u32,
-) -> String {
-    let maker_f = format_units(maker_amount, maker_decimals).unwrap_or_default().parse::<f64>().unwrap_or(0.0);
-    let taker_f = format_units(taker_amount, taker_decimals).unwrap_or_default().parse::<f64>().unwrap_or(0.0);
+    scale: u32,
+) -> Amount {
+    Amount::ratio(
+        Amount::new(maker_amount, maker_decimals),
+        Amount::new(taker_amount, taker_decimals),
+        scale,
+    )
+    .unwrap_or_else(|| Amount::zero(scale))
+}
+
+/// `fee / notional` in basis points, to 2 decimals. Zero when there is no notional.
+pub fn calculate_fee_bps(fee: Amount, notional: Amount) -> Amount {
+    fee.checked_mul(Amount::new(U256::from(10_000u64), 0), fee.decimals())
+        .and_then(|scaled| Amount::ratio(scaled, notional, 2))
+        .unwrap_or_else(|| Amount::zero(2))
+}
 
-    if taker_f == 0.0 {
-        return "0.0".to_string();
+/// Sums notional and fees over `trades`, skipping taker-order summaries so each match's volume
+/// is counted once. Fills with no USDC leg count towards `fills` only.
+pub fn fee_totals(trades: &[TradeOutput]) -> FeeTotals {
+    let mut notional_usdc = Amount::zero(6);
+    let mut fee_usdc = Amount::zero(6);
+    let mut fills = 0;
+    for trade in trades.iter().filter(|t| !t.is_taker_summary()) {
+        fills += 1;
+        if let Some(notional) = usdc_notional(trade) {
+            notional_usdc = notional_usdc.checked_add(notional).unwrap_or(notional_usdc);
+            fee_usdc = fee_usdc.checked_add(trade.fee_usdc).unwrap_or(fee_usdc);
+        }
     }
+    FeeTotals {
+        fills,
+        notional_usdc,
+        fee_usdc,
+        fee_bps: calculate_fee_bps(fee_usdc, notional_usdc),
+    }
+}
+
+/// USDC leg of a fill (None when neither asset is collateral).
+pub fn usdc_notional(trade: &TradeOutput) -> Option<Amount> {
+    let (raw, decimals) = if trade.maker_asset_id == "0" {
+        (&trade.maker_amount_filled, trade.maker_decimals)
+    } else if trade.taker_asset_id == "0" {
+        (&trade.taker_amount_filled, trade.taker_decimals)
+    } else {
+        return None;
+    };
+    Some(Amount::new(U256::from_dec_str(raw).ok()?, decimals))
+}
+
+/// The `count` indexed topics after topic0.
+pub fn log_topics(log: &Log, count: usize) -> Result<&[H256]> {
+    log.topics.get(1..=count).ok_or_else(|| anyhow::anyhow!("Missing indexed topics on log"))
+}
+
+/// ABI-decodes the non-indexed fields of `log`.
+pub fn decode_log_data(log: &Log, types: &[ParamType]) -> Result<Vec<Token>> {
+    decode(types, &log.data).map_err(|e| anyhow::anyhow!("Failed to decode log data: {}", e))
+}
 
-    let price = maker_f / taker_f;
-    format!("{:.6}", price)
+pub fn uint_array_type() -> ParamType {
+    ParamType::Array(Box::new(ParamType::Uint(256)))
+}
+
+pub fn token_address(token: &Token) -> Result<Address> {
+    token.clone().into_address().ok_or_else(|| anyhow::anyhow!("Expected address in log data"))
+}
+
+pub fn token_uint(token: &Token) -> Result<U256> {
+    token.clone().into_uint().ok_or_else(|| anyhow::anyhow!("Expected uint in log data"))
+}
+
+pub fn token_uint_array(token: &Token) -> Result<Vec<U256>> {
+    token.clone().into_array()
+        .ok_or_else(|| anyhow::anyhow!("Expected uint[] in log data"))?
+        .iter()
+        .map(token_uint)
+        .collect()
 }
 
 pub fn truncate_str(s: &str, start_chars: usize, end_chars: usize) -> String {
@@ -34,15 +108,8 @@ pub fn truncate_str(s: &str, start_chars: usize, end_chars: usize) -> String {
     format!("{}...{}", &s[..start_chars], &s[s.len() - end_chars..])
 }
 
-pub fn format_token_amount(amount: f64) -> String {
-    if amount == 0.0 {
-        return "0.0".to_string();
-    }
-    if amount < 0.0001 {
-        format!("{:.2e}", amount)
-    } else {
-        format!("{:.4}", amount)
-    }
+pub fn format_token_amount(amount: Amount) -> String {
+    amount.to_trimmed_string()
 }
 
 pub fn get_condition_id(
@@ -81,3 +148,101 @@ pub fn get_position_id(
     ]);
     H256::from(keccak256(&encoded))
 }
+
+/// Normalizes a hex or decimal token ID to the `0x{:x}` form used by `TradeOutput`.
+pub fn canonical_token_id(token_id: &str) -> Option<String> {
+    parse_token_id(token_id).map(|value| format!("0x{:x}", value))
+}
+
+/// Top-level position (parent collection 0) for `index_set` of `condition_id`.
+pub fn outcome_position(collateral_token: Address, condition_id: H256, index_set: u64) -> OutcomePosition {
+    let collection_id = get_collection_id(H256::zero(), condition_id, U256::from(index_set));
+    OutcomePosition {
+        index_set,
+        collection_id: format!("{:?}", collection_id),
+        position_id: format!("0x{:x}", get_position_id(collateral_token, collection_id)),
+    }
+}
+
+/// True when `oracle` is the NegRiskAdapter, i.e. the condition belongs to a neg-risk market.
+pub fn is_neg_risk_oracle(oracle: Address) -> bool {
+    Address::from_str(NEG_RISK_ADAPTER_ADDRESS).is_ok_and(|adapter| adapter == oracle)
+}
+
+/// `MarketInfo` with one position per outcome slot. Block fields are left empty for the caller.
+///
+/// Neg-risk conditions (`neg_risk`, or the NegRiskAdapter as oracle) derive positions from the
+/// adapter's WrappedCollateral; everything else uses USDC.e.
+pub fn derive_market_info(
+    condition_id: H256,
+    question_id: H256,
+    oracle: Address,
+    outcome_slot_count: u64,
+    neg_risk: bool,
+) -> Result<MarketInfo> {
+    // Index sets are u64 bitmasks; the CTF itself requires at least two slots
+    if !(2..=64).contains(&outcome_slot_count) {
+        return Err(anyhow::anyhow!("Unsupported outcome slot count: {}", outcome_slot_count));
+    }
+    let neg_risk = neg_risk || is_neg_risk_oracle(oracle);
+    let (collateral_token, adapter) = if neg_risk {
+        (Address::from_str(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS)?, Some(format_address(Address::from_str(NEG_RISK_ADAPTER_ADDRESS)?)))
+    } else {
+        (Address::from_str(USDC_ADDRESS)?, None)
+    };
+    let outcomes = (0..outcome_slot_count)
+        .map(|slot| outcome_position(collateral_token, condition_id, 1u64 << slot))
+        .collect();
+
+    Ok(MarketInfo {
+        condition_id: format!("{:?}", condition_id),
+        question_id: format!("{:?}", question_id),
+        title: None,
+        description: None,
+        oracle: format_address(oracle),
+        outcome_slot_count,
+        collateral_token: format_address(collateral_token),
+        neg_risk,
+        adapter,
+        outcomes,
+        combined_positions: Vec::new(),
+        block_number: None,
+        block_hash: None,
+        timestamp: None,
+    })
+}
+
+/// Adds positions for unions of outcome slots. Each set must be a proper, non-singleton subset
+/// of the condition's slots (the full set is just the collateral).
+pub fn add_combined_positions(market: &mut MarketInfo, index_sets: &[u64]) -> Result<()> {
+    let full_set = if market.outcome_slot_count >= 64 { u64::MAX } else { (1u64 << market.outcome_slot_count) - 1 };
+    let condition_id = H256::from_str(&market.condition_id).map_err(|_| anyhow::anyhow!("Invalid condition ID"))?;
+    let collateral_token = Address::from_str(&market.collateral_token).map_err(|_| anyhow::anyhow!("Invalid collateral token"))?;
+
+    for &index_set in index_sets {
+        if index_set == 0 || index_set & !full_set != 0 || index_set == full_set {
+            return Err(anyhow::anyhow!("Index set {} is not a proper subset of {} outcome slots", index_set, market.outcome_slot_count));
+        }
+        if index_set.is_power_of_two() || market.combined_positions.iter().any(|p| p.index_set == index_set) {
+            continue; // Already a single outcome, or already added
+        }
+        market.combined_positions.push(outcome_position(collateral_token, condition_id, index_set));
+    }
+    Ok(())
+}
+
+/// Parses a token ID given as `0x` hex or decimal.
+pub fn parse_token_id(token_id: &str) -> Option<U256> {
+    if let Some(hex) = token_id.strip_prefix("0x") {
+        U256::from_str_radix(hex, 16).ok()
+    } else {
+        U256::from_dec_str(token_id).ok()
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS` (UTC) for a unix timestamp.
+pub fn format_timestamp(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}